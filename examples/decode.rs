@@ -25,7 +25,7 @@ fn main() {
     decode_post_request();
     println!();
 
-    // Example 3: Decode using optimized uninit headers (Request only)
+    // Example 3: Decode using optimized uninit headers
     println!("3. Decoding with uninitialized headers (optimized):");
     println!("---------------------------------------------------");
     decode_request_optimized();
@@ -144,8 +144,16 @@ fn decode_chunked_request() {
             println!("  Total length (including chunks): {} bytes", total_len);
             println!();
             println!("  Note: request.body contains the raw chunked data including");
-            println!("        chunk size markers. Use a chunked decoder to extract");
-            println!("        the actual content if needed.");
+            println!("        chunk size markers. Use decode_dechunked() to get the");
+            println!("        actual content.");
+        }
+        Err(e) => eprintln!("✗ Error: {:?}", e),
+    }
+
+    let mut headers = [httparse::EMPTY_HEADER; 16];
+    match FullRequest::decode_dechunked(raw, &mut headers) {
+        Ok((_, body, _)) => {
+            println!("  Dechunked body: {:?}", String::from_utf8_lossy(&body));
         }
         Err(e) => eprintln!("✗ Error: {:?}", e),
     }
@@ -185,8 +193,8 @@ fn decode_responses() {
 
     println!();
 
-    // Example 3: 204 No Content (no body)
-    let raw = b"HTTP/1.1 204 No Content\r\nServer: nginx\r\n\r\n";
+    // Example 3: 204 No Content (no body, even with a stray Content-Length)
+    let raw = b"HTTP/1.1 204 No Content\r\nServer: nginx\r\nContent-Length: 12\r\n\r\n";
     let mut headers = [httparse::EMPTY_HEADER; 16];
 
     match FullResponse::decode(raw, &mut headers) {
@@ -203,10 +211,18 @@ fn decode_responses() {
 
     println!();
 
-    // Note about decode_uninit for responses
-    println!("  Note: FullResponse does NOT support decode_uninit() because");
-    println!("        httparse::Response lacks parse_with_uninit_headers method.");
-    println!("        Attempting to use it will panic with a clear error message.");
+    // Example 4: Decode using optimized uninit headers
+    let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+    let mut headers = [const { MaybeUninit::uninit() }; 16];
+
+    match FullResponse::decode_uninit(raw, &mut headers) {
+        Ok((response, _)) => {
+            println!("✓ Successfully decoded with uninit headers:");
+            println!("  Status: {}", response.head.code.unwrap());
+            println!("  Body: {}", String::from_utf8_lossy(response.body));
+        }
+        Err(e) => eprintln!("✗ Error: {:?}", e),
+    }
 }
 
 fn handle_incomplete_messages() {
@@ -118,6 +118,72 @@ mod tests {
         println!("Sync encoding 1000 requests: {:?}", duration);
     }
 
+    #[test]
+    fn benchmark_sync_encoding_runtime_per_call() {
+        // Previous behavior of `encode()`: a fresh runtime for every message
+        let start = std::time::Instant::now();
+
+        for _ in 0..1000 {
+            let request = Request::builder()
+                .method("GET")
+                .uri("/test")
+                .header("Host", "example.com")
+                .body(Full::new(Bytes::from("test")))
+                .unwrap();
+
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            let _ = rt.block_on(request.encode_async()).unwrap();
+        }
+
+        let per_call = start.elapsed();
+
+        let start = std::time::Instant::now();
+
+        for _ in 0..1000 {
+            let request = Request::builder()
+                .method("GET")
+                .uri("/test")
+                .header("Host", "example.com")
+                .body(Full::new(Bytes::from("test")))
+                .unwrap();
+
+            let _ = request.encode().unwrap();
+        }
+
+        let shared = start.elapsed();
+        println!(
+            "Sync encoding 1000 requests: {:?} with a runtime per call, {:?} with the shared runtime",
+            per_call, shared
+        );
+    }
+
+    #[test]
+    fn benchmark_sync_encoding_into_reused_buffer() {
+        let mut buf = bytes::BytesMut::with_capacity(4096);
+        let start = std::time::Instant::now();
+
+        for _ in 0..1000 {
+            let request = Request::builder()
+                .method("GET")
+                .uri("/test")
+                .header("Host", "example.com")
+                .body(Full::new(Bytes::from("test")))
+                .unwrap();
+
+            buf.clear();
+            let _ = request.encode_into(&mut buf).unwrap();
+        }
+
+        let duration = start.elapsed();
+        println!(
+            "Sync encoding 1000 requests into one buffer: {:?}",
+            duration
+        );
+    }
+
     #[tokio::test]
     async fn benchmark_async_encoding() {
         let start = std::time::Instant::now();
@@ -0,0 +1,261 @@
+//! Typed `Cache-Control` directives.
+//!
+//! This module provides [`CacheControl`], the parsed form of the `Cache-Control`
+//! header (RFC 9111 §5.2) for both requests and responses.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::util::combine_header_values;
+use httparse::Header;
+
+/// Delta-seconds values larger than this are clamped to it (RFC 9111 §1.2.2).
+const MAX_DELTA_SECONDS: u64 = 1 << 31;
+
+/// Parsed `Cache-Control` directives.
+///
+/// Directive names are matched case-insensitively. When a directive appears more than
+/// once, the first occurrence wins (RFC 9111 §4.2.1). Numeric arguments that are not
+/// valid delta-seconds leave the field as `None`; values beyond 2³¹ are clamped.
+///
+/// The same struct holds request and response directives; fields that don't apply to
+/// a message type simply stay unset.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "std")] {
+/// use http_wire::WireDecode;
+/// use http_wire::response::FullResponse;
+///
+/// let raw = b"HTTP/1.1 200 OK\r\nCache-Control: private, max-age=3600\r\nContent-Length: 0\r\n\r\n";
+/// let mut headers = [httparse::EMPTY_HEADER; 16];
+/// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+///
+/// let cc = response.cache_control();
+/// assert!(cc.private);
+/// assert_eq!(cc.max_age, Some(3600));
+/// assert!(!cc.no_store);
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    /// `max-age=N` (request and response).
+    pub max_age: Option<u64>,
+    /// `s-maxage=N` (response).
+    pub s_maxage: Option<u64>,
+    /// `max-stale[=N]` (request). `Some(None)` accepts a response of any staleness.
+    pub max_stale: Option<Option<u64>>,
+    /// `min-fresh=N` (request).
+    pub min_fresh: Option<u64>,
+    /// `stale-while-revalidate=N` (response, RFC 5861).
+    pub stale_while_revalidate: Option<u64>,
+    /// `stale-if-error=N` (request and response, RFC 5861).
+    pub stale_if_error: Option<u64>,
+    /// `no-cache`, with or without a field-name list.
+    pub no_cache: bool,
+    /// `no-store`.
+    pub no_store: bool,
+    /// `no-transform`.
+    pub no_transform: bool,
+    /// `only-if-cached` (request).
+    pub only_if_cached: bool,
+    /// `must-revalidate` (response).
+    pub must_revalidate: bool,
+    /// `proxy-revalidate` (response).
+    pub proxy_revalidate: bool,
+    /// `must-understand` (response).
+    pub must_understand: bool,
+    /// `public` (response).
+    pub public: bool,
+    /// `private`, with or without a field-name list (response).
+    pub private: bool,
+    /// `immutable` (response, RFC 8246).
+    pub immutable: bool,
+    /// Unrecognized directives as lowercase names with their unquoted arguments.
+    pub extensions: Vec<(String, Option<String>)>,
+}
+
+impl CacheControl {
+    /// Parses a single `Cache-Control` field value.
+    pub fn parse(value: &[u8]) -> Self {
+        let mut cc = Self::default();
+        let mut seen: Vec<String> = Vec::new();
+
+        for directive in split_directives(value) {
+            let (name, arg) = match directive.iter().position(|&b| b == b'=') {
+                Some(eq) => (&directive[..eq], Some(unquote(&directive[eq + 1..]))),
+                None => (directive, None),
+            };
+            let name = String::from_utf8_lossy(name.trim_ascii()).to_ascii_lowercase();
+            if name.is_empty() || seen.contains(&name) {
+                continue;
+            }
+            seen.push(name.clone());
+
+            let seconds = arg.as_deref().and_then(parse_delta_seconds);
+            match name.as_str() {
+                "max-age" => cc.max_age = seconds,
+                "s-maxage" => cc.s_maxage = seconds,
+                "max-stale" => cc.max_stale = Some(seconds),
+                "min-fresh" => cc.min_fresh = seconds,
+                "stale-while-revalidate" => cc.stale_while_revalidate = seconds,
+                "stale-if-error" => cc.stale_if_error = seconds,
+                "no-cache" => cc.no_cache = true,
+                "no-store" => cc.no_store = true,
+                "no-transform" => cc.no_transform = true,
+                "only-if-cached" => cc.only_if_cached = true,
+                "must-revalidate" => cc.must_revalidate = true,
+                "proxy-revalidate" => cc.proxy_revalidate = true,
+                "must-understand" => cc.must_understand = true,
+                "public" => cc.public = true,
+                "private" => cc.private = true,
+                "immutable" => cc.immutable = true,
+                _ => {
+                    let arg = arg.map(|a| String::from_utf8_lossy(&a).into_owned());
+                    cc.extensions.push((name, arg));
+                }
+            }
+        }
+        cc
+    }
+
+    /// Parses all `Cache-Control` headers in `headers`, combined per RFC 9110 §5.3.
+    ///
+    /// Returns the default (no directives) if the header is absent.
+    pub fn from_headers(headers: &[Header<'_>]) -> Self {
+        combine_header_values(headers, "Cache-Control")
+            .map(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+}
+
+/// Splits a field value on commas that are not inside a quoted string.
+fn split_directives(value: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut in_quotes = false;
+    let mut escaped = false;
+    value
+        .split(move |&b| {
+            if escaped {
+                escaped = false;
+            } else if in_quotes && b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_quotes = !in_quotes;
+            } else if b == b',' && !in_quotes {
+                return true;
+            }
+            false
+        })
+        .map(<[u8]>::trim_ascii)
+        .filter(|d| !d.is_empty())
+}
+
+/// Removes surrounding quotes and backslash escapes from a directive argument.
+fn unquote(arg: &[u8]) -> Vec<u8> {
+    let arg = arg.trim_ascii();
+    let Some(inner) = arg.strip_prefix(b"\"").and_then(|a| a.strip_suffix(b"\"")) else {
+        return arg.to_vec();
+    };
+    let mut out = Vec::with_capacity(inner.len());
+    let mut bytes = inner.iter();
+    while let Some(&b) = bytes.next() {
+        if b == b'\\' {
+            out.extend(bytes.next());
+        } else {
+            out.push(b);
+        }
+    }
+    out
+}
+
+/// Parses delta-seconds (RFC 9111 §1.2.2), clamping overly large values.
+fn parse_delta_seconds(arg: &[u8]) -> Option<u64> {
+    if arg.is_empty() || !arg.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let seconds = arg.iter().try_fold(0u64, |acc, &d| {
+        acc.checked_mul(10)?.checked_add(u64::from(d - b'0'))
+    });
+    Some(seconds.map_or(MAX_DELTA_SECONDS, |s| s.min(MAX_DELTA_SECONDS)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_response_directives() {
+        let cc = CacheControl::parse(
+            b"public, max-age=3600, s-maxage=\"60\", must-revalidate, Immutable, stale-while-revalidate=30",
+        );
+        assert!(cc.public);
+        assert!(cc.must_revalidate);
+        assert!(cc.immutable);
+        assert_eq!(cc.max_age, Some(3600));
+        assert_eq!(cc.s_maxage, Some(60));
+        assert_eq!(cc.stale_while_revalidate, Some(30));
+        assert!(!cc.private);
+        assert!(cc.extensions.is_empty());
+    }
+
+    #[test]
+    fn test_parse_request_directives() {
+        let cc = CacheControl::parse(b"no-cache, max-stale, min-fresh=10, only-if-cached");
+        assert!(cc.no_cache);
+        assert!(cc.only_if_cached);
+        assert_eq!(cc.max_stale, Some(None));
+        assert_eq!(cc.min_fresh, Some(10));
+
+        let cc = CacheControl::parse(b"max-stale=120");
+        assert_eq!(cc.max_stale, Some(Some(120)));
+    }
+
+    #[test]
+    fn test_parse_quoted_field_names_and_extensions() {
+        let cc = CacheControl::parse(
+            b"private=\"Set-Cookie, Authorization\", no-store, community=\"UCI\"",
+        );
+        assert!(cc.private);
+        assert!(cc.no_store);
+        assert_eq!(
+            cc.extensions,
+            vec![("community".to_string(), Some("UCI".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_and_duplicate_values() {
+        let cc = CacheControl::parse(b"max-age=abc, max-age=10");
+        // The first occurrence wins, even when invalid
+        assert_eq!(cc.max_age, None);
+
+        let cc = CacheControl::parse(b"max-age=10, max-age=20");
+        assert_eq!(cc.max_age, Some(10));
+
+        let cc = CacheControl::parse(b"max-age=99999999999999999999999");
+        assert_eq!(cc.max_age, Some(MAX_DELTA_SECONDS));
+
+        let cc = CacheControl::parse(b",, ,");
+        assert_eq!(cc, CacheControl::default());
+    }
+
+    #[test]
+    fn test_from_headers_combines_lines() {
+        let headers = [
+            Header {
+                name: "Cache-Control",
+                value: b"no-cache",
+            },
+            Header {
+                name: "cache-control",
+                value: b"max-age=5",
+            },
+        ];
+        let cc = CacheControl::from_headers(&headers);
+        assert!(cc.no_cache);
+        assert_eq!(cc.max_age, Some(5));
+
+        assert_eq!(CacheControl::from_headers(&[]), CacheControl::default());
+    }
+}
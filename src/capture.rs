@@ -0,0 +1,199 @@
+//! Byte capture for live connections.
+//!
+//! [`CapturingIo`] wraps any `AsyncRead + AsyncWrite` stream and records the exact
+//! bytes flowing in both directions, which is useful to debug or record the traffic
+//! of a real socket.
+
+use std::{
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use parking_lot::Mutex;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+#[derive(Debug, Default)]
+struct Buffers {
+    written: Vec<u8>,
+    read: Vec<u8>,
+}
+
+/// Stream wrapper that records every byte written to and read from the inner stream.
+///
+/// The recorded bytes are read through a [`CaptureHandle`] obtained from
+/// [`handle`](Self::handle), which remains usable after the wrapper has been moved
+/// into a connection driver. The accessors live on the handle rather than the
+/// wrapper so they don't shadow `AsyncReadExt::read`.
+///
+/// Capture is unbounded: both buffers grow for as long as the stream is used.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::CapturingIo;
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (client, mut server) = tokio::io::duplex(1024);
+/// let mut io = CapturingIo::new(client);
+/// let handle = io.handle();
+///
+/// io.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+/// server.write_all(b"HTTP/1.1 204 No Content\r\n\r\n").await.unwrap();
+/// let mut buf = [0u8; 64];
+/// let n = io.read(&mut buf).await.unwrap();
+///
+/// assert_eq!(handle.written(), b"GET / HTTP/1.1\r\n\r\n");
+/// assert_eq!(handle.read(), &buf[..n]);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CapturingIo<T> {
+    inner: T,
+    buffers: Arc<Mutex<Buffers>>,
+}
+
+/// Shared access to the bytes recorded by a [`CapturingIo`].
+#[derive(Debug, Clone)]
+pub struct CaptureHandle {
+    buffers: Arc<Mutex<Buffers>>,
+}
+
+impl<T> CapturingIo<T> {
+    /// Wraps `inner`, starting with empty capture buffers.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            buffers: Arc::new(Mutex::new(Buffers::default())),
+        }
+    }
+
+    /// Returns a handle to the capture buffers that outlives moves of the wrapper.
+    pub fn handle(&self) -> CaptureHandle {
+        CaptureHandle {
+            buffers: self.buffers.clone(),
+        }
+    }
+
+    /// Returns a reference to the inner stream.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Unwraps the inner stream, discarding the wrapper.
+    ///
+    /// Existing [`CaptureHandle`]s keep access to the bytes captured so far.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl CaptureHandle {
+    /// Returns a copy of the bytes written to the inner stream so far.
+    pub fn written(&self) -> Vec<u8> {
+        self.buffers.lock().written.clone()
+    }
+
+    /// Returns a copy of the bytes read from the inner stream so far.
+    pub fn read(&self) -> Vec<u8> {
+        self.buffers.lock().read.clone()
+    }
+
+    /// Clears both capture buffers.
+    pub fn clear(&self) {
+        let mut buffers = self.buffers.lock();
+        buffers.written.clear();
+        buffers.read.clear();
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for CapturingIo<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let start = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            // Capture only the bytes filled by this read
+            self.buffers
+                .lock()
+                .read
+                .extend_from_slice(&buf.filled()[start..]);
+        }
+        poll
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for CapturingIo<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            // Capture only what the inner stream accepted
+            self.buffers.lock().written.extend_from_slice(&buf[..n]);
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), io::Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_capture_both_directions() {
+        let (client, mut server) = tokio::io::duplex(64);
+        let mut io = CapturingIo::new(client);
+        let handle = io.handle();
+
+        io.write_all(b"ping").await.unwrap();
+        server.write_all(b"pong").await.unwrap();
+        let mut buf = [0u8; 4];
+        io.read_exact(&mut buf).await.unwrap();
+
+        let mut echoed = [0u8; 4];
+        server.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(&echoed, b"ping");
+
+        // The handle keeps working after the wrapper is consumed
+        drop(io.into_inner());
+        assert_eq!(handle.written(), b"ping");
+        assert_eq!(handle.read(), b"pong");
+
+        handle.clear();
+        assert!(handle.written().is_empty());
+        assert!(handle.read().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_capture_partial_write() {
+        // A duplex with 4 bytes of capacity accepts only part of a larger write
+        let (client, _server) = tokio::io::duplex(4);
+        let mut io = CapturingIo::new(client);
+        let handle = io.handle();
+
+        let n = io.write(b"abcdefgh").await.unwrap();
+        assert_eq!(n, 4);
+        assert_eq!(handle.written(), b"abcd");
+    }
+}
@@ -0,0 +1,547 @@
+//! Diagnostics for chunked transfer coding.
+//!
+//! This module provides [`verify_chunked_body`], a strict checker for chunked bodies
+//! that reports what it found, or exactly what is wrong, rather than only the
+//! framed length, [`decode_trailers`], which parses the trailer section, and
+//! [`parse_chunked_body_ext`], which returns the chunk extensions.
+
+use alloc::vec::Vec;
+use httparse::Header;
+
+use crate::error::WireError;
+
+/// Summary of a well-formed chunked body.
+///
+/// Returned by [`verify_chunked_body`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkedInfo {
+    /// Total length of the chunk data, with the framing removed.
+    pub decoded_len: usize,
+    /// Number of data chunks, not counting the terminating zero-size chunk.
+    pub chunks: usize,
+    /// Whether a non-empty trailer section follows the last chunk.
+    pub has_trailers: bool,
+    /// Length of the chunked body on the wire, including the final blank line.
+    pub framed_len: usize,
+}
+
+/// Verifies a chunked body and reports its decoded length and structure.
+///
+/// `buf` must start at the first chunk-size line; bytes after the end of the body are
+/// ignored. A body is accepted exactly when `decode` accepts it: every chunk-size line
+/// must be one or more hex digits, optionally followed by a `;` chunk extension, and
+/// every line must end with CRLF.
+///
+/// # Errors
+///
+/// - [`WireError::InvalidChunkSize`] if a chunk-size line is not valid hex or
+///   overflows `usize`, with the offset of that line
+/// - [`WireError::MissingChunkDelimiter`] if a line or chunk data is not followed by
+///   CRLF, with the offset where the CRLF was expected
+/// - [`WireError::IncompleteChunkedBody`] if `buf` ends before the body does
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::verify_chunked_body;
+///
+/// let info = verify_chunked_body(b"5\r\nhello\r\n6\r\n world\r\n0\r\nX-Sum: 1\r\n\r\n").unwrap();
+/// assert_eq!(info.decoded_len, 11);
+/// assert_eq!(info.chunks, 2);
+/// assert!(info.has_trailers);
+/// assert_eq!(info.framed_len, 36);
+/// ```
+pub fn verify_chunked_body(buf: &[u8]) -> Result<ChunkedInfo, WireError> {
+    let mut decoded_len = 0usize;
+    let mut chunks = 0;
+    let layout = walk_chunks(buf, |data| {
+        decoded_len += data.len();
+        chunks += 1;
+    })?;
+
+    Ok(ChunkedInfo {
+        decoded_len,
+        chunks,
+        has_trailers: layout.framed_len - layout.trailers_start > 2,
+        framed_len: layout.framed_len,
+    })
+}
+
+/// Parses the trailer section of a chunked body into `headers`.
+///
+/// `buf` must start at the first chunk-size line. The chunks are walked with the same
+/// strict rules as [`verify_chunked_body`], then the fields after the terminating
+/// zero-size chunk are parsed by httparse. Returns an empty slice if the body has no
+/// trailers.
+///
+/// # Errors
+///
+/// - [`WireError::PartialHead`] if the trailer section is incomplete
+/// - [`WireError::HttparseError`] if a trailer field is malformed, or if there are
+///   more trailers than `headers` can hold
+/// - the errors of [`verify_chunked_body`] for the chunks themselves
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::decode_trailers;
+///
+/// let body = b"5\r\nhello\r\n0\r\ngrpc-status: 0\r\ngrpc-message: OK\r\n\r\n";
+/// let mut headers = [httparse::EMPTY_HEADER; 4];
+/// let trailers = decode_trailers(body, &mut headers).unwrap();
+/// assert_eq!(trailers.len(), 2);
+/// assert_eq!(trailers[0].name, "grpc-status");
+/// assert_eq!(trailers[0].value, b"0");
+/// ```
+pub fn decode_trailers<'h, 'buf>(
+    buf: &'buf [u8],
+    headers: &'h mut [Header<'buf>],
+) -> Result<&'h [Header<'buf>], WireError> {
+    let start = walk_data_chunks(buf, ChunkRules::STRICT, |_, _| {}, |_| {})?;
+    // The trailer lines must end where the decoder sees them end
+    if let Err(err) = walk_trailers(buf, start, false) {
+        return Err(match err {
+            ChunkedError::Incomplete => WireError::PartialHead,
+            err => err.into(),
+        });
+    }
+    match httparse::parse_headers(&buf[start..], headers)? {
+        httparse::Status::Complete((_, trailers)) => Ok(trailers),
+        httparse::Status::Partial => Err(WireError::PartialHead),
+    }
+}
+
+/// The `(offset, extension)` pairs returned by [`parse_chunked_body_ext`].
+pub type ChunkExtensions<'buf> = Vec<(usize, &'buf [u8])>;
+
+/// Parses a chunked body, returning its length and the extensions of its chunks.
+///
+/// `buf` must start at the first chunk-size line. The body is checked with the same
+/// strict rules as [`verify_chunked_body`], and the returned length is its length on
+/// the wire, including the trailers and the final blank line.
+///
+/// The list has one `(offset, extension)` entry per chunk-size line, in order and
+/// including the terminating zero-size chunk. `offset` is the start of the line in
+/// `buf`, and `extension` the bytes after the first `;` up to the line's CRLF, left
+/// unparsed. Chunks without an extension have an empty `extension`.
+///
+/// The chunked framing used by `decode` skips extensions without recording them, so
+/// only callers that need them pay for this list.
+///
+/// # Errors
+///
+/// Returns the same errors as [`verify_chunked_body`].
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::parse_chunked_body_ext;
+///
+/// let body = b"5;sig=abc\r\nhello\r\n3\r\nfoo\r\n0;sig=def\r\n\r\n";
+/// let (len, extensions) = parse_chunked_body_ext(body).unwrap();
+/// assert_eq!(len, body.len());
+/// assert_eq!(
+///     extensions,
+///     [(0, &b"sig=abc"[..]), (18, b""), (26, b"sig=def")]
+/// );
+/// ```
+pub fn parse_chunked_body_ext(buf: &[u8]) -> Result<(usize, ChunkExtensions<'_>), WireError> {
+    let mut extensions = Vec::new();
+    let layout = walk_chunked(
+        buf,
+        ChunkRules::STRICT,
+        |offset, line| {
+            let extension = line
+                .iter()
+                .position(|&b| b == b';')
+                .map_or(&line[line.len()..], |semi| &line[semi + 1..]);
+            extensions.push((offset, extension));
+        },
+        |_| {},
+    )?;
+    Ok((layout.framed_len, extensions))
+}
+
+/// Positions of the sections that follow the data chunks of a chunked body.
+pub(crate) struct ChunkedLayout {
+    /// Offset of the trailer section, just after the terminating zero-size chunk line.
+    pub(crate) trailers_start: usize,
+    /// Length of the whole chunked body, including the final blank line.
+    pub(crate) framed_len: usize,
+}
+
+/// The rules a chunked body is walked with.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ChunkRules {
+    /// Whether lines may end with a bare LF instead of CRLF.
+    pub(crate) bare_lf: bool,
+    /// Maximum number of data chunks, not counting the terminating zero-size chunk.
+    pub(crate) max_chunks: usize,
+    /// Maximum size of each data chunk.
+    pub(crate) max_chunk_size: usize,
+}
+
+impl ChunkRules {
+    /// CRLF line endings and no limits.
+    pub(crate) const STRICT: Self = Self {
+        bare_lf: false,
+        max_chunks: usize::MAX,
+        max_chunk_size: usize::MAX,
+    };
+}
+
+/// Why walking a chunked body stopped, with the offset in its input where it did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ChunkedError {
+    /// The input ends before the body does.
+    Incomplete,
+    /// Position where a line ending was expected, after a line or the chunk data.
+    MissingDelimiter(usize),
+    /// Start of a chunk-size line that is not valid hex or overflows `usize`.
+    InvalidSize(usize),
+    /// Start of the first chunk-size line beyond the chunk limit.
+    TooManyChunks(usize),
+    /// Start of a chunk-size line whose size is above the chunk size limit.
+    ChunkTooLarge(usize),
+}
+
+impl ChunkedError {
+    /// Returns where walking stopped in an input of `len` bytes.
+    pub(crate) fn offset(self, len: usize) -> usize {
+        match self {
+            ChunkedError::Incomplete => len,
+            ChunkedError::MissingDelimiter(offset)
+            | ChunkedError::InvalidSize(offset)
+            | ChunkedError::TooManyChunks(offset)
+            | ChunkedError::ChunkTooLarge(offset) => offset,
+        }
+    }
+
+    /// Converts to the error `decode` reports for a body of `len` bytes starting at
+    /// `base`, where a body cut short is an invalid body ending at `base + len`.
+    pub(crate) fn at(self, base: usize, len: usize) -> WireError {
+        let offset = base + self.offset(len);
+        match self {
+            ChunkedError::Incomplete | ChunkedError::MissingDelimiter(_) => {
+                WireError::InvalidChunkedBody { offset }
+            }
+            ChunkedError::InvalidSize(_) => WireError::InvalidChunkSize { offset },
+            ChunkedError::TooManyChunks(_) => WireError::TooManyChunks { offset },
+            ChunkedError::ChunkTooLarge(_) => WireError::ChunkTooLarge { offset },
+        }
+    }
+}
+
+impl From<ChunkedError> for WireError {
+    fn from(err: ChunkedError) -> Self {
+        match err {
+            ChunkedError::Incomplete => WireError::IncompleteChunkedBody,
+            ChunkedError::MissingDelimiter(offset) => WireError::MissingChunkDelimiter { offset },
+            ChunkedError::InvalidSize(offset) => WireError::InvalidChunkSize { offset },
+            ChunkedError::TooManyChunks(offset) => WireError::TooManyChunks { offset },
+            ChunkedError::ChunkTooLarge(offset) => WireError::ChunkTooLarge { offset },
+        }
+    }
+}
+
+/// Strictly walks a chunked body, calling `on_chunk` with the data of each chunk.
+///
+/// This is the parser behind [`verify_chunked_body`] and reports the same errors.
+pub(crate) fn walk_chunks<'buf>(
+    buf: &'buf [u8],
+    on_chunk: impl FnMut(&'buf [u8]),
+) -> Result<ChunkedLayout, WireError> {
+    Ok(walk_chunked(buf, ChunkRules::STRICT, |_, _| {}, on_chunk)?)
+}
+
+/// Walks a chunked body with `rules`, calling `on_line` with the offset and content of
+/// each chunk-size line, without its line ending, and `on_chunk` with the data of
+/// each chunk.
+///
+/// Every chunked parser in the crate, including the framing used by `decode`, is
+/// built on this walker, so they all agree on where a chunked body ends.
+pub(crate) fn walk_chunked<'buf>(
+    buf: &'buf [u8],
+    rules: ChunkRules,
+    on_line: impl FnMut(usize, &'buf [u8]),
+    on_chunk: impl FnMut(&'buf [u8]),
+) -> Result<ChunkedLayout, ChunkedError> {
+    let trailers_start = walk_data_chunks(buf, rules, on_line, on_chunk)?;
+    let framed_len = walk_trailers(buf, trailers_start, rules.bare_lf)?;
+    Ok(ChunkedLayout {
+        trailers_start,
+        framed_len,
+    })
+}
+
+/// Walks the data chunks of a chunked body, up to and including the terminating
+/// zero-size chunk line, and returns the offset of the trailer section.
+fn walk_data_chunks<'buf>(
+    buf: &'buf [u8],
+    rules: ChunkRules,
+    mut on_line: impl FnMut(usize, &'buf [u8]),
+    mut on_chunk: impl FnMut(&'buf [u8]),
+) -> Result<usize, ChunkedError> {
+    let mut pos = 0;
+    let mut chunks = 0usize;
+
+    loop {
+        let line_start = pos;
+        let (line_end, next) = find_line_end(buf, pos, rules.bare_lf)?;
+        on_line(line_start, &buf[line_start..line_end]);
+        let chunk_size = parse_chunk_size(&buf[line_start..line_end])
+            .ok_or(ChunkedError::InvalidSize(line_start))?;
+        if chunk_size > rules.max_chunk_size {
+            return Err(ChunkedError::ChunkTooLarge(line_start));
+        }
+        pos = next;
+
+        if chunk_size == 0 {
+            return Ok(pos);
+        }
+        chunks += 1;
+        if chunks > rules.max_chunks {
+            return Err(ChunkedError::TooManyChunks(line_start));
+        }
+
+        // A size that fits in `usize` may still not leave room for the end of the chunk
+        let data_end = pos
+            .checked_add(chunk_size)
+            .filter(|end| *end <= usize::MAX - 2)
+            .ok_or(ChunkedError::InvalidSize(line_start))?;
+        // The chunk data must be followed by a line ending, not just any two bytes
+        let delimiter = &buf[data_end.min(buf.len())..buf.len().min(data_end + 2)];
+        let next = match delimiter {
+            [b'\n', ..] if rules.bare_lf => data_end + 1,
+            b"\r\n" => data_end + 2,
+            [] | b"\r" => return Err(ChunkedError::Incomplete),
+            _ => return Err(ChunkedError::MissingDelimiter(data_end)),
+        };
+
+        on_chunk(&buf[pos..data_end]);
+        pos = next;
+    }
+}
+
+/// Walks the trailer section starting at `pos`, field lines up to an empty line, and
+/// returns the position after it.
+fn walk_trailers(buf: &[u8], mut pos: usize, bare_lf: bool) -> Result<usize, ChunkedError> {
+    loop {
+        let (line_end, next) = find_line_end(buf, pos, bare_lf)?;
+        if line_end == pos {
+            return Ok(next);
+        }
+        pos = next;
+    }
+}
+
+/// Returns the end of the content of the line that starts at `start`, and the start
+/// of the next line.
+///
+/// Lines end with CRLF, or with a bare LF if `bare_lf` is set.
+fn find_line_end(buf: &[u8], start: usize, bare_lf: bool) -> Result<(usize, usize), ChunkedError> {
+    let lf = buf[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .ok_or(ChunkedError::Incomplete)?;
+    let lf = start + lf;
+    if lf > start && buf[lf - 1] == b'\r' {
+        Ok((lf - 1, lf + 1))
+    } else if bare_lf {
+        Ok((lf, lf + 1))
+    } else {
+        Err(ChunkedError::MissingDelimiter(lf))
+    }
+}
+
+/// Strictly parses a chunk-size line (without its line ending), ignoring any
+/// extension.
+///
+/// Returns `None` if the size is empty, not hex, or overflows `usize`.
+pub(crate) fn parse_chunk_size(line: &[u8]) -> Option<usize> {
+    let end = line.iter().position(|&b| b == b';').unwrap_or(line.len());
+    // Whitespace is allowed before an extension (BWS), not inside the number
+    let digits = line[..end].trim_ascii_end();
+    if digits.is_empty() {
+        return None;
+    }
+
+    digits.iter().try_fold(0usize, |size, &b| {
+        let digit = (b as char).to_digit(16)?;
+        size.checked_mul(16)?.checked_add(digit as usize)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_simple_body() {
+        let info = verify_chunked_body(b"5\r\nhello\r\n0\r\n\r\n").unwrap();
+        assert_eq!(
+            info,
+            ChunkedInfo {
+                decoded_len: 5,
+                chunks: 1,
+                has_trailers: false,
+                framed_len: 15,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_empty_body_with_extension_and_trailing_data() {
+        let info = verify_chunked_body(b"0;name=value\r\n\r\nGET / HTTP/1.1").unwrap();
+        assert_eq!(info.decoded_len, 0);
+        assert_eq!(info.chunks, 0);
+        assert_eq!(info.framed_len, 16);
+    }
+
+    #[test]
+    fn test_verify_reports_invalid_size() {
+        for body in [
+            &b"5x\r\nhello\r\n0\r\n\r\n"[..],
+            b"\r\nhello\r\n0\r\n\r\n",
+            b"5 5\r\nhello\r\n0\r\n\r\n",
+            b"fffffffffffffffff\r\n",
+        ] {
+            assert!(
+                matches!(
+                    verify_chunked_body(body),
+                    Err(WireError::InvalidChunkSize { offset: 0 })
+                ),
+                "{}",
+                String::from_utf8_lossy(body)
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_reports_offset_of_later_chunk() {
+        assert!(matches!(
+            verify_chunked_body(b"5\r\nhello\r\nzz\r\nab\r\n0\r\n\r\n"),
+            Err(WireError::InvalidChunkSize { offset: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_verify_reports_missing_crlf() {
+        // Bare LF after the size line
+        assert!(matches!(
+            verify_chunked_body(b"5\nhello\r\n0\r\n\r\n"),
+            Err(WireError::MissingChunkDelimiter { offset: 1 })
+        ));
+        // Data longer than the declared size
+        assert!(matches!(
+            verify_chunked_body(b"3\r\nhello\r\n0\r\n\r\n"),
+            Err(WireError::MissingChunkDelimiter { offset: 6 })
+        ));
+    }
+
+    #[test]
+    fn test_verify_reports_incomplete() {
+        for body in [
+            &b""[..],
+            b"5\r\nhel",
+            b"5\r\nhello\r\n",
+            b"5\r\nhello\r\n0\r\n",
+            b"5\r\nhello\r\n0\r\nX-Sum: 1\r\n",
+        ] {
+            assert!(
+                matches!(
+                    verify_chunked_body(body),
+                    Err(WireError::IncompleteChunkedBody)
+                ),
+                "{}",
+                String::from_utf8_lossy(body)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parsers_agree_on_framing() {
+        let options = crate::DecodeOptions::new();
+        for body in [
+            &b"3\r\nabc\r\n0\r\n\r\n"[..],
+            b"3;ext\r\nabc\r\n0\r\nX-Sum: 1\r\n\r\n",
+            b"3\r\nabcXY0\r\n\r\n",
+            b" g3\r\nabc\r\n0\r\n\r\n",
+            b"\r\n0\r\n\r\n",
+            b"3\nabc\r\n0\r\n\r\n",
+            b"3\r\nabc\r\n0\r\nX-Sum: 1\nX-Done: 1\r\n\r\n",
+        ] {
+            let verified = verify_chunked_body(body).map(|info| info.framed_len);
+            assert_eq!(
+                options.chunked_body_len(body, 0).ok(),
+                verified.as_ref().ok().copied(),
+                "{}",
+                String::from_utf8_lossy(body)
+            );
+            assert_eq!(
+                parse_chunked_body_ext(body).map(|(len, _)| len).ok(),
+                verified.as_ref().ok().copied()
+            );
+            assert_eq!(
+                crate::util::split_chunks(body, false).is_ok(),
+                verified.is_ok()
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_trailers() {
+        let mut headers = [httparse::EMPTY_HEADER; 4];
+        let trailers = decode_trailers(b"3\r\nabc\r\n0\r\n\r\n", &mut headers).unwrap();
+        assert!(trailers.is_empty());
+
+        let mut headers = [httparse::EMPTY_HEADER; 4];
+        let trailers =
+            decode_trailers(b"0\r\nX-Sum: 1\r\nX-Done: yes\r\n\r\nnext", &mut headers).unwrap();
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(
+            (trailers[1].name, trailers[1].value),
+            ("X-Done", &b"yes"[..])
+        );
+    }
+
+    #[test]
+    fn test_decode_trailers_errors() {
+        let mut headers = [httparse::EMPTY_HEADER; 4];
+        assert!(matches!(
+            decode_trailers(b"3\r\nabc\r\n0\r\nX-Sum: 1\r\n", &mut headers),
+            Err(WireError::PartialHead)
+        ));
+        assert!(matches!(
+            decode_trailers(b"3\r\nab", &mut headers),
+            Err(WireError::IncompleteChunkedBody)
+        ));
+
+        let mut headers = [httparse::EMPTY_HEADER; 1];
+        assert!(matches!(
+            decode_trailers(b"0\r\nA: 1\r\nB: 2\r\n\r\n", &mut headers),
+            Err(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        ));
+    }
+
+    #[test]
+    fn test_parse_chunked_body_ext() {
+        let body = b"4;a=1;b=\"x\"\r\nwiki\r\n5 ; c\r\npedia\r\n0\r\nX-Sum: 1\r\n\r\nrest";
+        let (len, extensions) = parse_chunked_body_ext(body).unwrap();
+        assert_eq!(len, body.len() - 4);
+        assert_eq!(
+            extensions,
+            [(0, &b"a=1;b=\"x\""[..]), (19, b" c"), (33, b"")]
+        );
+
+        // Errors match verify_chunked_body
+        assert!(matches!(
+            parse_chunked_body_ext(b"4;a=1\r\nwiki\r\n"),
+            Err(WireError::IncompleteChunkedBody)
+        ));
+        assert!(matches!(
+            parse_chunked_body_ext(b"g;a=1\r\n"),
+            Err(WireError::InvalidChunkSize { offset: 0 })
+        ));
+    }
+}
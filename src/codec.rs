@@ -0,0 +1,316 @@
+//! Codecs for reading messages from a byte stream.
+//!
+//! This module provides [`RequestCodec`] and [`ResponseCodec`], which implement
+//! [`tokio_util::codec::Decoder`] so a socket can be wrapped in a
+//! [`FramedRead`](tokio_util::codec::FramedRead) that yields one message at a time.
+
+use bytes::{Buf, BytesMut};
+use tokio_util::codec::Decoder;
+
+use crate::decoder::{DecodeOptions, Limits};
+use crate::error::WireError;
+use crate::request::FullRequest;
+use crate::response::FullResponse;
+use crate::session::HeaderStorage;
+use crate::util::flag_incomplete_chunked;
+
+/// Most bytes reserved ahead of an incomplete body. `Content-Length` comes from the
+/// peer, so the read buffer only grows as body bytes actually arrive.
+const MAX_RESERVE: usize = 64 * 1024;
+
+/// Decodes requests from a byte stream into [`http::Request`]s.
+///
+/// Each request is decoded like [`FullRequest::decode_with_options`] with the codec's
+/// options and header storage, converted with [`FullRequest::into_http`], and consumed
+/// from the read buffer. With [`DecodeOptions::allow_bare_lf`], the conversion accepts
+/// the bare LF chunk lines the decoder did. While a request is incomplete the codec returns `Ok(None)`, so
+/// the stream reads more data. A new codec sets no limits; see
+/// [`with_limits`](Self::with_limits) for reading from untrusted peers.
+///
+/// # Example
+///
+/// ```rust
+/// use futures::StreamExt;
+/// use http_wire::RequestCodec;
+/// use tokio_util::codec::FramedRead;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let input: &[u8] = b"GET /a HTTP/1.1\r\nHost: x\r\n\r\nPOST /b HTTP/1.1\r\nHost: x\r\nContent-Length: 2\r\n\r\nhi";
+/// let mut requests = FramedRead::new(input, RequestCodec::new());
+///
+/// let first = requests.next().await.unwrap().unwrap();
+/// assert_eq!(first.uri(), "/a");
+/// let second = requests.next().await.unwrap().unwrap();
+/// assert_eq!(second.body(), "hi");
+/// assert!(requests.next().await.is_none());
+/// # });
+/// ```
+#[derive(Debug, Default)]
+pub struct RequestCodec {
+    headers: HeaderStorage,
+    options: DecodeOptions,
+}
+
+impl RequestCodec {
+    /// Creates a codec that decodes requests with up to 64 headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a codec that decodes requests with up to `max_headers` headers.
+    pub fn with_max_headers(max_headers: usize) -> Self {
+        Self {
+            headers: HeaderStorage::new(max_headers),
+            options: DecodeOptions::new(),
+        }
+    }
+
+    /// Creates a codec that decodes requests like
+    /// [`FullRequest::decode_with_options`].
+    ///
+    /// Header storage is sized from [`DecodeOptions::max_headers`], or holds 64 headers
+    /// if it is unset.
+    pub fn with_options(options: DecodeOptions) -> Self {
+        Self {
+            headers: options
+                .max_headers
+                .map(HeaderStorage::new)
+                .unwrap_or_default(),
+            options,
+        }
+    }
+
+    /// Creates a codec that enforces every limit in `limits`.
+    pub fn with_limits(limits: Limits) -> Self {
+        Self::with_options(limits.into())
+    }
+}
+
+impl Decoder for RequestCodec {
+    type Item = http::Request<bytes::Bytes>;
+    type Error = WireError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let decoded = FullRequest::decode_with_options(src, self.headers.slots(), &self.options)
+            .and_then(|(request, len)| {
+                Ok((request.to_http_with(self.options.allow_bare_lf)?, len))
+            });
+        complete(src, decoded)
+    }
+}
+
+/// Decodes responses from a byte stream into [`http::Response`]s.
+///
+/// Works like [`RequestCodec`], with [`FullResponse::decode_with_options`] and
+/// [`FullResponse::into_http`].
+///
+/// # Example
+///
+/// ```rust
+/// use bytes::BytesMut;
+/// use http_wire::ResponseCodec;
+/// use tokio_util::codec::Decoder;
+///
+/// let mut codec = ResponseCodec::new();
+/// let mut buf = BytesMut::from(&b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhel"[..]);
+/// assert!(codec.decode(&mut buf).unwrap().is_none());
+///
+/// buf.extend_from_slice(b"lo");
+/// let response = codec.decode(&mut buf).unwrap().unwrap();
+/// assert_eq!(response.body(), "hello");
+/// assert!(buf.is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct ResponseCodec {
+    headers: HeaderStorage,
+    options: DecodeOptions,
+}
+
+impl ResponseCodec {
+    /// Creates a codec that decodes responses with up to 64 headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a codec that decodes responses with up to `max_headers` headers.
+    pub fn with_max_headers(max_headers: usize) -> Self {
+        Self {
+            headers: HeaderStorage::new(max_headers),
+            options: DecodeOptions::new(),
+        }
+    }
+
+    /// Creates a codec that decodes responses like
+    /// [`FullResponse::decode_with_options`].
+    ///
+    /// Header storage is sized from [`DecodeOptions::max_headers`], or holds 64 headers
+    /// if it is unset.
+    pub fn with_options(options: DecodeOptions) -> Self {
+        Self {
+            headers: options
+                .max_headers
+                .map(HeaderStorage::new)
+                .unwrap_or_default(),
+            options,
+        }
+    }
+
+    /// Creates a codec that enforces every limit in `limits`.
+    pub fn with_limits(limits: Limits) -> Self {
+        Self::with_options(limits.into())
+    }
+}
+
+impl Decoder for ResponseCodec {
+    type Item = http::Response<bytes::Bytes>;
+    type Error = WireError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let decoded = FullResponse::decode_with_options(src, self.headers.slots(), &self.options)
+            .and_then(|(response, len)| {
+                Ok((response.to_http_with(self.options.allow_bare_lf)?, len))
+            });
+        complete(src, decoded)
+    }
+}
+
+/// Consumes a decoded message from `src`, or turns an incomplete one into `Ok(None)`.
+fn complete<T>(
+    src: &mut BytesMut,
+    decoded: Result<(T, usize), WireError>,
+) -> Result<Option<T>, WireError> {
+    match decoded.map_err(|err| flag_incomplete_chunked(err, src.len())) {
+        Ok((message, len)) => {
+            src.advance(len);
+            Ok(Some(message))
+        }
+        Err(WireError::IncompleteBody(missing)) => {
+            src.reserve(missing.min(MAX_RESERVE));
+            Ok(None)
+        }
+        Err(WireError::PartialHead | WireError::IncompleteChunkedBody) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_codec_partial_reads() {
+        let input = b"POST /a HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\nGET /b HTTP/1.1\r\nHost: x\r\n\r\n";
+        let mut codec = RequestCodec::new();
+        let mut buf = BytesMut::new();
+        let mut requests = Vec::new();
+
+        for &byte in input {
+            buf.extend_from_slice(&[byte]);
+            while let Some(request) = codec.decode(&mut buf).unwrap() {
+                requests.push(request);
+            }
+        }
+
+        assert!(buf.is_empty());
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].body(), "abc");
+        assert_eq!(requests[1].uri(), "/b");
+    }
+
+    #[test]
+    fn test_codec_errors() {
+        let mut codec = RequestCodec::with_max_headers(1);
+        let mut buf = BytesMut::from(&b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n"[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        ));
+
+        let mut codec = ResponseCodec::new();
+        let mut buf =
+            BytesMut::from(&b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\nabc"[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(WireError::InvalidChunkedBody { .. })
+        ));
+
+        // A stream that ends mid-message is an error
+        let mut buf = BytesMut::from(&b"HTTP/1.1 200 OK\r\n"[..]);
+        assert!(matches!(codec.decode_eof(&mut buf), Err(WireError::Io(_))));
+    }
+
+    #[test]
+    fn test_codec_huge_content_length() {
+        // The declared length is not reserved up front
+        let mut codec = RequestCodec::new();
+        let mut buf = BytesMut::from(
+            &b"POST / HTTP/1.1\r\nHost: x\r\nContent-Length: 100000000000000\r\n\r\nab"[..],
+        );
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        assert!(buf.capacity() <= buf.len() + MAX_RESERVE);
+
+        // With limits it is rejected outright
+        let mut codec = RequestCodec::with_limits(Limits::default());
+        assert_eq!(codec.decode(&mut buf).err(), Some(WireError::BodyTooLarge));
+
+        let mut codec = ResponseCodec::with_options(DecodeOptions::new().max_headers(1));
+        let mut buf = BytesMut::from(&b"HTTP/1.1 200 OK\r\nA: 1\r\nB: 2\r\n\r\n"[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        ));
+    }
+
+    #[test]
+    fn test_codec_bare_lf_round_trip() {
+        let options = DecodeOptions::new().allow_bare_lf(true);
+
+        let mut codec = ResponseCodec::with_options(options.clone());
+        let mut buf = BytesMut::from(
+            &b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\nabc\n0\n\nHTTP/1.1 204 No Content\r\n\r\n"[..],
+        );
+        let response = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(response.body(), "abc");
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap().status(), 204);
+        assert!(buf.is_empty());
+
+        let mut codec = RequestCodec::with_options(options.clone());
+        let mut buf = BytesMut::from(
+            &b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\n0\nX-Sum: 1\n\n"[..],
+        );
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap().body(), "abc");
+        assert!(buf.is_empty());
+
+        // Converting the decoded message directly only accepts CRLF
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\nabc\n0\n\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode_with_options(raw, &mut headers, &options).unwrap();
+        assert_eq!(
+            response.into_http().err(),
+            Some(WireError::InvalidChunkedBody { offset: 1 })
+        );
+    }
+
+    #[test]
+    fn test_codec_response_transfer_coding_not_chunked() {
+        // The body of such a response ends when the connection closes, so it is not
+        // returned as soon as the head arrives
+        let mut codec = ResponseCodec::new();
+        let mut buf =
+            BytesMut::from(&b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\n\r\n\x1f\x8b"[..]);
+        assert_eq!(
+            codec.decode(&mut buf).err(),
+            Some(WireError::UnsupportedTransferCoding)
+        );
+
+        let mut codec =
+            ResponseCodec::with_options(DecodeOptions::new().assume_close_delimited(true));
+        let mut buf = BytesMut::from(
+            &b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked, gzip\r\n\r\n3\r\nabc\r\n0\r\n\r\n"[..],
+        );
+        assert_eq!(
+            codec.decode(&mut buf).err(),
+            Some(WireError::UnsupportedTransferCoding)
+        );
+    }
+}
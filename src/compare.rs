@@ -0,0 +1,170 @@
+//! Semantic comparison of HTTP wire messages.
+//!
+//! This module provides [`wire_equal`], which decides whether two serialized
+//! messages represent the same HTTP message regardless of header order, header
+//! name casing, or body framing.
+
+use crate::WireDecode;
+use crate::owned::DEFAULT_MAX_HEADERS;
+use crate::request::FullRequest;
+use crate::response::FullResponse;
+use crate::util::unframed_body;
+use httparse::Header;
+
+/// Compares two HTTP wire messages for semantic equality.
+///
+/// Both buffers are decoded and compared on:
+/// - method, path and version (requests) or status code and version (responses)
+/// - headers, ignoring order and name casing
+/// - the decoded body, with chunked framing removed
+///
+/// Framing headers (`Content-Length` and `Transfer-Encoding`) are excluded from the
+/// header comparison, so the same body sent with different framing compares equal.
+///
+/// Returns `false` if either buffer cannot be decoded as a complete message, or if
+/// one is a request and the other a response.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::wire_equal;
+///
+/// let a = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhello";
+/// let b = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\ncontent-type: text/plain\r\n\r\n5\r\nhello\r\n0\r\n\r\n";
+/// assert!(wire_equal(a, b));
+/// ```
+pub fn wire_equal(a: &[u8], b: &[u8]) -> bool {
+    match (a.starts_with(b"HTTP/"), b.starts_with(b"HTTP/")) {
+        (true, true) => responses_equal(a, b),
+        (false, false) => requests_equal(a, b),
+        _ => false,
+    }
+}
+
+fn requests_equal(a: &[u8], b: &[u8]) -> bool {
+    let mut headers_a = [httparse::EMPTY_HEADER; DEFAULT_MAX_HEADERS];
+    let mut headers_b = [httparse::EMPTY_HEADER; DEFAULT_MAX_HEADERS];
+
+    let (Ok((req_a, _)), Ok((req_b, _))) = (
+        FullRequest::decode(a, &mut headers_a),
+        FullRequest::decode(b, &mut headers_b),
+    ) else {
+        return false;
+    };
+
+    req_a.head.method == req_b.head.method
+        && req_a.head.path == req_b.head.path
+        && req_a.head.version == req_b.head.version
+        && headers_equal(req_a.head.headers, req_b.head.headers)
+        && bodies_equal(
+            req_a.head.headers,
+            req_a.body,
+            req_b.head.headers,
+            req_b.body,
+        )
+}
+
+fn responses_equal(a: &[u8], b: &[u8]) -> bool {
+    let mut headers_a = [httparse::EMPTY_HEADER; DEFAULT_MAX_HEADERS];
+    let mut headers_b = [httparse::EMPTY_HEADER; DEFAULT_MAX_HEADERS];
+
+    let (Ok((res_a, _)), Ok((res_b, _))) = (
+        FullResponse::decode(a, &mut headers_a),
+        FullResponse::decode(b, &mut headers_b),
+    ) else {
+        return false;
+    };
+
+    res_a.head.code == res_b.head.code
+        && res_a.head.version == res_b.head.version
+        && headers_equal(res_a.head.headers, res_b.head.headers)
+        && bodies_equal(
+            res_a.head.headers,
+            res_a.body,
+            res_b.head.headers,
+            res_b.body,
+        )
+}
+
+/// Returns `true` if the header is used for body framing.
+fn is_framing_header(name: &str) -> bool {
+    name.eq_ignore_ascii_case("Content-Length") || name.eq_ignore_ascii_case("Transfer-Encoding")
+}
+
+/// Builds a sorted list of `(lowercase name, trimmed value)` pairs, excluding framing headers.
+fn normalized_headers<'a>(headers: &[Header<'a>]) -> Vec<(String, &'a [u8])> {
+    let mut out: Vec<_> = headers
+        .iter()
+        .filter(|h| !is_framing_header(h.name))
+        .map(|h| (h.name.to_ascii_lowercase(), h.value.trim_ascii()))
+        .collect();
+    out.sort();
+    out
+}
+
+fn headers_equal(a: &[Header<'_>], b: &[Header<'_>]) -> bool {
+    normalized_headers(a) == normalized_headers(b)
+}
+
+fn bodies_equal(headers_a: &[Header<'_>], a: &[u8], headers_b: &[Header<'_>], b: &[u8]) -> bool {
+    match (
+        unframed_body(headers_a, a, false),
+        unframed_body(headers_b, b, false),
+    ) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_equal_identical() {
+        let raw = b"GET /api HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        assert!(wire_equal(raw, raw));
+    }
+
+    #[test]
+    fn test_wire_equal_header_order_and_case() {
+        let a = b"GET /api HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n";
+        let b = b"GET /api HTTP/1.1\r\naccept: */*\r\nhost: example.com\r\n\r\n";
+        assert!(wire_equal(a, b));
+    }
+
+    #[test]
+    fn test_wire_equal_chunked_vs_content_length() {
+        let a = b"POST /api HTTP/1.1\r\nHost: example.com\r\nContent-Length: 11\r\n\r\nhello world";
+        let b = b"POST /api HTTP/1.1\r\nHost: example.com\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        assert!(wire_equal(a, b));
+    }
+
+    #[test]
+    fn test_wire_equal_different_path() {
+        let a = b"GET /a HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let b = b"GET /b HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        assert!(!wire_equal(a, b));
+    }
+
+    #[test]
+    fn test_wire_equal_different_header_value() {
+        let a = b"HTTP/1.1 200 OK\r\nServer: a\r\nContent-Length: 0\r\n\r\n";
+        let b = b"HTTP/1.1 200 OK\r\nServer: b\r\nContent-Length: 0\r\n\r\n";
+        assert!(!wire_equal(a, b));
+    }
+
+    #[test]
+    fn test_wire_equal_request_vs_response() {
+        let a = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let b = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+        assert!(!wire_equal(a, b));
+    }
+
+    #[test]
+    fn test_wire_equal_incomplete() {
+        let a = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+        let b = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhel";
+        assert!(!wire_equal(a, b));
+    }
+}
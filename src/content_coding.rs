@@ -0,0 +1,226 @@
+//! Removal of content codings (`Content-Encoding`) from message bodies.
+//!
+//! Each coding is provided by an optional feature; `identity` is always supported.
+//!
+//! | Coding           | Feature                     |
+//! |------------------|-----------------------------|
+//! | `gzip`, `x-gzip` | `compression`               |
+//! | `deflate`        | `compression`               |
+//! | `br`             | `brotli` (or `compression`) |
+
+use std::borrow::Cow;
+
+use crate::error::WireError;
+use crate::util::content_codings;
+
+/// Largest body decompressed when the caller does not set a limit.
+pub(crate) const DEFAULT_MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
+/// Undoes the content codings listed in the `Content-Encoding` headers.
+///
+/// Codings are removed in the reverse of the order they were applied, so
+/// `Content-Encoding: gzip, br` is decoded as brotli first. The body is borrowed
+/// unchanged when no coding other than `identity` is present. Removing a coding fails
+/// with [`WireError::BodyTooLarge`] once it yields more than `max_len` bytes.
+pub(crate) fn decode_content<'buf>(
+    headers: &[httparse::Header<'buf>],
+    body: Cow<'buf, [u8]>,
+    max_len: usize,
+) -> Result<Cow<'buf, [u8]>, WireError> {
+    let codings: Vec<&[u8]> = content_codings(headers).collect();
+
+    let mut body = body;
+    for coding in codings.into_iter().rev() {
+        if coding.eq_ignore_ascii_case(b"identity") {
+            continue;
+        }
+        body = Cow::Owned(decode_coding(coding, &body, max_len)?);
+    }
+    Ok(body)
+}
+
+/// Removes a single content coding.
+fn decode_coding(coding: &[u8], data: &[u8], max_len: usize) -> Result<Vec<u8>, WireError> {
+    #[cfg(feature = "brotli")]
+    if coding.eq_ignore_ascii_case(b"br") {
+        let reader = brotli_decompressor::Decompressor::new(data, 4096);
+        return read_limited(reader, data.len(), max_len, "br");
+    }
+
+    #[cfg(feature = "compression")]
+    if coding.eq_ignore_ascii_case(b"gzip") || coding.eq_ignore_ascii_case(b"x-gzip") {
+        let reader = flate2::read::MultiGzDecoder::new(data);
+        return read_limited(reader, data.len(), max_len, "gzip");
+    }
+
+    #[cfg(feature = "compression")]
+    if coding.eq_ignore_ascii_case(b"deflate") {
+        // `deflate` is a zlib stream, but some servers send raw DEFLATE data instead
+        let zlib = flate2::read::ZlibDecoder::new(data);
+        return match read_limited(zlib, data.len(), max_len, "deflate") {
+            Err(WireError::InvalidContentEncoding(_)) => {
+                let raw = flate2::read::DeflateDecoder::new(data);
+                read_limited(raw, data.len(), max_len, "deflate")
+            }
+            result => result,
+        };
+    }
+
+    let _ = (data, max_len);
+    Err(WireError::UnsupportedEncoding(
+        String::from_utf8_lossy(coding).into_owned(),
+    ))
+}
+
+/// Reads a decompressing reader of `len` input bytes to the end, up to `max_len`
+/// output bytes.
+///
+/// The output is read through [`Read::take`](std::io::Read::take), so a small input
+/// that expands without bound stops one byte past the limit instead of filling memory.
+#[cfg(feature = "brotli")]
+fn read_limited(
+    reader: impl std::io::Read,
+    len: usize,
+    max_len: usize,
+    coding: &'static str,
+) -> Result<Vec<u8>, WireError> {
+    use std::io::Read;
+
+    let mut out = Vec::with_capacity(len.saturating_mul(4).min(max_len));
+    reader
+        .take((max_len as u64).saturating_add(1))
+        .read_to_end(&mut out)
+        .map_err(|_| WireError::InvalidContentEncoding(coding))?;
+    if out.len() > max_len {
+        return Err(WireError::BodyTooLarge);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header<'a>(value: &'a [u8]) -> httparse::Header<'a> {
+        httparse::Header {
+            name: "Content-Encoding",
+            value,
+        }
+    }
+
+    #[test]
+    fn test_identity_borrows() {
+        let headers = [header(b"identity")];
+        let body = decode_content(&headers, Cow::Borrowed(b"hello"), 0).unwrap();
+        assert!(matches!(body, Cow::Borrowed(b"hello")));
+
+        let body = decode_content(&[], Cow::Borrowed(b"hello"), 0).unwrap();
+        assert!(matches!(body, Cow::Borrowed(b"hello")));
+    }
+
+    #[test]
+    fn test_unknown_coding() {
+        let headers = [header(b"x-custom")];
+        assert!(matches!(
+            decode_content(&headers, Cow::Borrowed(b"hello"), usize::MAX),
+            Err(WireError::UnsupportedEncoding(coding)) if coding == "x-custom"
+        ));
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn test_brotli() {
+        // "hello" as a single uncompressed meta-block
+        let compressed = b"\x0b\x02\x80hello\x03";
+        let headers = [header(b"identity"), header(b" BR ")];
+        let body = decode_content(&headers, Cow::Borrowed(compressed), 5).unwrap();
+        assert_eq!(&body[..], b"hello");
+
+        // Output beyond the limit is not decompressed
+        assert_eq!(
+            decode_content(&headers, Cow::Borrowed(compressed), 4).err(),
+            Some(WireError::BodyTooLarge)
+        );
+
+        assert!(matches!(
+            decode_content(&headers, Cow::Borrowed(b"\xffnot brotli"), usize::MAX),
+            Err(WireError::InvalidContentEncoding("br"))
+        ));
+    }
+
+    #[cfg(feature = "compression")]
+    fn compress<W: std::io::Write>(mut encoder: W, data: &[u8]) -> W {
+        encoder.write_all(data).unwrap();
+        encoder
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_gzip_and_deflate() {
+        use flate2::Compression;
+        use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+
+        let gzip = compress(GzEncoder::new(Vec::new(), Compression::default()), b"hello")
+            .finish()
+            .unwrap();
+        let body = decode_content(&[header(b"X-Gzip")], Cow::Borrowed(&gzip), usize::MAX).unwrap();
+        assert_eq!(&body[..], b"hello");
+
+        let zlib = compress(
+            ZlibEncoder::new(Vec::new(), Compression::default()),
+            b"hello",
+        )
+        .finish()
+        .unwrap();
+        let raw = compress(
+            DeflateEncoder::new(Vec::new(), Compression::default()),
+            b"hello",
+        )
+        .finish()
+        .unwrap();
+        for data in [zlib, raw] {
+            let body =
+                decode_content(&[header(b"deflate")], Cow::Borrowed(&data), usize::MAX).unwrap();
+            assert_eq!(&body[..], b"hello");
+        }
+
+        // Codings are removed last to first
+        let gzip_of_zlib = compress(
+            GzEncoder::new(Vec::new(), Compression::default()),
+            &compress(
+                ZlibEncoder::new(Vec::new(), Compression::default()),
+                b"hello",
+            )
+            .finish()
+            .unwrap(),
+        )
+        .finish()
+        .unwrap();
+        let headers = [header(b"deflate, gzip")];
+        let body = decode_content(&headers, Cow::Borrowed(&gzip_of_zlib), usize::MAX).unwrap();
+        assert_eq!(&body[..], b"hello");
+
+        assert!(matches!(
+            decode_content(&[header(b"gzip")], Cow::Borrowed(b"not gzip"), usize::MAX),
+            Err(WireError::InvalidContentEncoding("gzip"))
+        ));
+
+        // A small body that expands past the limit fails instead of filling memory
+        let zeros = vec![0; 1 << 20];
+        let gzip = compress(GzEncoder::new(Vec::new(), Compression::best()), &zeros)
+            .finish()
+            .unwrap();
+        let zlib = compress(ZlibEncoder::new(Vec::new(), Compression::best()), &zeros)
+            .finish()
+            .unwrap();
+        for (coding, data) in [(&b"gzip"[..], gzip), (b"deflate", zlib)] {
+            let headers = [header(coding)];
+            let body = decode_content(&headers, Cow::Borrowed(&data), 1 << 20).unwrap();
+            assert_eq!(body.len(), 1 << 20);
+            assert_eq!(
+                decode_content(&headers, Cow::Borrowed(&data), 1000).err(),
+                Some(WireError::BodyTooLarge)
+            );
+        }
+    }
+}
@@ -0,0 +1,667 @@
+//! Options and state for decoding messages.
+//!
+//! [`DecodeOptions`] is passed to
+//! [`FullRequest::decode_with_options`](crate::request::FullRequest::decode_with_options)
+//! and [`FullResponse::decode_with_options`](crate::response::FullResponse::decode_with_options)
+//! to bound the work done on untrusted input, and [`Limits`] sets all the size limits
+//! at once. [`Streaming`] decodes messages from data that arrives in pieces.
+
+#[cfg(feature = "std")]
+use bytes::BytesMut;
+
+#[cfg(feature = "std")]
+use crate::chunked::parse_chunk_size;
+use crate::chunked::{ChunkRules, ChunkedError, walk_chunked};
+use crate::error::WireError;
+#[cfg(feature = "std")]
+use crate::message::{Message, OwnedMessage, decode_message};
+#[cfg(feature = "std")]
+use crate::request::OwnedRequest;
+#[cfg(feature = "std")]
+use crate::response::OwnedResponse;
+#[cfg(feature = "std")]
+use crate::session::HeaderStorage;
+#[cfg(feature = "std")]
+use crate::util::{find_blank_line, find_lf, flag_incomplete_chunked};
+
+/// Options applied when decoding an HTTP message.
+///
+/// The default options impose no limits, matching [`WireDecode::decode`](crate::WireDecode::decode).
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{DecodeOptions, WireError};
+/// use http_wire::request::FullRequest;
+///
+/// let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\n1\r\nb\r\n1\r\nc\r\n0\r\n\r\n";
+/// let options = DecodeOptions::new().max_chunks(2);
+/// let mut headers = [httparse::EMPTY_HEADER; 16];
+///
+/// let result = FullRequest::decode_with_options(raw, &mut headers, &options);
+/// assert!(matches!(result, Err(WireError::TooManyChunks { .. })));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DecodeOptions {
+    pub(crate) max_chunks: Option<usize>,
+    pub(crate) max_chunk_size: Option<usize>,
+    pub(crate) max_head_bytes: Option<usize>,
+    pub(crate) max_headers: Option<usize>,
+    pub(crate) max_body_bytes: Option<usize>,
+    pub(crate) close_delimited: bool,
+    pub(crate) head_response: bool,
+    pub(crate) allow_bare_lf: bool,
+    pub(crate) allowed_methods: Option<&'static [&'static str]>,
+    pub(crate) strict: bool,
+}
+
+impl DecodeOptions {
+    /// Creates options with no limits.
+    pub const fn new() -> Self {
+        Self {
+            max_chunks: None,
+            max_chunk_size: None,
+            max_head_bytes: None,
+            max_headers: None,
+            max_body_bytes: None,
+            close_delimited: false,
+            head_response: false,
+            allow_bare_lf: false,
+            allowed_methods: None,
+            strict: false,
+        }
+    }
+
+    /// Limits the size of the header section, from the start line to the blank line
+    /// that ends it.
+    ///
+    /// Decoding fails with [`WireError::HeadTooLarge`] once the head is longer than
+    /// `max` bytes, including while it is still incomplete. Without this limit, a peer
+    /// that keeps sending header lines gets [`WireError::PartialHead`] forever while
+    /// the caller's buffer grows.
+    pub fn max_head_bytes(mut self, max: usize) -> Self {
+        self.max_head_bytes = Some(max);
+        self
+    }
+
+    /// Limits the number of header fields.
+    ///
+    /// Decoding a message with more than `max` headers fails with
+    /// [`WireError::HttparseError`] wrapping [`httparse::Error::TooManyHeaders`], as
+    /// if the header storage only had `max` slots.
+    pub fn max_headers(mut self, max: usize) -> Self {
+        self.max_headers = Some(max);
+        self
+    }
+
+    /// Limits the size of the body.
+    ///
+    /// Decoding fails with [`WireError::BodyTooLarge`] when `Content-Length` is larger
+    /// than `max`, without waiting for the body to arrive. A chunked or close-delimited
+    /// body fails as soon as more than `max` bytes of it are buffered; the limit
+    /// applies to a chunked body as framed, including chunk-size lines and trailers.
+    pub fn max_body_bytes(mut self, max: usize) -> Self {
+        self.max_body_bytes = Some(max);
+        self
+    }
+
+    /// Limits the number of data chunks accepted in a chunked body.
+    ///
+    /// Decoding fails with [`WireError::TooManyChunks`]
+    /// as soon as a chunk beyond `max` is seen. The terminating zero-size chunk is not
+    /// counted. This guards against chunk floods, where a small body split into
+    /// millions of tiny chunks costs far more to parse than its size suggests.
+    pub fn max_chunks(mut self, max: usize) -> Self {
+        self.max_chunks = Some(max);
+        self
+    }
+
+    /// Limits the size of each data chunk in a chunked body.
+    ///
+    /// Decoding fails with [`WireError::ChunkTooLarge`] as soon as a chunk-size line
+    /// declares more than `max` bytes, without waiting for the chunk data. A size that
+    /// does not fit in `usize` is always rejected with [`WireError::InvalidChunkSize`].
+    pub fn max_chunk_size(mut self, max: usize) -> Self {
+        self.max_chunk_size = Some(max);
+        self
+    }
+
+    /// Treats a response without `Content-Length` or chunked `Transfer-Encoding` as
+    /// delimited by the connection closing.
+    ///
+    /// Such a response's body is everything after the head, so the buffer must hold
+    /// all the data read until the peer closed the connection. This is how HTTP/1.0
+    /// servers and some HTTP/1.1 servers send a body of unknown length (RFC 9112
+    /// §6.3). Without this option the body is taken to be empty.
+    ///
+    /// Requests are never delimited this way: a request without framing headers has
+    /// no body. Responses that cannot have a body (1xx, 204 and 304) are not
+    /// affected either.
+    ///
+    /// A response whose `Transfer-Encoding` does not end in `chunked` can only be
+    /// delimited this way, so without this option it fails with
+    /// [`WireError::UnsupportedTransferCoding`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use http_wire::DecodeOptions;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\nuntil close";
+    /// let options = DecodeOptions::new().assume_close_delimited(true);
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    ///
+    /// let (response, len) = FullResponse::decode_with_options(raw, &mut headers, &options).unwrap();
+    /// assert_eq!(response.body, b"until close");
+    /// assert_eq!(len, raw.len());
+    /// # }
+    /// ```
+    pub fn assume_close_delimited(mut self, enabled: bool) -> Self {
+        self.close_delimited = enabled;
+        self
+    }
+
+    /// Decodes responses as replies to a `HEAD` request, which never have a body.
+    ///
+    /// A response to `HEAD` carries the framing headers of the body a `GET` would
+    /// have received, but not the body itself (RFC 9110 §9.3.2). With this option the
+    /// body is empty and the message ends with the head, whatever its
+    /// `Content-Length` or `Transfer-Encoding`; the framing headers are still
+    /// validated, and [`FullResponse::content_length`](crate::response::FullResponse::content_length)
+    /// is still set. Requests are not affected.
+    ///
+    /// See [`FullResponse::decode_for_method`](crate::response::FullResponse::decode_for_method).
+    pub fn response_to_head(mut self, enabled: bool) -> Self {
+        self.head_response = enabled;
+        self
+    }
+
+    /// Accepts chunk-size lines, chunk data and trailer lines that end with a bare LF
+    /// instead of CRLF.
+    ///
+    /// Some embedded and legacy servers terminate lines with `\n` alone. The head is
+    /// parsed by httparse, which already accepts bare LF line endings, but by default
+    /// the framing of a chunked body must use CRLF and a bare LF fails with
+    /// [`WireError::InvalidChunkedBody`].
+    ///
+    /// Only enable this for peers known to need it. When a proxy and the server behind
+    /// it disagree on where a line or a chunk ends, one of them can be made to see a
+    /// request that the other treats as body data, which is how request smuggling
+    /// works. A message accepted with this option should be re-encoded with CRLF line
+    /// endings, never forwarded as is.
+    ///
+    /// The body keeps its bare LF line endings. [`RequestCodec`](crate::RequestCodec)
+    /// and [`ResponseCodec`](crate::ResponseCodec) built with this option remove the
+    /// chunked framing with bare LF allowed too. The methods of a decoded message that
+    /// remove it, such as `into_http`, `body_chunks`, `trailers` and
+    /// `decode_body_decompressed`, do not know how the message was decoded: they only
+    /// accept CRLF and fail on such a body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "std")] {
+    /// use http_wire::{DecodeOptions, WireDecode};
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\n5\nhello\n0\n\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// assert!(FullResponse::decode(raw, &mut headers).is_err());
+    ///
+    /// let options = DecodeOptions::new().allow_bare_lf(true);
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, len) = FullResponse::decode_with_options(raw, &mut headers, &options).unwrap();
+    /// assert_eq!(response.body, b"5\nhello\n0\n\n");
+    /// assert_eq!(len, raw.len());
+    /// # }
+    /// ```
+    pub fn allow_bare_lf(mut self, enabled: bool) -> Self {
+        self.allow_bare_lf = enabled;
+        self
+    }
+
+    /// Only accepts requests whose method is one of `methods`.
+    ///
+    /// Methods are compared case-sensitively (RFC 9110 §9.1). A request with any other
+    /// method, or with a method containing a byte that is not a token character, fails
+    /// with [`WireError::InvalidMethod`] as soon as the method has been received,
+    /// before the rest of the head is parsed. Without this option such bytes are
+    /// reported as [`WireError::Malformed`]. Responses are not affected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::{DecodeOptions, WireError};
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let options = DecodeOptions::new().allowed_methods(&["GET", "HEAD"]);
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    ///
+    /// let result = FullRequest::decode_with_options(b"DELETE / HTTP/1.1\r\n\r\n", &mut headers, &options);
+    /// assert!(matches!(result, Err(WireError::InvalidMethod)));
+    /// ```
+    pub fn allowed_methods(mut self, methods: &'static [&'static str]) -> Self {
+        self.allowed_methods = Some(methods);
+        self
+    }
+
+    /// Reports obsolete line folding with a dedicated error.
+    ///
+    /// A header value continued on a line starting with a space or tab is obsolete
+    /// (RFC 9112 §5.2) and can be used to smuggle headers past a peer that unfolds
+    /// it. Such a head is never accepted: by default it fails like any
+    /// other malformed head with [`WireError::Malformed`], and with this option with
+    /// [`WireError::ObsoleteFolding`] at the start of the continuation line.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::{DecodeOptions, WireError};
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"GET / HTTP/1.1\r\nX-Foo:\r\n bar\r\n\r\n";
+    /// let options = DecodeOptions::new().strict(true);
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    ///
+    /// let result = FullRequest::decode_with_options(raw, &mut headers, &options);
+    /// assert!(matches!(result, Err(WireError::ObsoleteFolding { offset: 24 })));
+    /// ```
+    pub fn strict(mut self, enabled: bool) -> Self {
+        self.strict = enabled;
+        self
+    }
+
+    /// Replaces a [`WireError::Malformed`] head error with a more specific one when
+    /// [`strict`](Self::strict) is enabled.
+    pub(crate) fn refine_head_error(&self, err: WireError, buf: &[u8]) -> WireError {
+        match err {
+            WireError::Malformed { offset, .. }
+                if self.strict
+                    && matches!(buf.get(offset), Some(b' ' | b'\t'))
+                    && offset > 0
+                    && buf[offset - 1] == b'\n' =>
+            {
+                WireError::ObsoleteFolding { offset }
+            }
+            err => err,
+        }
+    }
+
+    /// Checks `head_len`, the length of a complete head or of the buffered part of an
+    /// incomplete one, against the head size limit.
+    #[inline]
+    pub(crate) fn check_head_len(&self, head_len: usize) -> Result<(), WireError> {
+        match self.max_head_bytes {
+            Some(max) if head_len > max => Err(WireError::HeadTooLarge),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the number of headers of a complete head against the header limit.
+    #[inline]
+    pub(crate) fn check_header_count(&self, count: usize) -> Result<(), WireError> {
+        match self.max_headers {
+            Some(max) if count > max => Err(httparse::Error::TooManyHeaders.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the length of a body, complete or not, against the body size limit.
+    #[inline]
+    pub(crate) fn check_body_len(&self, body_len: usize) -> Result<(), WireError> {
+        match self.max_body_bytes {
+            Some(max) if body_len > max => Err(WireError::BodyTooLarge),
+            _ => Ok(()),
+        }
+    }
+
+    /// Frames the chunked body following a head of `headers_len` bytes in `buf`, and
+    /// returns its length.
+    pub(crate) fn chunked_body_len(
+        &self,
+        buf: &[u8],
+        headers_len: usize,
+    ) -> Result<usize, WireError> {
+        let body = &buf[headers_len..];
+        // Only the allowed part of the body is parsed, so running out of it means the
+        // body is too large
+        let allowed = &body[..body.len().min(self.max_body_bytes.unwrap_or(usize::MAX))];
+        let rules = ChunkRules {
+            bare_lf: self.allow_bare_lf,
+            max_chunks: self.max_chunks.unwrap_or(usize::MAX),
+            max_chunk_size: self.max_chunk_size.unwrap_or(usize::MAX),
+        };
+        match walk_chunked(allowed, rules, |_, _| {}, |_| {}) {
+            Ok(layout) => Ok(layout.framed_len),
+            Err(ChunkedError::Incomplete) if allowed.len() < body.len() => {
+                Err(WireError::BodyTooLarge)
+            }
+            Err(err) => Err(err.at(headers_len, allowed.len())),
+        }
+    }
+}
+
+/// Size limits for decoding untrusted input.
+///
+/// Every field is enforced, so decoding does a bounded amount of work and never waits
+/// for an unbounded amount of data, whatever the input. This makes it suitable for
+/// fuzzing or for parsing traffic from unauthenticated peers. The [`Default`] values
+/// are conservative; raise them for peers known to send larger messages.
+///
+/// Pass it to [`FullRequest::decode_limited`](crate::request::FullRequest::decode_limited)
+/// or [`FullResponse::decode_limited`](crate::response::FullResponse::decode_limited),
+/// or convert it into [`DecodeOptions`] to combine it with other options.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{Limits, WireError};
+/// use http_wire::request::FullRequest;
+///
+/// let limits = Limits {
+///     max_body_bytes: 4,
+///     ..Limits::default()
+/// };
+/// let mut headers = [httparse::EMPTY_HEADER; 16];
+/// let raw = b"POST / HTTP/1.1\r\nContent-Length: 1000000\r\n\r\n";
+/// let result = FullRequest::decode_limited(raw, &mut headers, &limits);
+/// assert!(matches!(result, Err(WireError::BodyTooLarge)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum number of header fields, see [`DecodeOptions::max_headers`]. Defaults
+    /// to 64.
+    pub max_headers: usize,
+    /// Maximum size of the header section, see [`DecodeOptions::max_head_bytes`].
+    /// Defaults to 8 KiB.
+    pub max_head_bytes: usize,
+    /// Maximum size of the body, see [`DecodeOptions::max_body_bytes`]. Defaults to
+    /// 1 MiB.
+    pub max_body_bytes: usize,
+    /// Maximum number of data chunks in a chunked body, see
+    /// [`DecodeOptions::max_chunks`]. Defaults to 1024.
+    pub max_chunk_count: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_headers: 64,
+            max_head_bytes: 8 * 1024,
+            max_body_bytes: 1024 * 1024,
+            max_chunk_count: 1024,
+        }
+    }
+}
+
+impl From<Limits> for DecodeOptions {
+    fn from(limits: Limits) -> Self {
+        DecodeOptions::new()
+            .max_headers(limits.max_headers)
+            .max_head_bytes(limits.max_head_bytes)
+            .max_body_bytes(limits.max_body_bytes)
+            .max_chunks(limits.max_chunk_count)
+    }
+}
+
+/// An incremental decoder for messages that arrive in pieces.
+///
+/// Data is appended with [`feed`](Self::feed) and complete messages are taken with
+/// [`next_message`](Self::next_message), in order. Requests and responses may be mixed;
+/// each is decoded like [`decode_message`].
+///
+/// The decoder remembers how far it has looked into an incomplete message, so a
+/// message that trickles in a few bytes at a time is not parsed again from the start
+/// on every call. The full parse is only run once the end of the head has arrived and
+/// the body can be complete: once `Content-Length` bytes are buffered, or once the
+/// last chunk of a chunked body and its trailers are.
+///
+/// Decoded messages are [`Bytes`](bytes::Bytes) slices of the internal buffer, which
+/// is reclaimed once they are dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{OwnedMessage, Streaming};
+///
+/// let mut decoder = Streaming::new();
+/// decoder.feed(b"POST /items HTTP/1.1\r\nContent-Len");
+/// assert!(decoder.next_message().unwrap().is_none());
+///
+/// decoder.feed(b"gth: 5\r\n\r\nhel");
+/// assert!(decoder.next_message().unwrap().is_none());
+///
+/// decoder.feed(b"loHTTP/1.1 204 No Content\r\n\r\n");
+/// let Some(OwnedMessage::Request(request)) = decoder.next_message().unwrap() else {
+///     panic!("expected a request");
+/// };
+/// assert_eq!(request.body, "hello");
+/// assert!(matches!(decoder.next_message(), Ok(Some(OwnedMessage::Response(_)))));
+/// assert!(decoder.next_message().unwrap().is_none());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct Streaming {
+    buf: BytesMut,
+    headers: HeaderStorage,
+    scan: Scan,
+}
+
+/// How far the message at the front of the buffer has been examined.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+enum Scan {
+    /// Looking for the blank line ending the head; none starts before `from`.
+    Head { from: usize },
+    /// The head is complete and `head_len` bytes long; the parser frames the body.
+    Body { head_len: usize },
+    /// The message cannot be complete before `len` bytes are buffered.
+    Length { len: usize },
+    /// Walking a chunked body; the next chunk-size line starts at `pos`.
+    Chunks { pos: usize },
+    /// Looking for the blank line ending the trailers; none starts before `from`.
+    Trailers { from: usize },
+}
+
+#[cfg(feature = "std")]
+impl Default for Scan {
+    fn default() -> Self {
+        Scan::Head { from: 0 }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Streaming {
+    /// Creates a decoder for messages with up to 64 headers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a decoder for messages with up to `max_headers` headers.
+    ///
+    /// Decoding a message with more headers fails with
+    /// [`WireError::HttparseError`].
+    pub fn with_max_headers(max_headers: usize) -> Self {
+        Self {
+            headers: HeaderStorage::new(max_headers),
+            ..Self::default()
+        }
+    }
+
+    /// Appends received data to the buffer.
+    pub fn feed(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    /// Returns the number of buffered bytes not yet returned as a message.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Takes the next complete message from the buffer.
+    ///
+    /// Returns `Ok(None)` if more data is needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode_message`] for a malformed message. The
+    /// malformed data stays in the buffer, so later calls fail the same way.
+    pub fn next_message(&mut self) -> Result<Option<OwnedMessage>, WireError> {
+        if !self.may_be_complete() {
+            return Ok(None);
+        }
+
+        let len = match decode_message(&self.buf, self.headers.slots()) {
+            Ok((_, len)) => len,
+            Err(err) => {
+                self.scan = match (flag_incomplete_chunked(err, self.buf.len()), self.scan) {
+                    (WireError::IncompleteBody(missing), _) => Scan::Length {
+                        len: self.buf.len() + missing,
+                    },
+                    (WireError::IncompleteChunkedBody, Scan::Body { head_len }) => {
+                        Scan::Chunks { pos: head_len }
+                    }
+                    // The scan and the parser disagree: parse again on any new data
+                    (WireError::PartialHead | WireError::IncompleteChunkedBody, _) => {
+                        Scan::Length {
+                            len: self.buf.len() + 1,
+                        }
+                    }
+                    (err, _) => return Err(err),
+                };
+                return Ok(None);
+            }
+        };
+
+        self.scan = Scan::default();
+        let buf = self.buf.split_to(len).freeze();
+        let message = match decode_message(&buf, self.headers.slots())? {
+            (Message::Request(request), _) => {
+                OwnedMessage::Request(OwnedRequest::from_full(&buf, &request))
+            }
+            (Message::Response(response), _) => {
+                OwnedMessage::Response(OwnedResponse::from_full(&buf, &response))
+            }
+        };
+        Ok(Some(message))
+    }
+
+    /// Advances the scan over the buffered data, returning `true` once the message at
+    /// the front may be complete.
+    fn may_be_complete(&mut self) -> bool {
+        let buf = &self.buf[..];
+        loop {
+            match self.scan {
+                Scan::Head { from } => {
+                    let Some(head_len) = find_blank_line(buf, from) else {
+                        self.scan = Scan::Head {
+                            from: buf.len().saturating_sub(2),
+                        };
+                        return false;
+                    };
+                    self.scan = Scan::Body { head_len };
+                    return true;
+                }
+                Scan::Body { .. } => return true,
+                Scan::Length { len } => return buf.len() >= len,
+                Scan::Chunks { pos } => {
+                    let Some(lf) = find_lf(buf, pos) else {
+                        return false;
+                    };
+                    let line = &buf[pos..lf];
+                    match parse_chunk_size(line.strip_suffix(b"\r").unwrap_or(line)) {
+                        Some(0) => self.scan = Scan::Trailers { from: lf },
+                        Some(size) => {
+                            let next = (lf + 1).saturating_add(size).saturating_add(2);
+                            if buf.len() < next {
+                                return false;
+                            }
+                            self.scan = Scan::Chunks { pos: next };
+                        }
+                        // Let the parser report the error
+                        None => return true,
+                    }
+                }
+                Scan::Trailers { from } => {
+                    if find_blank_line(buf, from).is_some() {
+                        return true;
+                    }
+                    self.scan = Scan::Trailers {
+                        from: buf.len().saturating_sub(2).max(from),
+                    };
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streaming_byte_at_a_time() {
+        let input: &[u8] = b"POST /a HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3;ext\r\nabc\r\n0\r\nX-Sum: 1\r\n\r\n\
+            HTTP/1.1 304 Not Modified\r\nContent-Length: 100\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+        let mut decoder = Streaming::new();
+        let mut messages = Vec::new();
+
+        for byte in input.chunks(1) {
+            decoder.feed(byte);
+            while let Some(message) = decoder.next_message().unwrap() {
+                messages.push(message);
+            }
+        }
+
+        assert_eq!(decoder.buffered(), 0);
+        let [
+            OwnedMessage::Request(request),
+            OwnedMessage::Response(not_modified),
+            OwnedMessage::Response(ok),
+        ] = &messages[..]
+        else {
+            panic!("unexpected messages: {messages:?}");
+        };
+        assert_eq!(request.body, "3;ext\r\nabc\r\n0\r\nX-Sum: 1\r\n\r\n");
+        assert_eq!(not_modified.code, 304);
+        assert_eq!(ok.body, "ok");
+    }
+
+    #[test]
+    fn test_streaming_skips_parse_until_body_can_be_complete() {
+        let mut decoder = Streaming::new();
+        decoder.feed(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n");
+        assert!(decoder.next_message().unwrap().is_none());
+        assert!(matches!(decoder.scan, Scan::Length { len: 49 }));
+        decoder.feed(b"01234");
+        assert!(decoder.next_message().unwrap().is_none());
+        decoder.feed(b"56789");
+        assert!(decoder.next_message().unwrap().is_some());
+
+        let mut decoder = Streaming::new();
+        decoder.feed(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel");
+        assert!(decoder.next_message().unwrap().is_none());
+        assert!(matches!(decoder.scan, Scan::Chunks { pos: 47 }));
+        decoder.feed(b"lo\r\n0\r\n");
+        assert!(decoder.next_message().unwrap().is_none());
+        assert!(matches!(decoder.scan, Scan::Trailers { .. }));
+        decoder.feed(b"\r\n");
+        assert!(decoder.next_message().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_streaming_error_persists() {
+        let mut decoder = Streaming::with_max_headers(1);
+        decoder.feed(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n");
+        for _ in 0..2 {
+            assert!(matches!(
+                decoder.next_message(),
+                Err(WireError::HttparseError(httparse::Error::TooManyHeaders))
+            ));
+        }
+    }
+}
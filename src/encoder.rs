@@ -0,0 +1,1038 @@
+//! Options controlling how messages are encoded.
+//!
+//! [`EncodeOptions`] is passed to [`WireEncode::encode_with`](crate::WireEncode::encode_with)
+//! to adjust the serialized output beyond what the `http` types can express.
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use std::borrow::Cow;
+
+use crate::chunked::walk_chunks;
+use crate::error::WireError;
+#[cfg(feature = "digest")]
+use crate::util::find_header;
+use crate::util::write_header;
+use crate::util::{find_head_end, has_chunked_encoding};
+
+/// Default name of the header used by [`EncodeOptions::pad_to`].
+const DEFAULT_PADDING_HEADER: &str = "X-Padding";
+
+/// How the header section of an encoded message is terminated.
+///
+/// Used with [`EncodeOptions::terminator`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Terminator {
+    /// The standard terminator: the last header line ends with `\r\n`, followed
+    /// by an empty `\r\n` line.
+    #[default]
+    CrLfCrLf,
+    /// A bare-LF terminator: the last header line ends with `\n`, followed by an
+    /// empty `\n` line.
+    LfLf,
+    /// No blank line at all: every header line is terminated by `\r\n`, but the
+    /// head is left open and the body is omitted.
+    ///
+    /// The output ends with the single `\r\n` of the last header line, so a server
+    /// reading it keeps waiting for the rest of the head. Useful for exercising
+    /// header read timeouts.
+    None,
+}
+
+/// How a status line with an empty reason phrase is written.
+///
+/// Used with [`EncodeOptions::empty_reason_style`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyReason {
+    /// Keep the space after the status code: `HTTP/1.1 200 \r\n`, as required by the
+    /// RFC 9112 grammar.
+    WithSpace,
+    /// Drop the space after the status code: `HTTP/1.1 200\r\n`, as sent by some
+    /// servers.
+    WithoutSpace,
+}
+
+/// Hash algorithm used by [`EncodeOptions::add_content_digest`].
+///
+/// Requires the `digest` feature.
+#[cfg(feature = "digest")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    /// SHA-256, registered as `sha-256`.
+    Sha256,
+    /// SHA-512, registered as `sha-512`.
+    Sha512,
+}
+
+#[cfg(feature = "digest")]
+impl DigestAlgorithm {
+    /// Returns the `Content-Digest` field value for `content` (RFC 9530 §2).
+    fn field_value(self, content: &[u8]) -> String {
+        use base64::Engine;
+        use sha2::Digest;
+
+        let (key, hash) = match self {
+            DigestAlgorithm::Sha256 => ("sha-256", sha2::Sha256::digest(content).to_vec()),
+            DigestAlgorithm::Sha512 => ("sha-512", sha2::Sha512::digest(content).to_vec()),
+        };
+        let encoded = base64::engine::general_purpose::STANDARD.encode(hash);
+        format!("{key}=:{encoded}:")
+    }
+}
+
+/// Options applied when encoding an HTTP message.
+///
+/// Options are set with chained builder methods and passed by reference to
+/// [`WireEncode::encode_with`](crate::WireEncode::encode_with).
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{EncodeOptions, WireEncode};
+/// use http::Request;
+/// use http_body_util::Empty;
+/// use bytes::Bytes;
+///
+/// let request = Request::builder()
+///     .uri("/")
+///     .header("Host", "example.com")
+///     .body(Empty::<Bytes>::new())
+///     .unwrap();
+///
+/// let options = EncodeOptions::new().pad_to(256);
+/// let bytes = request.encode_with(&options).unwrap();
+/// assert_eq!(bytes.len(), 256);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EncodeOptions {
+    pad_to: Option<usize>,
+    padding_header: String,
+    padding_fill: u8,
+    terminator: Terminator,
+    pin_first: Vec<String>,
+    pin_last: Vec<String>,
+    chunk_size: Option<usize>,
+    force_chunked: bool,
+    reason: Option<String>,
+    empty_reason: Option<EmptyReason>,
+    #[cfg(feature = "digest")]
+    digest: Option<DigestAlgorithm>,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            pad_to: None,
+            padding_header: DEFAULT_PADDING_HEADER.to_owned(),
+            padding_fill: b' ',
+            terminator: Terminator::CrLfCrLf,
+            pin_first: Vec::new(),
+            pin_last: Vec::new(),
+            chunk_size: None,
+            force_chunked: false,
+            reason: None,
+            empty_reason: None,
+            #[cfg(feature = "digest")]
+            digest: None,
+        }
+    }
+}
+
+impl EncodeOptions {
+    /// Creates options that leave the encoded message unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pads the header section so the whole message is exactly `size` bytes.
+    ///
+    /// A padding header (`X-Padding` by default) is appended as the last header, with
+    /// its value filled with the padding byte (a space by default). Encoding fails with
+    /// [`WireError::PaddingOverflow`] if the message plus an empty padding header is
+    /// already larger than `size`.
+    pub fn pad_to(mut self, size: usize) -> Self {
+        self.pad_to = Some(size);
+        self
+    }
+
+    /// Sets the name of the header used by [`pad_to`](Self::pad_to).
+    pub fn padding_header(mut self, name: impl Into<String>) -> Self {
+        self.padding_header = name.into();
+        self
+    }
+
+    /// Sets the byte used to fill the padding header value.
+    ///
+    /// The byte is written as-is; choose a valid header value byte unless you are
+    /// deliberately producing malformed messages.
+    pub fn padding_fill(mut self, fill: u8) -> Self {
+        self.padding_fill = fill;
+        self
+    }
+
+    /// Sets how the header section is terminated.
+    ///
+    /// Anything other than [`Terminator::CrLfCrLf`] produces a malformed message,
+    /// which is useful for testing how servers handle broken or stalled input.
+    pub fn terminator(mut self, terminator: Terminator) -> Self {
+        self.terminator = terminator;
+        self
+    }
+
+    /// Moves the named headers to the front of the header section.
+    ///
+    /// Pinned headers are written in the order of `names`, matched case-insensitively;
+    /// repeated headers keep their relative order. All other headers keep their
+    /// original order. A name listed in both this and
+    /// [`pin_headers_last`](Self::pin_headers_last) is pinned first.
+    pub fn pin_headers_first(mut self, names: &[&str]) -> Self {
+        self.pin_first = names.iter().map(|n| n.to_string()).collect();
+        self
+    }
+
+    /// Moves the named headers to the back of the header section.
+    ///
+    /// Works like [`pin_headers_first`](Self::pin_headers_first). The padding header
+    /// added by [`pad_to`](Self::pad_to) is still written after the pinned headers.
+    pub fn pin_headers_last(mut self, names: &[&str]) -> Self {
+        self.pin_last = names.iter().map(|n| n.to_string()).collect();
+        self
+    }
+
+    /// Splits a chunked body into chunks of at most `size` bytes.
+    ///
+    /// Only applies to messages encoded with `Transfer-Encoding: chunked`, such as
+    /// those with a streaming body, an explicit `Transfer-Encoding` header or
+    /// [`force_chunked`](Self::force_chunked). Each
+    /// chunk is framed individually and the last one may be shorter; a body smaller
+    /// than `size` is sent as a single chunk. Trailers are kept as they are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero, since a zero-size chunk ends the body.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "chunk size must be greater than zero");
+        self.chunk_size = Some(size);
+        self
+    }
+
+    /// Sends the body with chunked framing even when its length is known.
+    ///
+    /// A message that would be framed by `Content-Length` loses that header and gets
+    /// `transfer-encoding: chunked` instead, and its body is sent as a single chunk,
+    /// or split by [`chunk_size`](Self::chunk_size), followed by the last chunk. An
+    /// empty body is sent as the last chunk alone. Messages that are already chunked
+    /// are left unchanged.
+    ///
+    /// This applies regardless of the version, method or status, so it can produce
+    /// messages that must not be chunked, such as HTTP/1.0 ones; it is meant for
+    /// exercising chunked parsers.
+    pub fn force_chunked(mut self, force: bool) -> Self {
+        self.force_chunked = force;
+        self
+    }
+
+    /// Writes `reason` as the reason phrase of a response status line.
+    ///
+    /// Replaces the canonical reason for the status code, or the one set with a
+    /// [`hyper::ext::ReasonPhrase`], so `201` can be sent as `HTTP/1.1 201 Custom
+    /// Reason`. An empty `reason` is written as set by
+    /// [`empty_reason_style`](Self::empty_reason_style), keeping the space by default.
+    /// Control characters other than HTAB, such as CR and LF, are not allowed in a
+    /// reason phrase (RFC 9112 §4) and are stripped, so the phrase cannot end the
+    /// status line early; the rest is written as-is. Request lines are left unchanged.
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        let mut reason = reason.into();
+        reason.retain(|c| c == '\t' || !c.is_ascii_control());
+        self.reason = Some(reason);
+        self
+    }
+
+    /// Sets how a status line with an empty reason phrase is written.
+    ///
+    /// Only applies to responses whose reason phrase is empty, such as one with an
+    /// empty [`hyper::ext::ReasonPhrase`]; both forms are normalized to `style`.
+    /// Request lines and non-empty reason phrases are left unchanged.
+    pub fn empty_reason_style(mut self, style: EmptyReason) -> Self {
+        self.empty_reason = Some(style);
+        self
+    }
+
+    /// Adds a `Content-Digest` header (RFC 9530) with the hash of the body.
+    ///
+    /// The digest covers the message content: for a chunked body, the chunk data
+    /// without framing or trailers, so it does not depend on
+    /// [`chunk_size`](Self::chunk_size). The header is appended after the existing
+    /// headers, as in `Content-Digest: sha-256=:<base64>:`; a `Content-Digest` already
+    /// present is left as is.
+    ///
+    /// Requires the `digest` feature.
+    #[cfg(feature = "digest")]
+    pub fn add_content_digest(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.digest = Some(algorithm);
+        self
+    }
+
+    /// Returns `true` if no option changes the encoded message.
+    fn is_identity(&self) -> bool {
+        #[cfg(feature = "digest")]
+        if self.digest.is_some() {
+            return false;
+        }
+
+        self.pad_to.is_none()
+            && self.terminator == Terminator::CrLfCrLf
+            && self.pin_first.is_empty()
+            && self.pin_last.is_empty()
+            && self.chunk_size.is_none()
+            && !self.force_chunked
+            && self.reason.is_none()
+            && self.empty_reason.is_none()
+    }
+
+    /// Applies the options to an already serialized message.
+    pub(crate) fn apply(&self, message: Bytes) -> Result<Bytes, WireError> {
+        if self.is_identity() {
+            return Ok(message);
+        }
+
+        // The head always ends at the first blank line. Split it into the header
+        // lines (each terminated by CRLF) and the body.
+        let head_end = find_head_end(&message).ok_or(WireError::PartialHead)?;
+        let chunked = is_chunked_head(&message[..head_end]);
+        let force_chunked = self.force_chunked && !chunked;
+        let lines = Cow::Borrowed(&message[..head_end - 2]);
+        let lines = if force_chunked {
+            Cow::Owned(chunked_lines(&lines))
+        } else {
+            lines
+        };
+        // A `Content-Digest` set by the caller is kept as is
+        #[cfg(feature = "digest")]
+        let digest = self.digest.filter(|_| {
+            !check_head_fields(&message[..head_end], |headers| {
+                find_header(headers, "Content-Digest").is_some()
+            })
+        });
+        #[cfg(feature = "digest")]
+        let lines = match digest {
+            Some(algorithm) => {
+                let content = content_of(&message[..head_end], &message[head_end..])?;
+                let mut lines = lines.into_owned();
+                let value = algorithm.field_value(&content);
+                write_header(&mut lines, b"Content-Digest", value.as_bytes());
+                Cow::Owned(lines)
+            }
+            None => lines,
+        };
+        let lines = lines.as_ref();
+        let body = match self.chunk_size {
+            _ if force_chunked => {
+                // The body is not chunked yet, so it is all content
+                let data = &message[head_end..];
+                let size = self.chunk_size.unwrap_or(data.len().max(1));
+                let mut out = Vec::with_capacity(data.len() + 32);
+                write_chunks(&mut out, data, size);
+                out.extend_from_slice(b"\r\n");
+                Cow::Owned(out)
+            }
+            Some(size) if chunked => Cow::Owned(rechunk(&message[head_end..], size)?),
+            _ => Cow::Borrowed(&message[head_end..]),
+        };
+        let body = body.as_ref();
+
+        let mut out = BytesMut::with_capacity(message.len() + 64);
+        self.write_ordered_lines(&mut out, lines);
+
+        if let Some(target) = self.pad_to {
+            // Bytes written after the header lines for the chosen terminator
+            let tail = match self.terminator {
+                Terminator::CrLfCrLf => 2 + body.len(),
+                Terminator::LfLf => body.len(),
+                Terminator::None => 0,
+            };
+            // name + ": " + value + "\r\n"
+            let overhead = lines.len() + self.padding_header.len() + 4 + tail;
+            if overhead > target {
+                return Err(WireError::PaddingOverflow(overhead));
+            }
+            let fill = vec![self.padding_fill; target - overhead];
+            write_header(&mut out, self.padding_header.as_bytes(), &fill);
+        }
+
+        match self.terminator {
+            Terminator::CrLfCrLf => {
+                out.put_slice(b"\r\n");
+                out.put_slice(body);
+            }
+            Terminator::LfLf => {
+                // Replace the CRLF of the last header line, then add a bare LF
+                out.truncate(out.len() - 2);
+                out.put_slice(b"\n\n");
+                out.put_slice(body);
+            }
+            Terminator::None => {}
+        }
+        Ok(out.freeze())
+    }
+
+    /// Writes the start line and header lines, reordered by the pinned header names.
+    ///
+    /// `lines` is the head without its final blank line, so every line, including the
+    /// last, ends with CRLF.
+    fn write_ordered_lines(&self, out: &mut BytesMut, lines: &[u8]) {
+        let start_end = lines
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(lines.len(), |lf| lf + 1);
+        let (start_line, fields) = lines.split_at(start_end);
+        self.write_start_line(out, start_line);
+
+        if self.pin_first.is_empty() && self.pin_last.is_empty() {
+            out.put_slice(fields);
+            return;
+        }
+
+        let position = |pins: &[String], name: &[u8]| {
+            pins.iter()
+                .position(|p| p.as_bytes().eq_ignore_ascii_case(name))
+        };
+
+        let mut first: Vec<(usize, &[u8])> = Vec::new();
+        let mut middle: Vec<&[u8]> = Vec::new();
+        let mut last: Vec<(usize, &[u8])> = Vec::new();
+        for line in fields.split_inclusive(|&b| b == b'\n') {
+            let name = line.split(|&b| b == b':').next().unwrap_or_default();
+            if let Some(i) = position(&self.pin_first, name) {
+                first.push((i, line));
+            } else if let Some(i) = position(&self.pin_last, name) {
+                last.push((i, line));
+            } else {
+                middle.push(line);
+            }
+        }
+        // Stable sorts keep repeated headers in their original relative order
+        first.sort_by_key(|&(i, _)| i);
+        last.sort_by_key(|&(i, _)| i);
+
+        for line in first
+            .iter()
+            .map(|&(_, l)| l)
+            .chain(middle)
+            .chain(last.iter().map(|&(_, l)| l))
+        {
+            out.put_slice(line);
+        }
+    }
+
+    /// Writes the start line, replacing the reason phrase or normalizing an empty one
+    /// if requested.
+    fn write_start_line(&self, out: &mut BytesMut, line: &[u8]) {
+        let status_line = line.strip_suffix(b"\r\n").unwrap_or(line);
+        // "HTTP/1.x DDD" followed by the reason phrase
+        if let Some(reason) = &self.reason
+            && status_line.len() >= 12
+            && status_line.starts_with(b"HTTP/")
+        {
+            out.put_slice(&status_line[..12]);
+            if !reason.is_empty() || self.empty_reason != Some(EmptyReason::WithoutSpace) {
+                out.put_u8(b' ');
+            }
+            out.put_slice(reason.as_bytes());
+            out.put_slice(b"\r\n");
+            return;
+        }
+        if let Some(style) = self.empty_reason {
+            let status = line.strip_suffix(b"\r\n").unwrap_or(line);
+            let status = status.strip_suffix(b" ").unwrap_or(status);
+            // "HTTP/1.x DDD" with nothing after the status code
+            if status.len() == 12 && status.starts_with(b"HTTP/") {
+                out.put_slice(status);
+                if style == EmptyReason::WithSpace {
+                    out.put_u8(b' ');
+                }
+                out.put_slice(b"\r\n");
+                return;
+            }
+        }
+        out.put_slice(line);
+    }
+}
+
+/// Returns `true` if a serialized head declares a chunked body.
+fn is_chunked_head(head: &[u8]) -> bool {
+    check_head_fields(head, has_chunked_encoding)
+}
+
+/// Parses the header fields of a serialized head and returns what `check` says of
+/// them, or `false` if they do not parse.
+fn check_head_fields(head: &[u8], check: impl FnOnce(&[httparse::Header<'_>]) -> bool) -> bool {
+    // Skip the start line; the rest is the header section and the blank line
+    let Some(start) = head.iter().position(|&b| b == b'\n') else {
+        return false;
+    };
+    let fields = &head[start + 1..];
+    let mut headers = vec![httparse::EMPTY_HEADER; fields.iter().filter(|&&b| b == b'\n').count()];
+    match httparse::parse_headers(fields, &mut headers) {
+        Ok(httparse::Status::Complete((_, headers))) => check(headers),
+        _ => false,
+    }
+}
+
+/// Returns the content of a serialized body, without any chunked framing.
+#[cfg(feature = "digest")]
+fn content_of<'a>(head: &[u8], body: &'a [u8]) -> Result<Cow<'a, [u8]>, WireError> {
+    if !is_chunked_head(head) {
+        return Ok(Cow::Borrowed(body));
+    }
+    let mut data = Vec::with_capacity(body.len());
+    walk_chunks(body, |chunk| data.extend_from_slice(chunk))?;
+    Ok(Cow::Owned(data))
+}
+
+/// Re-frames a complete chunked body into chunks of at most `size` bytes.
+fn rechunk(body: &[u8], size: usize) -> Result<Vec<u8>, WireError> {
+    let mut data = Vec::with_capacity(body.len());
+    let layout = walk_chunks(body, |chunk| data.extend_from_slice(chunk))?;
+
+    let mut out = Vec::with_capacity(body.len() + (data.len() / size + 1) * 12);
+    write_chunks(&mut out, &data, size);
+    out.extend_from_slice(&body[layout.trailers_start..layout.framed_len]);
+    Ok(out)
+}
+
+/// Frames `data` as chunks of at most `size` bytes, followed by the last chunk line.
+fn write_chunks(out: &mut Vec<u8>, data: &[u8], size: usize) {
+    for chunk in data.chunks(size) {
+        out.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+        out.extend_from_slice(chunk);
+        out.extend_from_slice(b"\r\n");
+    }
+    out.extend_from_slice(b"0\r\n");
+}
+
+/// Rewrites the start and header lines of a message to declare a chunked body,
+/// dropping any `Content-Length`.
+fn chunked_lines(lines: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(lines.len() + 28);
+    for (i, line) in lines.split_inclusive(|&b| b == b'\n').enumerate() {
+        let name = line.split(|&b| b == b':').next().unwrap_or_default();
+        if i > 0 && name.eq_ignore_ascii_case(b"content-length") {
+            continue;
+        }
+        out.extend_from_slice(line);
+    }
+    write_header(&mut out, b"transfer-encoding", b"chunked");
+    out
+}
+
+/// Serializes a message head from raw bytes, without any validation.
+///
+/// The output is `first_line`, then each header as `name: value`, each line
+/// terminated by `\r\n`, followed by the blank line that ends the head.
+///
+/// Every byte is passed through unchanged: header names and values may contain
+/// CR, LF, NUL or any other byte that the `http` crate and hyper would reject. This
+/// makes it possible to produce deliberately malformed messages for security testing,
+/// such as CRLF injection through a header value.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::encode_raw_head;
+///
+/// let head = encode_raw_head(b"GET / HTTP/1.1", [(&b"X-Evil"[..], &b"a\r\nInjected: b"[..])]);
+/// assert_eq!(&head[..], b"GET / HTTP/1.1\r\nX-Evil: a\r\nInjected: b\r\n\r\n");
+/// ```
+pub fn encode_raw_head<I, K, V>(first_line: &[u8], headers: I) -> Bytes
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let mut out = BytesMut::with_capacity(first_line.len() + 256);
+    write_raw_head(&mut out, first_line, headers);
+    out.freeze()
+}
+
+/// Writes a head (first line, headers and blank line) verbatim into `out`.
+fn write_raw_head<I, K, V>(out: &mut BytesMut, first_line: &[u8], headers: I)
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    out.put_slice(first_line);
+    out.put_slice(b"\r\n");
+    for (name, value) in headers {
+        write_header(out, name.as_ref(), value.as_ref());
+    }
+    out.put_slice(b"\r\n");
+}
+
+/// Serializes a message with an arbitrary first line.
+///
+/// This is a low-level building block for protocols that reuse HTTP/1.x message
+/// framing with different start lines, such as ICAP, RTSP or SIP. The output is
+/// the head as written by [`encode_raw_head`], followed by the body.
+///
+/// Nothing is validated or added: headers are written verbatim and in order, and no
+/// `Content-Length` is synthesized, so the caller is responsible for framing the body
+/// according to the target protocol.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::encode_custom;
+///
+/// let bytes = encode_custom(
+///     "OPTIONS rtsp://example.com/media RTSP/1.0",
+///     [("CSeq", "1"), ("User-Agent", "http_wire")],
+///     b"",
+/// );
+/// assert_eq!(
+///     &bytes[..],
+///     b"OPTIONS rtsp://example.com/media RTSP/1.0\r\nCSeq: 1\r\nUser-Agent: http_wire\r\n\r\n"
+/// );
+/// ```
+pub fn encode_custom<I, K, V>(first_line: &str, headers: I, body: &[u8]) -> Bytes
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<[u8]>,
+    V: AsRef<[u8]>,
+{
+    let mut out = BytesMut::with_capacity(first_line.len() + body.len() + 256);
+    write_raw_head(&mut out, first_line.as_bytes(), headers);
+    out.put_slice(body);
+    out.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WireEncode;
+    use http_body_util::{Empty, Full};
+
+    fn request() -> http::Request<Empty<Bytes>> {
+        http::Request::builder()
+            .uri("/api")
+            .header("Host", "example.com")
+            .body(Empty::<Bytes>::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pad_to_exact_size() {
+        let bytes = request()
+            .encode_with(&EncodeOptions::new().pad_to(200))
+            .unwrap();
+        assert_eq!(bytes.len(), 200);
+
+        let output = String::from_utf8_lossy(&bytes);
+        assert!(output.contains("\r\nX-Padding:  "));
+        assert!(output.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_pad_to_custom_header_and_fill() {
+        let options = EncodeOptions::new()
+            .pad_to(128)
+            .padding_header("X-Fill")
+            .padding_fill(b'a');
+        let response = http::Response::new(Full::new(Bytes::from("hello")));
+        let bytes = response.encode_with(&options).unwrap();
+
+        assert_eq!(bytes.len(), 128);
+        let output = String::from_utf8_lossy(&bytes);
+        assert!(output.contains("\r\nX-Fill: aaa"));
+        assert!(output.ends_with("\r\n\r\nhello"));
+    }
+
+    #[test]
+    fn test_pad_to_too_small() {
+        let result = request().encode_with(&EncodeOptions::new().pad_to(10));
+        assert!(matches!(result, Err(WireError::PaddingOverflow(_))));
+    }
+
+    #[test]
+    fn test_no_options_is_identity() {
+        let plain = request().encode().unwrap();
+        let with = request().encode_with(&EncodeOptions::new()).unwrap();
+        assert_eq!(plain, with);
+    }
+
+    #[test]
+    fn test_encode_custom_with_body() {
+        let body = b"v=0\r\n";
+        let length = body.len().to_string();
+        let bytes = encode_custom(
+            "INVITE sip:bob@example.com SIP/2.0",
+            [
+                ("Via", "SIP/2.0/UDP host"),
+                ("Content-Length", length.as_str()),
+            ],
+            body,
+        );
+        assert_eq!(
+            &bytes[..],
+            b"INVITE sip:bob@example.com SIP/2.0\r\nVia: SIP/2.0/UDP host\r\nContent-Length: 5\r\n\r\nv=0\r\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_custom_from_header_map() {
+        let mut map = http::HeaderMap::new();
+        map.insert(
+            "encapsulated",
+            http::HeaderValue::from_static("null-body=0"),
+        );
+        let bytes = encode_custom("ICAP/1.0 204 No Content", &map, b"");
+        assert_eq!(
+            &bytes[..],
+            b"ICAP/1.0 204 No Content\r\nencapsulated: null-body=0\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_terminator_lflf() {
+        let response = http::Response::new(Full::new(Bytes::from("hello")));
+        let bytes = response
+            .encode_with(&EncodeOptions::new().terminator(Terminator::LfLf))
+            .unwrap();
+
+        assert!(bytes.ends_with(b"\n\nhello"));
+        assert!(!bytes.windows(4).any(|w| w == b"\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_terminator_none_omits_body() {
+        let response = http::Response::new(Full::new(Bytes::from("hello")));
+        let bytes = response
+            .encode_with(&EncodeOptions::new().terminator(Terminator::None))
+            .unwrap();
+
+        assert!(bytes.ends_with(b"\r\n"));
+        assert!(!bytes.ends_with(b"\r\n\r\n"));
+        assert!(!bytes.ends_with(b"hello"));
+    }
+
+    #[test]
+    fn test_terminator_none_exact_request_head() {
+        let request = http::Request::builder()
+            .uri("/slow")
+            .header("Host", "example.com")
+            .header("Accept", "*/*")
+            .body(Full::new(Bytes::from("ignored")))
+            .unwrap();
+        let bytes = request
+            .encode_with(&EncodeOptions::new().terminator(Terminator::None))
+            .unwrap();
+
+        assert_eq!(
+            &bytes[..],
+            b"GET /slow HTTP/1.1\r\nhost: example.com\r\naccept: */*\r\ncontent-length: 7\r\n"
+        );
+    }
+
+    #[test]
+    fn test_terminator_with_padding() {
+        for terminator in [Terminator::CrLfCrLf, Terminator::LfLf, Terminator::None] {
+            let options = EncodeOptions::new().pad_to(150).terminator(terminator);
+            let bytes = request().encode_with(&options).unwrap();
+            assert_eq!(bytes.len(), 150, "{terminator:?}");
+        }
+    }
+
+    #[test]
+    fn test_pin_headers_first_and_last() {
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/upload")
+            .header("Host", "example.com")
+            .header("Accept", "*/*")
+            .header("X-Trace", "1")
+            .header("User-Agent", "test")
+            .header("x-trace", "2")
+            .body(Full::new(Bytes::from("hi")))
+            .unwrap();
+        let options = EncodeOptions::new()
+            .pin_headers_first(&["Content-Length", "User-Agent"])
+            .pin_headers_last(&["HOST", "x-trace"]);
+        let bytes = request.encode_with(&options).unwrap();
+
+        assert_eq!(
+            &bytes[..],
+            b"POST /upload HTTP/1.1\r\n\
+              content-length: 2\r\n\
+              user-agent: test\r\n\
+              accept: */*\r\n\
+              host: example.com\r\n\
+              x-trace: 1\r\n\
+              x-trace: 2\r\n\
+              \r\nhi"
+        );
+    }
+
+    #[test]
+    fn test_pin_headers_with_padding_and_terminator() {
+        let request = http::Request::builder()
+            .uri("/")
+            .header("Accept", "*/*")
+            .header("Host", "example.com")
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+        let options = EncodeOptions::new()
+            .pin_headers_first(&["host"])
+            .pad_to(200)
+            .terminator(Terminator::LfLf);
+        let bytes = request.encode_with(&options).unwrap();
+
+        assert_eq!(bytes.len(), 200);
+        let head = String::from_utf8_lossy(&bytes);
+        let mut lines = head.lines();
+        lines.next();
+        assert!(lines.next().unwrap().starts_with("host:"));
+        assert!(
+            head.trim_end()
+                .lines()
+                .last()
+                .unwrap()
+                .starts_with("X-Padding:")
+        );
+    }
+
+    fn chunked_request(body: &'static str) -> http::Request<Full<Bytes>> {
+        http::Request::builder()
+            .method("POST")
+            .uri("/upload")
+            .header("Transfer-Encoding", "chunked")
+            .body(Full::new(Bytes::from(body)))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_chunk_size_splits_body() {
+        let bytes = chunked_request("hello world")
+            .encode_with(&EncodeOptions::new().chunk_size(4))
+            .unwrap();
+
+        assert_eq!(
+            &bytes[..],
+            b"POST /upload HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n\
+              4\r\nhell\r\n4\r\no wo\r\n3\r\nrld\r\n0\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_chunk_size_larger_than_body() {
+        let bytes = chunked_request("hi")
+            .encode_with(&EncodeOptions::new().chunk_size(16 * 1024))
+            .unwrap();
+        assert!(bytes.ends_with(b"\r\n\r\n2\r\nhi\r\n0\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_chunk_size_ignored_without_chunked_encoding() {
+        let response = http::Response::new(Full::new(Bytes::from("hello")));
+        let bytes = response
+            .encode_with(&EncodeOptions::new().chunk_size(2))
+            .unwrap();
+        assert!(bytes.ends_with(b"\r\n\r\nhello"));
+        assert!(bytes.windows(18).any(|w| w == b"content-length: 5\r"));
+    }
+
+    #[test]
+    fn test_chunk_size_with_padding() {
+        let options = EncodeOptions::new().chunk_size(1).pad_to(300);
+        let bytes = chunked_request("abc").encode_with(&options).unwrap();
+        assert_eq!(bytes.len(), 300);
+        assert!(bytes.ends_with(b"1\r\na\r\n1\r\nb\r\n1\r\nc\r\n0\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_force_chunked() {
+        let request = || {
+            http::Request::builder()
+                .method("POST")
+                .uri("/upload")
+                .body(Full::new(Bytes::from("hello")))
+                .unwrap()
+        };
+        let forced = EncodeOptions::new().force_chunked(true);
+
+        let bytes = request().encode_with(&forced).unwrap();
+        assert_eq!(
+            &bytes[..],
+            b"POST /upload HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n"
+        );
+
+        let bytes = request()
+            .encode_with(&forced.clone().chunk_size(2))
+            .unwrap();
+        assert!(bytes.ends_with(b"\r\n\r\n2\r\nhe\r\n2\r\nll\r\n1\r\no\r\n0\r\n\r\n"));
+
+        let response = http::Response::new(Empty::<Bytes>::new());
+        let bytes = response.encode_with(&forced).unwrap();
+        assert!(!bytes.windows(15).any(|w| w == b"content-length:"));
+        assert!(bytes.ends_with(b"transfer-encoding: chunked\r\n\r\n0\r\n\r\n"));
+
+        // An already chunked message is not framed twice
+        let bytes = chunked_request("hi").encode_with(&forced).unwrap();
+        assert_eq!(bytes, chunked_request("hi").encode().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be greater than zero")]
+    fn test_chunk_size_zero_panics() {
+        let _ = EncodeOptions::new().chunk_size(0);
+    }
+
+    #[test]
+    fn test_empty_reason_style() {
+        let response = || {
+            let mut response = http::Response::new(Empty::<Bytes>::new());
+            response
+                .extensions_mut()
+                .insert(hyper::ext::ReasonPhrase::from_static(b""));
+            response
+        };
+
+        let bytes = response().encode().unwrap();
+        assert!(bytes.starts_with(b"HTTP/1.1 200 \r\n"));
+
+        let options = EncodeOptions::new().empty_reason_style(EmptyReason::WithoutSpace);
+        let bytes = response().encode_with(&options).unwrap();
+        assert!(bytes.starts_with(b"HTTP/1.1 200\r\ncontent-length: 0\r\n"));
+
+        let options = options.empty_reason_style(EmptyReason::WithSpace);
+        let bytes = response().encode_with(&options).unwrap();
+        assert!(bytes.starts_with(b"HTTP/1.1 200 \r\ncontent-length: 0\r\n"));
+    }
+
+    #[test]
+    fn test_empty_reason_style_keeps_other_start_lines() {
+        let options = EncodeOptions::new().empty_reason_style(EmptyReason::WithoutSpace);
+
+        let bytes = request().encode_with(&options).unwrap();
+        assert_eq!(bytes, request().encode().unwrap());
+
+        let response = http::Response::new(Empty::<Bytes>::new());
+        let bytes = response.encode_with(&options).unwrap();
+        assert!(bytes.starts_with(b"HTTP/1.1 200 OK\r\n"));
+    }
+
+    #[test]
+    fn test_reason_override() {
+        let response = || {
+            http::Response::builder()
+                .status(201)
+                .body(Empty::<Bytes>::new())
+                .unwrap()
+        };
+
+        let bytes = response().encode().unwrap();
+        assert!(bytes.starts_with(b"HTTP/1.1 201 Created\r\n"));
+
+        let options = EncodeOptions::new().reason("Custom Reason");
+        let bytes = response().encode_with(&options).unwrap();
+        assert!(bytes.starts_with(b"HTTP/1.1 201 Custom Reason\r\ncontent-length: 0\r\n"));
+
+        // Line breaks cannot inject header lines
+        let options = EncodeOptions::new().reason("Bad\r\nX-Injected: 1\0\tReason");
+        let bytes = response().encode_with(&options).unwrap();
+        assert!(
+            bytes.starts_with(b"HTTP/1.1 201 BadX-Injected: 1\tReason\r\ncontent-length: 0\r\n")
+        );
+
+        let options = EncodeOptions::new().reason("");
+        let bytes = response().encode_with(&options).unwrap();
+        assert!(bytes.starts_with(b"HTTP/1.1 201 \r\n"));
+        let options = options.empty_reason_style(EmptyReason::WithoutSpace);
+        let bytes = response().encode_with(&options).unwrap();
+        assert!(bytes.starts_with(b"HTTP/1.1 201\r\n"));
+
+        let bytes = request().encode_with(&options).unwrap();
+        assert_eq!(bytes, request().encode().unwrap());
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_add_content_digest() {
+        // RFC 9530 §B.1: the content {"hello": "world"}
+        let body = "{\"hello\": \"world\"}\n";
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/")
+            .body(Full::new(Bytes::from(body)))
+            .unwrap();
+        let options = EncodeOptions::new().add_content_digest(DigestAlgorithm::Sha256);
+        let bytes = request.encode_with(&options).unwrap();
+
+        let output = String::from_utf8_lossy(&bytes);
+        assert!(output.contains(
+            "\r\nContent-Digest: sha-256=:RK/0qy18MlBSVnWgjwz6lZEWjP/lF5HF9bvEF8FabDg=:\r\n\r\n"
+        ));
+        assert!(output.ends_with(body));
+
+        // A digest set by the caller is kept rather than repeated
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/")
+            .header("content-digest", "sha-256=:mine:")
+            .body(Full::new(Bytes::from(body)))
+            .unwrap();
+        let bytes = request.encode_with(&options).unwrap();
+        let output = String::from_utf8_lossy(&bytes);
+        assert_eq!(
+            output
+                .to_ascii_lowercase()
+                .matches("content-digest")
+                .count(),
+            1
+        );
+        assert!(output.contains("content-digest: sha-256=:mine:\r\n"));
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_add_content_digest_ignores_chunking() {
+        let options = EncodeOptions::new().add_content_digest(DigestAlgorithm::Sha512);
+        let digest = |options: &EncodeOptions| {
+            let bytes = chunked_request("hello world").encode_with(options).unwrap();
+            let output = String::from_utf8_lossy(&bytes).into_owned();
+            let start = output.find("Content-Digest: sha-512=:").unwrap();
+            output[start..start + output[start..].find("\r\n").unwrap()].to_owned()
+        };
+
+        assert_eq!(digest(&options), digest(&options.clone().chunk_size(3)));
+        assert_eq!(
+            digest(&options),
+            format!(
+                "Content-Digest: {}",
+                DigestAlgorithm::Sha512.field_value(b"hello world")
+            )
+        );
+    }
+
+    #[test]
+    fn test_encode_raw_head_crlf_injection() {
+        let head = encode_raw_head(
+            b"GET /search HTTP/1.1",
+            [
+                (&b"Host"[..], &b"example.com"[..]),
+                (&b"X-Evil"[..], &b"a\r\nInjected: b"[..]),
+                (&b"X-Nul"[..], &b"before\0after"[..]),
+            ],
+        );
+
+        assert_eq!(
+            &head[..],
+            b"GET /search HTTP/1.1\r\nHost: example.com\r\nX-Evil: a\r\nInjected: b\r\nX-Nul: before\0after\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_raw_head_non_utf8_first_line() {
+        let head = encode_raw_head(b"G\xffT / HTTP/1.1", std::iter::empty::<(&[u8], &[u8])>());
+        assert_eq!(&head[..], b"G\xffT / HTTP/1.1\r\n\r\n");
+    }
+}
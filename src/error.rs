@@ -3,6 +3,9 @@
 //! This module defines the [`WireError`] type, which encompasses all possible errors
 //! that can occur during encoding or decoding.
 
+use alloc::boxed::Box;
+use alloc::string::String;
+
 /// Errors that can occur during HTTP wire format encoding.
 #[derive(Debug, thiserror::Error)]
 pub enum WireError {
@@ -10,13 +13,24 @@ pub enum WireError {
     ///
     /// This occurs when there's a failure during the HTTP handshake
     /// or while transmitting the message through the internal pipeline.
+    #[cfg(feature = "encode")]
     #[error("http connection error: {0}")]
     Connection(#[source] Box<dyn std::error::Error + Send + Sync>),
 
+    /// I/O error while reading a message from a stream.
+    ///
+    /// Returned by the [`RequestCodec`](crate::RequestCodec) and
+    /// [`ResponseCodec`](crate::ResponseCodec) decoders, for example when the stream
+    /// ends in the middle of a message.
+    #[cfg(feature = "std")]
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+
     /// Internal synchronization error.
     ///
     /// This occurs when an internal communication channel closes unexpectedly.
     /// If you encounter this error, please report it as a bug.
+    #[cfg(feature = "encode")]
     #[error("synchronization error: channel closed unexpectedly")]
     Sync,
 
@@ -30,11 +44,37 @@ pub enum WireError {
 
     /// Error from the httparse library during header parsing.
     ///
-    /// This wraps errors from the `httparse` crate when parsing HTTP headers fails.
-    /// Common causes include malformed header lines, invalid characters, or
-    /// header values that violate HTTP specifications.
+    /// When decoding a message head, this is only returned for
+    /// [`httparse::Error::TooManyHeaders`], if the head has more header lines than the
+    /// header storage can hold; other problems in the head are reported as
+    /// [`WireError::Malformed`]. Malformed trailer fields are reported with this
+    /// variant.
     #[error("{0}")]
-    HttparseError(#[from] httparse::Error),
+    HttparseError(#[cfg_attr(feature = "std", source)] httparse::Error),
+
+    /// The head of a message is malformed.
+    ///
+    /// `offset` is the byte where parsing stopped, counted from the start of the buffer
+    /// passed to `decode`, and `reason` describes what was wrong there, such as
+    /// `"invalid header name"`.
+    #[error("malformed head at offset {offset}: {reason}")]
+    Malformed {
+        /// Byte offset at which parsing stopped.
+        offset: usize,
+        /// What was invalid at `offset`.
+        reason: &'static str,
+    },
+
+    /// A header value is continued on a following line (obsolete line folding).
+    ///
+    /// Returned when decoding with [`DecodeOptions::strict`](crate::DecodeOptions::strict);
+    /// otherwise such a head is reported as [`WireError::Malformed`]. `offset` is the
+    /// start of the continuation line.
+    #[error("obsolete line folding at offset {offset}")]
+    ObsoleteFolding {
+        /// Byte offset of the continuation line.
+        offset: usize,
+    },
 
     /// HTTP headers are incomplete.
     ///
@@ -60,6 +100,344 @@ pub enum WireError {
     /// This error occurs when parsing a chunked body fails due to malformed
     /// chunk formatting, invalid chunk sizes, missing chunk terminators,
     /// or incomplete chunked data.
-    #[error("invalid chunked body")]
-    InvalidChunkedBody,
+    ///
+    /// `offset` is where the problem was detected, counted from the start of the
+    /// buffer passed to `decode`. For incomplete data it is the buffer length. Methods
+    /// that work on an already decoded body count from the start of the body.
+    #[error("invalid chunked body at offset {offset}")]
+    InvalidChunkedBody {
+        /// Byte offset at which parsing stopped.
+        offset: usize,
+    },
+
+    /// A chunk-size line is not a valid hexadecimal number.
+    ///
+    /// Returned by [`verify_chunked_body`](crate::verify_chunked_body) when a size
+    /// line is empty, contains non-hex characters, or overflows `usize`. `offset` is
+    /// the start of the offending line within the chunked body. Decoding a message
+    /// returns it for a size that overflows `usize`, with `offset` counted from the
+    /// start of the decoded buffer.
+    #[error("invalid chunk size at offset {offset}")]
+    InvalidChunkSize {
+        /// Byte offset of the chunk-size line.
+        offset: usize,
+    },
+
+    /// A line or chunk in a chunked body is not terminated by CRLF.
+    ///
+    /// `offset` is the position within the chunked body where the CRLF was expected.
+    #[error("missing CRLF in chunked body at offset {offset}")]
+    MissingChunkDelimiter {
+        /// Byte offset where the CRLF was expected.
+        offset: usize,
+    },
+
+    /// The method of a request is not a valid token or not an allowed method.
+    ///
+    /// Returned when decoding with
+    /// [`DecodeOptions::allowed_methods`](crate::DecodeOptions::allowed_methods) or
+    /// [`FullRequest::decode_strict`](crate::request::FullRequest::decode_strict).
+    #[error("invalid request method")]
+    InvalidMethod,
+
+    /// A request target is empty or contains spaces or control characters.
+    ///
+    /// Returned by [`FullRequest::with_target`](crate::request::FullRequest::with_target),
+    /// since such a target would split the request line or inject header lines.
+    #[error("invalid request target")]
+    InvalidTarget,
+
+    /// A `Content-Length` header value is not a valid body length.
+    ///
+    /// Returned when decoding a message whose `Content-Length` is not a plain decimal
+    /// number, such as `12abc`, `+12` or an empty value, is too large for `usize`, or
+    /// is too large to add to the length of the head.
+    #[error("invalid Content-Length")]
+    InvalidContentLength,
+
+    /// The message has several `Content-Length` headers with different values.
+    ///
+    /// Peers may pick different values to frame the body, so the message is rejected
+    /// when decoding. Repeated headers with the same value are accepted (RFC 9110
+    /// §8.6).
+    #[error("duplicate Content-Length headers with different values")]
+    DuplicateContentLength,
+
+    /// The message has both a `Content-Length` and a `Transfer-Encoding`.
+    ///
+    /// Peers may disagree on which header frames the body, so such a message can be
+    /// used to smuggle requests and is rejected when decoding (RFC 9112 §6.3), whatever
+    /// the transfer codings are.
+    #[error("conflicting framing: both Content-Length and Transfer-Encoding")]
+    ConflictingFraming,
+
+    /// A `Transfer-Encoding` whose final coding is not `chunked`.
+    ///
+    /// The length of such a body cannot be determined, and peers that guess differently
+    /// would see different messages, so a request is rejected when decoding (RFC 9112
+    /// §6.3). So is a response, unless
+    /// [`DecodeOptions::assume_close_delimited`](crate::DecodeOptions::assume_close_delimited)
+    /// is set and `chunked` is not among its codings, in which case its body is read
+    /// until the connection closes.
+    #[error("unsupported transfer coding: the final coding is not chunked")]
+    UnsupportedTransferCoding,
+
+    /// A chunked body ends before its terminating chunk and blank line.
+    #[error("incomplete chunked body")]
+    IncompleteChunkedBody,
+
+    /// A chunked body has more chunks than allowed.
+    ///
+    /// Returned when decoding with [`DecodeOptions::max_chunks`](crate::DecodeOptions::max_chunks).
+    /// `offset` is the start of the first chunk beyond the limit, counted from the
+    /// start of the decoded buffer.
+    #[error("too many chunks: limit exceeded at offset {offset}")]
+    TooManyChunks {
+        /// Byte offset of the first chunk-size line beyond the limit.
+        offset: usize,
+    },
+
+    /// A chunk of a chunked body is larger than allowed.
+    ///
+    /// Returned when decoding with
+    /// [`DecodeOptions::max_chunk_size`](crate::DecodeOptions::max_chunk_size).
+    /// `offset` is the start of the chunk-size line, counted from the start of the
+    /// decoded buffer.
+    #[error("chunk too large at offset {offset}")]
+    ChunkTooLarge {
+        /// Byte offset of the chunk-size line.
+        offset: usize,
+    },
+
+    /// The header section is longer than allowed.
+    ///
+    /// Returned when decoding with
+    /// [`DecodeOptions::max_head_bytes`](crate::DecodeOptions::max_head_bytes), whether or
+    /// not the end of the head has been received.
+    #[error("header section too large")]
+    HeadTooLarge,
+
+    /// The body is longer than allowed.
+    ///
+    /// Returned when decoding with
+    /// [`DecodeOptions::max_body_bytes`](crate::DecodeOptions::max_body_bytes), as soon
+    /// as `Content-Length` or the buffered part of the body exceeds the limit, and by
+    /// [`FullResponse::decode_body_decompressed`](crate::response::FullResponse::decode_body_decompressed)
+    /// when a body decompresses to more than its limit.
+    #[error("body too large")]
+    BodyTooLarge,
+
+    /// The message cannot be padded to the requested size.
+    ///
+    /// Returned when [`EncodeOptions::pad_to`](crate::EncodeOptions::pad_to) asks for
+    /// a size smaller than the message plus an empty padding header. The argument is
+    /// the smallest size the message can be padded to.
+    #[error("cannot pad message: minimum padded size is {0} bytes")]
+    PaddingOverflow(usize),
+
+    /// A message within a multi-message buffer could not be decoded.
+    ///
+    /// Returned by [`validate_session`](crate::validate_session) for the first
+    /// malformed or incomplete message. `offset` is the position of the message's
+    /// first byte in the buffer and `source` the underlying decode error.
+    #[error("invalid message at offset {offset}: {source}")]
+    InvalidMessage {
+        /// Offset of the failing message in the buffer.
+        offset: usize,
+        /// The error raised while decoding the message.
+        #[source]
+        source: Box<WireError>,
+    },
+
+    /// The request is not addressed to an allowed host.
+    ///
+    /// Returned by [`FullRequest::validate_host`](crate::request::FullRequest::validate_host)
+    /// when the effective host is missing, ambiguous, or not in the allowlist.
+    #[error("host not allowed")]
+    HostNotAllowed,
+
+    /// A `Content-Range` header value is malformed.
+    ///
+    /// Returned by [`ContentRange::parse`](crate::ContentRange::parse).
+    #[error("invalid Content-Range")]
+    InvalidContentRange,
+
+    /// A `Strict-Transport-Security` header value is invalid.
+    ///
+    /// Returned by [`StrictTransportSecurity::parse`](crate::StrictTransportSecurity::parse).
+    #[error("invalid Strict-Transport-Security")]
+    InvalidStrictTransportSecurity,
+
+    /// A decoded message cannot be represented with the `http` crate's types.
+    ///
+    /// Returned by [`FullRequest::into_http`](crate::request::FullRequest::into_http)
+    /// and [`FullResponse::into_http`](crate::response::FullResponse::into_http) when
+    /// the method, request target, status code, or a header name or value is rejected
+    /// by `http`. The argument describes which part was invalid.
+    #[cfg(feature = "std")]
+    #[error("cannot convert to http type: {0}")]
+    InvalidHttp(#[from] http::Error),
+
+    /// The body uses a content coding that is not supported.
+    ///
+    /// Returned by
+    /// [`FullResponse::decode_body_decompressed`](crate::response::FullResponse::decode_body_decompressed)
+    /// for a coding that is unknown or whose feature is not enabled. The argument is
+    /// the coding as sent.
+    #[error("unsupported content encoding: {0}")]
+    UnsupportedEncoding(String),
+
+    /// The body is not valid data for its content coding.
+    #[error("body is not valid {0} data")]
+    InvalidContentEncoding(&'static str),
+
+    /// The body declares a charset that cannot be decoded.
+    ///
+    /// Returned by [`FullResponse::body_utf8`](crate::response::FullResponse::body_utf8)
+    /// when the `charset` parameter of `Content-Type` names an unknown encoding.
+    /// The argument is the charset label as sent.
+    #[cfg(feature = "encoding")]
+    #[error("unsupported charset: {0}")]
+    UnsupportedCharset(String),
+
+    /// The body is not valid text in its declared charset.
+    #[cfg(feature = "encoding")]
+    #[error("body is not valid {0} text")]
+    InvalidText(&'static str),
+}
+
+// Written by hand rather than with `#[from]`, which would also make the httparse error
+// the source: it only implements `Error` when both crates are built with `std`
+impl From<httparse::Error> for WireError {
+    fn from(err: httparse::Error) -> Self {
+        WireError::HttparseError(err)
+    }
+}
+
+/// Compares errors structurally, so tests can use `assert_eq!`.
+///
+/// Variants carrying data compare it: offsets, byte counts, httparse errors, and
+/// strings, recursively for [`WireError::InvalidMessage`]. I/O errors compare by
+/// [`std::io::ErrorKind`] and [`WireError::InvalidHttp`] by its message. The boxed error
+/// in [`WireError::Connection`] cannot be compared, so any two connection errors are
+/// equal.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{WireDecode, WireError};
+/// use http_wire::request::FullRequest;
+///
+/// let mut headers = [httparse::EMPTY_HEADER; 16];
+/// let result = FullRequest::decode(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nab", &mut headers);
+/// assert_eq!(result.err(), Some(WireError::IncompleteBody(3)));
+/// ```
+impl PartialEq for WireError {
+    fn eq(&self, other: &Self) -> bool {
+        use WireError::*;
+
+        match (self, other) {
+            #[cfg(feature = "std")]
+            (Io(a), Io(b)) => a.kind() == b.kind(),
+            (HttparseError(a), HttparseError(b)) => a == b,
+            (IncompleteBody(a), IncompleteBody(b)) | (PaddingOverflow(a), PaddingOverflow(b)) => {
+                a == b
+            }
+            (InvalidChunkedBody { offset: a }, InvalidChunkedBody { offset: b })
+            | (InvalidChunkSize { offset: a }, InvalidChunkSize { offset: b })
+            | (MissingChunkDelimiter { offset: a }, MissingChunkDelimiter { offset: b })
+            | (TooManyChunks { offset: a }, TooManyChunks { offset: b })
+            | (ChunkTooLarge { offset: a }, ChunkTooLarge { offset: b })
+            | (ObsoleteFolding { offset: a }, ObsoleteFolding { offset: b }) => a == b,
+            (
+                InvalidMessage {
+                    offset: a,
+                    source: a_source,
+                },
+                InvalidMessage {
+                    offset: b,
+                    source: b_source,
+                },
+            ) => a == b && a_source == b_source,
+            (
+                Malformed {
+                    offset: a,
+                    reason: a_reason,
+                },
+                Malformed {
+                    offset: b,
+                    reason: b_reason,
+                },
+            ) => a == b && a_reason == b_reason,
+            #[cfg(feature = "std")]
+            (InvalidHttp(a), InvalidHttp(b)) => a.to_string() == b.to_string(),
+            (UnsupportedEncoding(a), UnsupportedEncoding(b)) => a == b,
+            (InvalidContentEncoding(a), InvalidContentEncoding(b)) => a == b,
+            #[cfg(feature = "encoding")]
+            (UnsupportedCharset(a), UnsupportedCharset(b)) => a == b,
+            #[cfg(feature = "encoding")]
+            (InvalidText(a), InvalidText(b)) => a == b,
+            // Variants with data are all matched above, so only `Connection` and the
+            // unit variants are left
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_eq() {
+        assert_eq!(WireError::PartialHead, WireError::PartialHead);
+        assert_eq!(WireError::IncompleteBody(3), WireError::IncompleteBody(3));
+        assert_ne!(WireError::IncompleteBody(3), WireError::IncompleteBody(4));
+        assert_ne!(WireError::IncompleteBody(3), WireError::PaddingOverflow(3));
+        assert_eq!(
+            WireError::HttparseError(httparse::Error::Token),
+            WireError::HttparseError(httparse::Error::Token)
+        );
+        assert_ne!(
+            WireError::HttparseError(httparse::Error::Token),
+            WireError::HttparseError(httparse::Error::Version)
+        );
+        let malformed = |offset, reason| WireError::Malformed { offset, reason };
+        assert_eq!(malformed(3, "invalid token"), malformed(3, "invalid token"));
+        assert_ne!(malformed(3, "invalid token"), malformed(4, "invalid token"));
+        assert_ne!(
+            WireError::InvalidChunkedBody { offset: 1 },
+            WireError::InvalidChunkSize { offset: 1 }
+        );
+
+        let invalid = |offset, source| WireError::InvalidMessage {
+            offset,
+            source: Box::new(source),
+        };
+        assert_eq!(
+            invalid(5, WireError::PartialHead),
+            invalid(5, WireError::PartialHead)
+        );
+        assert_ne!(
+            invalid(5, WireError::PartialHead),
+            invalid(5, WireError::IncompleteChunkedBody)
+        );
+
+        // Connection errors are equal whatever they wrap
+        #[cfg(feature = "encode")]
+        assert_eq!(
+            WireError::Connection("a".into()),
+            WireError::Connection("b".into())
+        );
+        #[cfg(feature = "encode")]
+        assert_ne!(WireError::Connection("a".into()), WireError::Sync);
+        #[cfg(feature = "std")]
+        assert_eq!(
+            WireError::Io(std::io::ErrorKind::UnexpectedEof.into()),
+            WireError::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "eof"
+            ))
+        );
+    }
 }
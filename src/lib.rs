@@ -4,39 +4,121 @@
 //!
 //! # Implementation Details
 //!
-//! This crate leverages the [hyper](https://crates.io/crates/hyper) library for reliable
-//! and specification-compliant HTTP serialization.
+//! Messages are serialized directly into a buffer by the [`wire`] module, which frames
+//! bodies the way [hyper](https://crates.io/crates/hyper) does on a real connection.
+//! No I/O is performed and no tasks are spawned.
 //!
-//! Because hyper is asynchronous, the synchronous encoding APIs provided by this crate
-//! internally create a temporary, single-threaded Tokio runtime to drive the serialization.
+//! Because message bodies are asynchronous, the synchronous encoding APIs provided by
+//! this crate read streaming bodies on a single-threaded Tokio runtime. The runtime is
+//! created lazily on first use and shared by all subsequent calls. Bodies whose size is
+//! known upfront, such as `Full` and `Empty`, are encoded on the calling thread without
+//! the runtime.
 //! If you are already operating within an async context, you should prefer the `_async`
-//! variants (e.g., [`WireEncodeAsync`]) to avoid the overhead of creating a nested runtime.
+//! variants (e.g., [`WireEncodeAsync`]), since blocking on the shared runtime from inside
+//! another runtime panics.
+//!
+//! # Feature Flags
+//!
+//! - `std` (default): everything except encoding. Without it the crate is `no_std`
+//!   and needs only `alloc`; request decoding with [`request::FullRequest`],
+//!   [`DecodeOptions`] and the chunked body helpers remain available.
+//! - `encode` (default): the [`wire`] module, [`WireEncode`], [`WireEncodeAsync`] and
+//!   the other encoding APIs. Encoding pulls in hyper and Tokio, so decode-only users
+//!   can build with `default-features = false, features = ["std"]` to leave them out.
 //!
 //! # Encoding
 //!
 //! Use the [`WireEncode`] trait to convert HTTP messages to their wire format (synchronously):
 //!
 
-use bytes::Bytes;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "encode")]
+use bytes::{Bytes, BytesMut};
+use core::mem::MaybeUninit;
 pub use httparse::Header;
-use std::{future::Future, mem::MaybeUninit};
+#[cfg(feature = "encode")]
+use std::future::Future;
+#[cfg(feature = "encode")]
+use tokio::io::AsyncWrite;
 
+mod cache_control;
+#[cfg(feature = "encode")]
+mod capture;
+mod chunked;
+#[cfg(feature = "codec")]
+mod codec;
+#[cfg(feature = "std")]
+mod compare;
+#[cfg(feature = "std")]
+mod content_coding;
+pub mod decoder;
+#[cfg(feature = "encode")]
+mod encoder;
 mod error;
+#[cfg(feature = "std")]
+mod link;
+#[cfg(feature = "std")]
+mod message;
+mod owned;
+#[cfg(feature = "std")]
+mod range;
 pub mod request;
+#[cfg(feature = "std")]
 pub mod response;
+#[cfg(feature = "std")]
+mod security;
+#[cfg(feature = "std")]
+mod session;
 mod util;
-mod wire;
+#[cfg(feature = "encode")]
+pub mod wire;
 
+pub use cache_control::CacheControl;
+#[cfg(feature = "encode")]
+pub use capture::{CaptureHandle, CapturingIo};
+pub use chunked::{
+    ChunkExtensions, ChunkedInfo, decode_trailers, parse_chunked_body_ext, verify_chunked_body,
+};
+#[cfg(feature = "codec")]
+pub use codec::{RequestCodec, ResponseCodec};
+#[cfg(feature = "std")]
+pub use compare::wire_equal;
+#[cfg(feature = "std")]
+pub use decoder::Streaming;
+pub use decoder::{DecodeOptions, Limits};
+#[cfg(feature = "digest")]
+pub use encoder::DigestAlgorithm;
+#[cfg(feature = "encode")]
+pub use encoder::{EmptyReason, EncodeOptions, Terminator, encode_custom, encode_raw_head};
 pub use error::WireError;
+#[cfg(feature = "std")]
+pub use link::Link;
+#[cfg(feature = "std")]
+pub use message::{
+    HTTP2_PREFACE, Message, MessageBoundary, MessageKind, OwnedMessage, Protocol, decode_message,
+    detect_protocol, expected_total_len, validate_session,
+};
+pub use owned::OwnedHeader;
+#[cfg(feature = "std")]
+pub use range::ContentRange;
+#[cfg(feature = "std")]
+pub use security::{StrictTransportSecurity, XFrameOptions};
+#[cfg(feature = "std")]
+pub use session::WireSession;
 
 /// Encode HTTP messages to their wire format bytes (synchronous version).
 ///
 /// This trait provides synchronous encoding without requiring an async runtime.
-/// It creates a minimal single-threaded Tokio runtime internally and blocks on
-/// the async encoding method.
+/// Bodies whose size is known upfront are encoded on the calling thread. Streaming
+/// bodies are read by blocking on a single-threaded Tokio runtime that is created once
+/// and shared by all calls.
 ///
 /// Implemented for `http::Request<B>` and `http::Response<B>`.
-/// Only HTTP/1.0 and HTTP/1.1 are supported.
+/// Only HTTP/1.0 and HTTP/1.1 are supported. The start line carries the version set on
+/// the message, and no `Connection` header is added for either version.
 ///
 /// # Example
 ///
@@ -57,11 +139,12 @@ pub use error::WireError;
 /// ```
 ///
 /// For async encoding, use [`WireEncodeAsync`] instead.
+#[cfg(feature = "encode")]
 pub trait WireEncode {
     /// Encodes the HTTP message to wire format bytes synchronously.
     ///
-    /// This method creates a minimal single-threaded Tokio runtime and blocks
-    /// until the encoding is complete.
+    /// For a streaming body, this method blocks on the shared single-threaded Tokio
+    /// runtime until the encoding is complete.
     ///
     /// # Errors
     ///
@@ -69,6 +152,129 @@ pub trait WireEncode {
     fn encode(self) -> Result<Bytes, WireError>
     where
         Self: Sized;
+
+    /// Appends the encoded message to `buf` and returns the number of bytes written.
+    ///
+    /// Reusing one buffer across calls avoids allocating a new one for every message,
+    /// for example when the buffer is written to a socket and cleared in a loop.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`encode`](Self::encode). Nothing is appended to
+    /// `buf` on error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bytes::{Bytes, BytesMut};
+    /// use http_body_util::Full;
+    /// use http_wire::WireEncode;
+    ///
+    /// let mut buf = BytesMut::with_capacity(4096);
+    /// for path in ["/a", "/b"] {
+    ///     let request = http::Request::builder()
+    ///         .uri(path)
+    ///         .header("Host", "example.com")
+    ///         .body(Full::new(Bytes::new()))
+    ///         .unwrap();
+    ///     let written = request.encode_into(&mut buf).unwrap();
+    ///     assert_eq!(written, 38);
+    /// }
+    /// assert_eq!(
+    ///     &buf[..],
+    ///     b"GET /a HTTP/1.1\r\nhost: example.com\r\n\r\nGET /b HTTP/1.1\r\nhost: example.com\r\n\r\n"
+    /// );
+    /// ```
+    fn encode_into(self, buf: &mut BytesMut) -> Result<usize, WireError>
+    where
+        Self: Sized,
+    {
+        let bytes = self.encode()?;
+        buf.extend_from_slice(&bytes);
+        Ok(bytes.len())
+    }
+
+    /// Encodes the HTTP message to wire format bytes, applying the given options.
+    ///
+    /// See [`EncodeOptions`] for the available options.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`encode`](Self::encode), plus any error raised
+    /// while applying the options (e.g. [`WireError::PaddingOverflow`]).
+    fn encode_with(self, options: &EncodeOptions) -> Result<Bytes, WireError>
+    where
+        Self: Sized,
+    {
+        options.apply(self.encode()?)
+    }
+
+    /// Encodes the HTTP message as separate head and body buffers for vectored writes.
+    ///
+    /// The first buffer is the head, including the blank line. The second, omitted
+    /// when the body is empty, is the body as it appears on the wire. For bodies of
+    /// known size, such as `Full<Bytes>`, the body buffer is the original `Bytes`, so
+    /// it is never copied into a combined buffer. Chunked bodies are returned with
+    /// their framing.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireEncode;
+    /// use http_body_util::Full;
+    /// use bytes::Bytes;
+    ///
+    /// let body = Bytes::from("hello");
+    /// let response = http::Response::new(Full::new(body.clone()));
+    ///
+    /// let parts = response.encode_vectored().unwrap();
+    /// assert!(parts[0].ends_with(b"\r\n\r\n"));
+    /// assert_eq!(parts[1].as_ptr(), body.as_ptr());
+    /// ```
+    fn encode_vectored(self) -> Result<Vec<Bytes>, WireError>
+    where
+        Self: Sized,
+    {
+        wire::split_vectored(self.encode()?, None)
+    }
+
+    /// Encodes the HTTP message as a head and a body buffer.
+    ///
+    /// Works like [`encode_vectored`](Self::encode_vectored), but always returns both
+    /// buffers: the head, including the blank line that ends it, and the body as it
+    /// appears on the wire, which is empty when there is no body. The split is made
+    /// while encoding, so a body that contains `\r\n\r\n` is never mistaken for the
+    /// end of the head.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`encode`](Self::encode).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireEncode;
+    /// use http_body_util::Full;
+    /// use bytes::Bytes;
+    ///
+    /// let response = http::Response::new(Full::new(Bytes::from("a\r\n\r\nb")));
+    ///
+    /// let (head, body) = response.encode_parts().unwrap();
+    /// assert!(head.ends_with(b"\r\n\r\n"));
+    /// assert_eq!(body, "a\r\n\r\nb");
+    /// ```
+    fn encode_parts(self) -> Result<(Bytes, Bytes), WireError>
+    where
+        Self: Sized,
+    {
+        let mut parts = self.encode_vectored()?.into_iter();
+        let head = parts.next().unwrap_or_default();
+        Ok((head, parts.next().unwrap_or_default()))
+    }
 }
 
 /// Encode HTTP messages to their wire format bytes (async version).
@@ -76,6 +282,8 @@ pub trait WireEncode {
 /// Implemented for `http::Request<B>` and `http::Response<B>`.
 /// Only HTTP/1.0 and HTTP/1.1 are supported.
 ///
+/// Encoding only polls the message body, so it can be awaited on any executor.
+///
 /// For synchronous encoding without requiring an async runtime,
 /// use [`WireEncode`] instead.
 ///
@@ -97,6 +305,7 @@ pub trait WireEncode {
 /// let bytes = request.encode_async().await.unwrap();
 /// # }
 /// ```
+#[cfg(feature = "encode")]
 pub trait WireEncodeAsync {
     /// Encodes the HTTP message to wire format bytes.
     ///
@@ -104,6 +313,51 @@ pub trait WireEncodeAsync {
     ///
     /// Returns [`WireError::UnsupportedVersion`] for HTTP/2 or later.
     fn encode_async(self) -> impl Future<Output = Result<Bytes, WireError>> + Send;
+
+    /// Writes the HTTP message to `writer` and returns the number of bytes written.
+    ///
+    /// Unlike [`encode_async`](Self::encode_async), the message is not collected
+    /// first: the head is written, then each body frame as soon as it is read, so only
+    /// one frame is held in memory at a time. The body is framed as by
+    /// [`encode_async`](Self::encode_async), chunked when its size is not known
+    /// upfront, and once a body framed by a `Content-Length` is complete the rest of
+    /// it is not read. The writer is flushed at the end.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::Connection`] if reading the body or writing to `writer`
+    /// fails, and [`WireError::UnsupportedVersion`] for HTTP/2 or later.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bytes::Bytes;
+    /// use http_body_util::StreamBody;
+    /// use http_wire::WireEncodeAsync;
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let frames = futures::stream::iter(["hello ", "world"].map(|chunk| {
+    ///     Ok::<_, std::io::Error>(hyper::body::Frame::data(Bytes::from(chunk)))
+    /// }));
+    /// let request = http::Request::builder()
+    ///     .method("POST")
+    ///     .uri("/upload")
+    ///     .body(StreamBody::new(frames))
+    ///     .unwrap();
+    ///
+    /// let mut socket = Vec::new();
+    /// let written = request.encode_to(&mut socket).await.unwrap();
+    /// assert_eq!(written, socket.len());
+    /// assert_eq!(
+    ///     socket,
+    ///     b"POST /upload HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n6\r\nhello \r\n5\r\nworld\r\n0\r\n\r\n"
+    /// );
+    /// # }
+    /// ```
+    fn encode_to<W>(self, writer: &mut W) -> impl Future<Output = Result<usize, WireError>> + Send
+    where
+        W: AsyncWrite + Unpin + Send;
 }
 
 /// Decode HTTP messages from raw bytes.
@@ -145,6 +399,7 @@ pub trait WireEncodeAsync {
 /// ## Decoding a response
 ///
 /// ```rust
+/// # #[cfg(feature = "std")] {
 /// use http_wire::WireDecode;
 /// use http_wire::response::FullResponse;
 ///
@@ -154,10 +409,8 @@ pub trait WireEncodeAsync {
 ///
 /// assert_eq!(response.head.code, Some(200));
 /// assert_eq!(response.body, b"hello");
+/// # }
 /// ```
-///
-/// Note: `decode_uninit` is not available for `FullResponse` because the underlying
-/// `httparse::Response` parser does not support uninitialized headers.
 pub trait WireDecode<'headers, 'buf>: Sized {
     /// Decode using initialized headers storage.
     ///
@@ -179,6 +432,12 @@ pub trait WireDecode<'headers, 'buf>: Sized {
     /// - Headers are incomplete (`WireError::PartialHead`)
     /// - Body is incomplete (`WireError::IncompleteBody`)
     /// - Chunked encoding is malformed (`WireError::InvalidChunkedBody`)
+    /// - `Content-Length` is not a number or overflows (`WireError::InvalidContentLength`)
+    /// - `Content-Length` headers disagree (`WireError::DuplicateContentLength`)
+    /// - Both `Content-Length` and `Transfer-Encoding` are present
+    ///   (`WireError::ConflictingFraming`)
+    /// - The final transfer coding is not `chunked`
+    ///   (`WireError::UnsupportedTransferCoding`)
     fn decode(
         buf: &'buf [u8],
         headers: &'headers mut [Header<'buf>],
@@ -188,7 +447,7 @@ pub trait WireDecode<'headers, 'buf>: Sized {
     ///
     /// This method avoids the overhead of initializing the headers array before parsing.
     /// It is only available for types where the underlying parser supports
-    /// `parse_with_uninit_headers`. Currently only `FullRequest` implements this.
+    /// uninitialized header storage. `FullRequest` and `FullResponse` implement this.
     ///
     /// # Arguments
     ///
@@ -207,7 +466,7 @@ pub trait WireDecode<'headers, 'buf>: Sized {
     /// # Panics
     ///
     /// The default implementation panics with an explanatory message for types that don't
-    /// support this optimization (e.g., `FullResponse`).
+    /// support this optimization.
     fn decode_uninit(
         buf: &'buf [u8],
         headers: &'headers mut [MaybeUninit<Header<'buf>>],
@@ -0,0 +1,259 @@
+//! Typed `Link` header values.
+//!
+//! This module provides [`Link`], a single entry of a `Link` header (RFC 8288), as
+//! returned by [`FullResponse::links`](crate::response::FullResponse::links).
+
+use httparse::Header;
+
+/// A single link of a `Link` header.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::Link;
+///
+/// let links = Link::parse(br#"<https://api.example.com/items?page=2>; rel="next", </items?page=9>; rel=last"#);
+/// assert_eq!(links.len(), 2);
+/// assert_eq!(links[0].uri, "https://api.example.com/items?page=2");
+/// assert!(links[0].has_rel("next"));
+/// assert_eq!(links[1].rel.as_deref(), Some("last"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Link {
+    /// The target URI, as written between `<` and `>`. It may be relative.
+    pub uri: String,
+    /// The `rel` parameter: one or more space-separated relation types.
+    pub rel: Option<String>,
+    /// The `type` parameter, a media type hint.
+    pub media_type: Option<String>,
+    /// The `title` parameter.
+    pub title: Option<String>,
+    /// All other parameters, in order, with lowercase names and unquoted values.
+    ///
+    /// A parameter without a value has an empty value.
+    pub params: Vec<(String, String)>,
+}
+
+impl Link {
+    /// Parses every link in a `Link` field value.
+    ///
+    /// Links are separated by commas, which may also appear inside the URI or a
+    /// quoted parameter value. Parameter names are case-insensitive and values may
+    /// be quoted strings with backslash escapes. As required by RFC 8288 §3.3, only
+    /// the first `rel` of a link is used; the same applies to `type` and `title`.
+    /// Entries that do not start with a `<uri>` are skipped.
+    pub fn parse(value: &[u8]) -> Vec<Link> {
+        let mut links = Vec::new();
+        let mut parser = Parser {
+            input: value,
+            pos: 0,
+        };
+
+        loop {
+            parser.skip_while(|b| b == b',' || b.is_ascii_whitespace());
+            match parser.peek() {
+                None => break,
+                Some(b'<') => {}
+                Some(_) => {
+                    parser.skip_entry();
+                    continue;
+                }
+            }
+
+            parser.pos += 1;
+            let Some(uri) = parser.take_until(b'>') else {
+                break;
+            };
+            let mut link = Link {
+                uri: String::from_utf8_lossy(uri).into_owned(),
+                ..Link::default()
+            };
+
+            loop {
+                parser.skip_while(|b| b == b' ' || b == b'\t');
+                match parser.peek() {
+                    Some(b';') => parser.pos += 1,
+                    Some(b',') | None => break,
+                    Some(_) => {
+                        parser.skip_entry();
+                        break;
+                    }
+                }
+                if let Some((name, value)) = parser.param() {
+                    link.set_param(name, value);
+                }
+            }
+            links.push(link);
+        }
+        links
+    }
+
+    /// Parses the links of all `Link` headers, in order.
+    pub(crate) fn from_headers(headers: &[Header<'_>]) -> Vec<Link> {
+        headers
+            .iter()
+            .filter(|h| h.name.eq_ignore_ascii_case("Link"))
+            .flat_map(|h| Link::parse(h.value))
+            .collect()
+    }
+
+    /// Returns the relation types of the `rel` parameter.
+    pub fn rels(&self) -> impl Iterator<Item = &str> {
+        self.rel.iter().flat_map(|rel| rel.split_ascii_whitespace())
+    }
+
+    /// Returns `true` if `rel` is one of the relation types, ignoring ASCII case.
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.rels().any(|r| r.eq_ignore_ascii_case(rel))
+    }
+
+    /// Returns the value of a parameter other than `rel`, `type` and `title`.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn set_param(&mut self, name: String, value: String) {
+        let slot = match name.as_str() {
+            "rel" => &mut self.rel,
+            "type" => &mut self.media_type,
+            "title" => &mut self.title,
+            _ => {
+                self.params.push((name, value));
+                return;
+            }
+        };
+        if slot.is_none() {
+            *slot = Some(value);
+        }
+    }
+}
+
+/// A cursor over a `Link` field value.
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn skip_while(&mut self, pred: impl Fn(u8) -> bool) {
+        while self.peek().is_some_and(&pred) {
+            self.pos += 1;
+        }
+    }
+
+    /// Returns the bytes up to `end` and moves past it, or `None` if `end` is missing.
+    fn take_until(&mut self, end: u8) -> Option<&'a [u8]> {
+        let len = self.input[self.pos..].iter().position(|&b| b == end)?;
+        let taken = &self.input[self.pos..self.pos + len];
+        self.pos += len + 1;
+        Some(taken)
+    }
+
+    /// Skips to the comma ending the current entry, stepping over quoted strings.
+    fn skip_entry(&mut self) {
+        while let Some(b) = self.peek() {
+            match b {
+                b',' => return,
+                b'"' => {
+                    self.quoted();
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// Parses `name[=value]` after a `;`, with a lowercase name.
+    fn param(&mut self) -> Option<(String, String)> {
+        self.skip_while(|b| b == b' ' || b == b'\t');
+        let start = self.pos;
+        self.skip_while(|b| !matches!(b, b'=' | b';' | b',') && !b.is_ascii_whitespace());
+        let name = String::from_utf8_lossy(&self.input[start..self.pos]).to_ascii_lowercase();
+        self.skip_while(|b| b == b' ' || b == b'\t');
+
+        let value = if self.peek() == Some(b'=') {
+            self.pos += 1;
+            self.skip_while(|b| b == b' ' || b == b'\t');
+            if self.peek() == Some(b'"') {
+                self.quoted()
+            } else {
+                let start = self.pos;
+                self.skip_while(|b| !matches!(b, b';' | b',') && !b.is_ascii_whitespace());
+                String::from_utf8_lossy(&self.input[start..self.pos]).into_owned()
+            }
+        } else {
+            String::new()
+        };
+
+        (!name.is_empty()).then_some((name, value))
+    }
+
+    /// Parses a quoted string starting at the opening quote, resolving escapes.
+    ///
+    /// An unterminated string extends to the end of the input.
+    fn quoted(&mut self) -> String {
+        self.pos += 1;
+        let mut value = Vec::new();
+        while let Some(b) = self.peek() {
+            self.pos += 1;
+            match b {
+                b'"' => break,
+                b'\\' => {
+                    if let Some(escaped) = self.peek() {
+                        value.push(escaped);
+                        self.pos += 1;
+                    }
+                }
+                _ => value.push(b),
+            }
+        }
+        String::from_utf8_lossy(&value).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_params() {
+        let links = Link::parse(
+            br#"<https://example.com/a,b>; REL="next prev" ; Type=text/html; title="A \"quoted\", title"; hreflang=en; crossorigin"#,
+        );
+        assert_eq!(
+            links,
+            [Link {
+                uri: "https://example.com/a,b".into(),
+                rel: Some("next prev".into()),
+                media_type: Some("text/html".into()),
+                title: Some("A \"quoted\", title".into()),
+                params: vec![
+                    ("hreflang".into(), "en".into()),
+                    ("crossorigin".into(), String::new()),
+                ],
+            }]
+        );
+        assert!(links[0].has_rel("PREV"));
+        assert_eq!(links[0].param("HrefLang"), Some("en"));
+    }
+
+    #[test]
+    fn test_parse_first_rel_wins() {
+        let links = Link::parse(b"</a>; rel=next; rel=last");
+        assert_eq!(links[0].rels().collect::<Vec<_>>(), ["next"]);
+    }
+
+    #[test]
+    fn test_parse_skips_malformed_entries() {
+        let links = Link::parse(b"garbage; rel=\"x,y\", </ok>; rel=ok, , </unterminated");
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].uri, "/ok");
+        assert!(Link::parse(b"").is_empty());
+    }
+}
@@ -0,0 +1,448 @@
+//! Helpers that work on messages of either direction.
+//!
+//! This module provides [`decode_message`], which decodes a message without knowing
+//! its direction upfront, [`validate_session`], which walks a buffer of back-to-back
+//! HTTP messages such as a recorded session, and [`expected_total_len`], which sizes
+//! a message from its head alone. [`detect_protocol`] tells HTTP/1.x traffic apart
+//! from an HTTP/2 connection preface.
+
+use crate::WireDecode;
+use crate::error::WireError;
+use crate::owned::DEFAULT_MAX_HEADERS;
+use crate::request::{FullRequest, OwnedRequest};
+use crate::response::{FullResponse, OwnedResponse};
+use crate::util::{find_header, is_tchar, parse_content_length};
+use httparse::Header;
+
+/// Whether a message is a request or a response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKind {
+    /// An HTTP request.
+    Request,
+    /// An HTTP response.
+    Response,
+}
+
+impl MessageKind {
+    /// Detects the kind of the message at the start of `buf`.
+    ///
+    /// Responses start with the `HTTP/` version token; anything else is
+    /// treated as a request.
+    pub fn detect(buf: &[u8]) -> Self {
+        if buf.starts_with(b"HTTP/") {
+            MessageKind::Response
+        } else {
+            MessageKind::Request
+        }
+    }
+}
+
+/// A decoded message of either direction.
+///
+/// Returned by [`decode_message`].
+pub enum Message<'headers, 'buf> {
+    /// A decoded request.
+    Request(FullRequest<'headers, 'buf>),
+    /// A decoded response.
+    Response(FullResponse<'headers, 'buf>),
+}
+
+impl Message<'_, '_> {
+    /// Returns whether this is a request or a response.
+    pub fn kind(&self) -> MessageKind {
+        match self {
+            Message::Request(_) => MessageKind::Request,
+            Message::Response(_) => MessageKind::Response,
+        }
+    }
+}
+
+/// A decoded message of either direction that owns its data.
+///
+/// Returned by [`Streaming::next_message`](crate::decoder::Streaming::next_message).
+#[derive(Debug, Clone)]
+pub enum OwnedMessage {
+    /// A decoded request.
+    Request(OwnedRequest),
+    /// A decoded response.
+    Response(OwnedResponse),
+}
+
+impl OwnedMessage {
+    /// Returns whether this is a request or a response.
+    pub fn kind(&self) -> MessageKind {
+        match self {
+            OwnedMessage::Request(_) => MessageKind::Request,
+            OwnedMessage::Response(_) => MessageKind::Response,
+        }
+    }
+}
+
+/// Decodes a request or a response, detecting which from the first line.
+///
+/// The kind is chosen with [`MessageKind::detect`] and the buffer is then decoded with
+/// [`FullRequest::decode`] or [`FullResponse::decode`]. This suits tools that see both
+/// directions of a connection, such as traffic recorders.
+///
+/// Returns the message and its total length (headers + body).
+///
+/// # Errors
+///
+/// Returns the same errors as the underlying decoder.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{Message, decode_message};
+///
+/// let raw = b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n";
+/// let mut headers = [httparse::EMPTY_HEADER; 16];
+///
+/// match decode_message(raw, &mut headers).unwrap() {
+///     (Message::Response(response), len) => {
+///         assert_eq!(response.head.code, Some(404));
+///         assert_eq!(len, raw.len());
+///     }
+///     (Message::Request(_), _) => unreachable!(),
+/// }
+/// ```
+pub fn decode_message<'headers, 'buf>(
+    buf: &'buf [u8],
+    headers: &'headers mut [Header<'buf>],
+) -> Result<(Message<'headers, 'buf>, usize), WireError> {
+    match MessageKind::detect(buf) {
+        MessageKind::Request => {
+            FullRequest::decode(buf, headers).map(|(request, len)| (Message::Request(request), len))
+        }
+        MessageKind::Response => FullResponse::decode(buf, headers)
+            .map(|(response, len)| (Message::Response(response), len)),
+    }
+}
+
+/// Location of a single message within a session buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageBoundary {
+    /// Whether the message is a request or a response.
+    pub kind: MessageKind,
+    /// Offset of the first byte of the message in the buffer.
+    pub offset: usize,
+    /// Total length of the message (headers + body).
+    pub len: usize,
+}
+
+/// Validates a buffer of back-to-back HTTP messages.
+///
+/// The buffer is walked from the start, decoding one message at a time. Requests and
+/// responses may be freely mixed; the kind of each message is detected from its first
+/// line (see [`MessageKind::detect`]).
+///
+/// Response bodies are framed from their own headers only, so a response to a `HEAD`
+/// request that carries a `Content-Length` cannot be validated this way.
+///
+/// # Errors
+///
+/// Returns [`WireError::InvalidMessage`] for the first message that is malformed or
+/// incomplete, carrying its offset and the underlying decode error.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{MessageKind, validate_session};
+///
+/// let session = b"GET / HTTP/1.1\r\nHost: a\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+/// let boundaries = validate_session(session).unwrap();
+///
+/// assert_eq!(boundaries.len(), 2);
+/// assert_eq!(boundaries[1].kind, MessageKind::Response);
+/// assert_eq!(boundaries[1].offset, 27);
+/// ```
+pub fn validate_session(bytes: &[u8]) -> Result<Vec<MessageBoundary>, WireError> {
+    let mut boundaries = Vec::new();
+    let mut offset = 0;
+
+    while offset < bytes.len() {
+        let mut headers = [httparse::EMPTY_HEADER; DEFAULT_MAX_HEADERS];
+        let (message, len) = decode_message(&bytes[offset..], &mut headers).map_err(|source| {
+            WireError::InvalidMessage {
+                offset,
+                source: Box::new(source),
+            }
+        })?;
+
+        boundaries.push(MessageBoundary {
+            kind: message.kind(),
+            offset,
+            len,
+        });
+        offset += len;
+    }
+
+    Ok(boundaries)
+}
+
+/// Computes the total message length from its parsed head, before the body arrives.
+///
+/// `head_bytes` is the length of the head including the terminating blank line, as
+/// returned by `httparse` on a complete parse. For a body framed by `Content-Length`,
+/// the result is `head_bytes + content_length`, which lets a reader allocate a buffer
+/// of exactly the right size.
+///
+/// Returns `None` when the length cannot be known upfront: for bodies with a
+/// `Transfer-Encoding`, and when there is no `Content-Length` (a close-delimited
+/// response, or a request without a body, which is complete at `head_bytes`). Also
+/// returns `None` if the `Content-Length` value is not a plain decimal number, as
+/// decoding would reject it, or the sum overflows.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::expected_total_len;
+///
+/// let raw = b"POST / HTTP/1.1\r\nContent-Length: 1000\r\n\r\npartial";
+/// let mut headers = [httparse::EMPTY_HEADER; 16];
+/// let mut request = httparse::Request::new(&mut headers);
+/// let head_bytes = request.parse(raw).unwrap().unwrap();
+///
+/// assert_eq!(expected_total_len(head_bytes, request.headers), Some(head_bytes + 1000));
+/// ```
+pub fn expected_total_len(head_bytes: usize, headers: &[Header<'_>]) -> Option<usize> {
+    if find_header(headers, "Transfer-Encoding").is_some() {
+        return None;
+    }
+    let content_length = parse_content_length(find_header(headers, "Content-Length")?).ok()?;
+    head_bytes.checked_add(content_length)
+}
+
+/// The HTTP/2 connection preface sent by a client (RFC 9113 §3.4).
+///
+/// When [`detect_protocol`] returns [`Protocol::Http2Preface`], the HTTP/2 frames start
+/// at `HTTP2_PREFACE.len()`.
+pub const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// The protocol spoken at the start of a buffer.
+///
+/// Returned by [`detect_protocol`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// An HTTP/1.x request line.
+    Http1Request,
+    /// An HTTP/1.x status line.
+    Http1Response,
+    /// The HTTP/2 client connection preface ([`HTTP2_PREFACE`]).
+    Http2Preface,
+    /// The buffer is too short to tell.
+    NeedMoreData,
+    /// Something else, such as a TLS handshake or another protocol.
+    Unknown,
+}
+
+/// Detects whether a buffer starts with HTTP/1.x or an HTTP/2 connection preface.
+///
+/// This is a cheap prefix check for a listener that must branch before decoding: an
+/// HTTP/1.x decoder fails confusingly on the HTTP/2 preface, which looks like a
+/// request with the unknown method `PRI`. Only the start line is inspected:
+///
+/// - a status line is recognized from its `HTTP/1.x ` prefix,
+/// - a request line must be a method token, a space, and a first line ending with
+///   ` HTTP/1.x`.
+///
+/// [`Protocol::NeedMoreData`] is returned as long as the buffer is a prefix of one of
+/// these forms, e.g. while the request line is still incomplete.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{HTTP2_PREFACE, Protocol, detect_protocol};
+///
+/// assert_eq!(detect_protocol(b"GET / HTTP/1.1\r\n"), Protocol::Http1Request);
+/// assert_eq!(detect_protocol(b"HTTP/1.1 200 OK\r\n"), Protocol::Http1Response);
+/// assert_eq!(detect_protocol(HTTP2_PREFACE), Protocol::Http2Preface);
+/// assert_eq!(detect_protocol(b"PRI * HT"), Protocol::NeedMoreData);
+/// assert_eq!(detect_protocol(b"\x16\x03\x01"), Protocol::Unknown);
+/// ```
+pub fn detect_protocol(buf: &[u8]) -> Protocol {
+    if buf.starts_with(HTTP2_PREFACE) {
+        return Protocol::Http2Preface;
+    }
+    if HTTP2_PREFACE.starts_with(buf) {
+        return Protocol::NeedMoreData;
+    }
+
+    if buf.starts_with(b"HTTP/") {
+        return match buf.get(..9) {
+            None if b"HTTP/1.".starts_with(&buf[..buf.len().min(7)]) => Protocol::NeedMoreData,
+            Some([b'H', b'T', b'T', b'P', b'/', b'1', b'.', minor, b' '])
+                if minor.is_ascii_digit() =>
+            {
+                Protocol::Http1Response
+            }
+            _ => Protocol::Unknown,
+        };
+    }
+
+    let Some(space) = buf.iter().position(|&b| !is_tchar(b)) else {
+        return Protocol::NeedMoreData;
+    };
+    if space == 0 || buf[space] != b' ' {
+        return Protocol::Unknown;
+    }
+    let Some(lf) = buf.iter().position(|&b| b == b'\n') else {
+        return Protocol::NeedMoreData;
+    };
+    let line = buf[..lf].strip_suffix(b"\r").unwrap_or(&buf[..lf]);
+    match line.len().checked_sub(9).map(|start| &line[start..]) {
+        Some([b' ', b'H', b'T', b'T', b'P', b'/', b'1', b'.', minor]) if minor.is_ascii_digit() => {
+            Protocol::Http1Request
+        }
+        _ => Protocol::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_message_detects_kind() {
+        let raw = b"DELETE /item/7 HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (message, len) = decode_message(raw, &mut headers).unwrap();
+        assert_eq!(message.kind(), MessageKind::Request);
+        assert_eq!(len, raw.len());
+        let Message::Request(request) = message else {
+            panic!("expected a request");
+        };
+        assert_eq!(request.head.method, Some("DELETE"));
+
+        let raw = b"HTTP/1.0 200 OK\r\nContent-Length: 2\r\n\r\nokextra";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (message, len) = decode_message(raw, &mut headers).unwrap();
+        assert_eq!(message.kind(), MessageKind::Response);
+        assert_eq!(len, raw.len() - 5);
+    }
+
+    #[test]
+    fn test_decode_message_propagates_errors() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert!(matches!(
+            decode_message(raw, &mut headers),
+            Err(WireError::IncompleteBody(5))
+        ));
+    }
+
+    #[test]
+    fn test_validate_session_mixed() {
+        let session = b"POST /a HTTP/1.1\r\nContent-Length: 3\r\n\r\nabcHTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nok\r\n0\r\n\r\nGET /b HTTP/1.1\r\n\r\n";
+        let boundaries = validate_session(session).unwrap();
+
+        assert_eq!(
+            boundaries,
+            [
+                MessageBoundary {
+                    kind: MessageKind::Request,
+                    offset: 0,
+                    len: 42,
+                },
+                MessageBoundary {
+                    kind: MessageKind::Response,
+                    offset: 42,
+                    len: 64,
+                },
+                MessageBoundary {
+                    kind: MessageKind::Request,
+                    offset: 106,
+                    len: 19,
+                },
+            ]
+        );
+        let last = boundaries.last().unwrap();
+        assert_eq!(last.offset + last.len, session.len());
+    }
+
+    #[test]
+    fn test_validate_session_empty() {
+        assert!(validate_session(b"").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_validate_session_reports_offset() {
+        let session = b"GET / HTTP/1.1\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort";
+        let err = validate_session(session).unwrap_err();
+
+        match err {
+            WireError::InvalidMessage { offset, source } => {
+                assert_eq!(offset, 18);
+                assert!(matches!(*source, WireError::IncompleteBody(5)));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_expected_total_len() {
+        let header = |name, value| Header { name, value };
+
+        assert_eq!(
+            expected_total_len(40, &[header("content-length", b"25")]),
+            Some(65)
+        );
+        assert_eq!(
+            expected_total_len(
+                40,
+                &[
+                    header("Content-Length", b"25"),
+                    header("Transfer-Encoding", b"gzip"),
+                ]
+            ),
+            None
+        );
+        assert_eq!(
+            expected_total_len(
+                40,
+                &[
+                    header("Content-Length", b"25"),
+                    header("Transfer-Encoding", b"chunked"),
+                ]
+            ),
+            None
+        );
+        assert_eq!(expected_total_len(40, &[header("Host", b"a")]), None);
+        // Values that decoding rejects have no length either
+        for value in [&b"abc"[..], b"12abc", b"+12", b" 25", b""] {
+            assert_eq!(
+                expected_total_len(40, &[header("Content-Length", value)]),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_detect_protocol() {
+        let cases: [(&[u8], Protocol); 12] = [
+            (b"", Protocol::NeedMoreData),
+            (b"P", Protocol::NeedMoreData),
+            (b"POST", Protocol::NeedMoreData),
+            (b"POST /upload HTTP/1.0\r\n", Protocol::Http1Request),
+            (b"PROPFIND /dav HTTP/1.1\n", Protocol::Http1Request),
+            (b"GET / HTTP/2\r\n", Protocol::Unknown),
+            (b"HTTP/1", Protocol::NeedMoreData),
+            (b"HTTP/1.0 200 OK\r\n", Protocol::Http1Response),
+            (b"HTTP/2 200\r\n", Protocol::Unknown),
+            (
+                b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\x00\x00",
+                Protocol::Http2Preface,
+            ),
+            (b"SSH-2.0-OpenSSH_9.6\r\n", Protocol::Unknown),
+            (b" GET / HTTP/1.1\r\n", Protocol::Unknown),
+        ];
+        for (buf, expected) in cases {
+            assert_eq!(
+                detect_protocol(buf),
+                expected,
+                "{}",
+                String::from_utf8_lossy(buf)
+            );
+        }
+    }
+}
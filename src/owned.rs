@@ -0,0 +1,40 @@
+//! Owned counterparts of the borrowed decode types.
+//!
+//! Owned messages hold [`Bytes`] slices of the original input buffer, so they can
+//! outlive it and be sent across tasks or threads without copying.
+
+use bytes::Bytes;
+use httparse::Header;
+
+/// Default number of header slots used by decode entry points that own their storage.
+pub(crate) const DEFAULT_MAX_HEADERS: usize = 64;
+
+/// Most header slots `decode_vec` allocates, however long the head.
+pub(crate) const MAX_VEC_HEADERS: usize = 1024;
+
+/// A header whose name and value share the allocation of the decoded buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnedHeader {
+    /// The header name, exactly as it appeared on the wire.
+    pub name: Bytes,
+    /// The header value, exactly as it appeared on the wire.
+    pub value: Bytes,
+}
+
+impl OwnedHeader {
+    /// Creates an owned header from one borrowed from `buf`, without copying.
+    pub(crate) fn from_header(buf: &Bytes, header: &Header<'_>) -> Self {
+        Self {
+            name: buf.slice_ref(header.name.as_bytes()),
+            value: buf.slice_ref(header.value),
+        }
+    }
+}
+
+/// Returns the value of the first owned header matching `name` (ASCII case-insensitive).
+pub(crate) fn find_owned_header<'a>(headers: &'a [OwnedHeader], name: &str) -> Option<&'a Bytes> {
+    headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case(name.as_bytes()))
+        .map(|h| &h.value)
+}
@@ -0,0 +1,168 @@
+//! Typed `Content-Range` values.
+//!
+//! This module provides [`ContentRange`], the parsed form of the `Content-Range`
+//! header of a `206 Partial Content` or `416 Range Not Satisfiable` response
+//! (RFC 9110 §14.4).
+
+use crate::error::WireError;
+
+/// A parsed `Content-Range` header value in `bytes` units.
+///
+/// Covers the three forms allowed by RFC 9110 §14.4:
+///
+/// | Header value          | `range`             | `complete_length` |
+/// |-----------------------|---------------------|-------------------|
+/// | `bytes 200-1023/2048` | `Some((200, 1023))` | `Some(2048)`      |
+/// | `bytes 200-1023/*`    | `Some((200, 1023))` | `None`            |
+/// | `bytes */2048`        | `None`              | `Some(2048)`      |
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::ContentRange;
+///
+/// let range = ContentRange::parse(b"bytes 200-1023/2048").unwrap();
+/// assert_eq!(range.range, Some((200, 1023)));
+/// assert_eq!(range.complete_length, Some(2048));
+/// assert_eq!(range.part_len(), Some(824));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRange {
+    /// First and last byte positions of the enclosed part, both inclusive.
+    ///
+    /// `None` for the unsatisfiable form `bytes */length`.
+    pub range: Option<(u64, u64)>,
+    /// Length of the complete representation, or `None` if sent as `*`.
+    pub complete_length: Option<u64>,
+}
+
+impl ContentRange {
+    /// Parses a `Content-Range` field value.
+    ///
+    /// Only the `bytes` unit is supported; it is matched case-insensitively.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidContentRange`] if the value is malformed, uses
+    /// another unit, has a last position before the first, or a range that does not
+    /// fit within the complete length.
+    pub fn parse(value: &[u8]) -> Result<Self, WireError> {
+        let value = value.trim_ascii();
+        let rest = value
+            .get(..6)
+            .filter(|unit| unit.eq_ignore_ascii_case(b"bytes "))
+            .map(|_| &value[6..])
+            .ok_or(WireError::InvalidContentRange)?;
+
+        let slash = rest
+            .iter()
+            .position(|&b| b == b'/')
+            .ok_or(WireError::InvalidContentRange)?;
+        let (range, length) = (&rest[..slash], &rest[slash + 1..]);
+
+        let complete_length = match length {
+            b"*" => None,
+            digits => Some(parse_u64(digits)?),
+        };
+
+        let range = match range {
+            // The unsatisfiable form must carry the complete length
+            b"*" if complete_length.is_some() => None,
+            b"*" => return Err(WireError::InvalidContentRange),
+            range => {
+                let dash = range
+                    .iter()
+                    .position(|&b| b == b'-')
+                    .ok_or(WireError::InvalidContentRange)?;
+                let first = parse_u64(&range[..dash])?;
+                let last = parse_u64(&range[dash + 1..])?;
+                if last < first || complete_length.is_some_and(|len| last >= len) {
+                    return Err(WireError::InvalidContentRange);
+                }
+                Some((first, last))
+            }
+        };
+
+        Ok(Self {
+            range,
+            complete_length,
+        })
+    }
+
+    /// Returns the number of bytes in the enclosed part, or `None` if unsatisfiable.
+    pub fn part_len(&self) -> Option<u64> {
+        self.range.map(|(first, last)| last - first + 1)
+    }
+
+    /// Returns `true` for the unsatisfiable form `bytes */length`.
+    pub fn is_unsatisfied(&self) -> bool {
+        self.range.is_none()
+    }
+}
+
+/// Parses a non-empty run of ASCII digits into a `u64`.
+fn parse_u64(digits: &[u8]) -> Result<u64, WireError> {
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(WireError::InvalidContentRange);
+    }
+    digits
+        .iter()
+        .try_fold(0u64, |acc, &d| {
+            acc.checked_mul(10)?.checked_add(u64::from(d - b'0'))
+        })
+        .ok_or(WireError::InvalidContentRange)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forms() {
+        assert_eq!(
+            ContentRange::parse(b"bytes 0-499/1234").unwrap(),
+            ContentRange {
+                range: Some((0, 499)),
+                complete_length: Some(1234),
+            }
+        );
+        assert_eq!(
+            ContentRange::parse(b"Bytes 500-999/*").unwrap(),
+            ContentRange {
+                range: Some((500, 999)),
+                complete_length: None,
+            }
+        );
+
+        let unsatisfied = ContentRange::parse(b" bytes */2048 ").unwrap();
+        assert!(unsatisfied.is_unsatisfied());
+        assert_eq!(unsatisfied.complete_length, Some(2048));
+        assert_eq!(unsatisfied.part_len(), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        for value in [
+            &b""[..],
+            b"bytes",
+            b"items 0-1/2",
+            b"bytes 0-1",
+            b"bytes */*",
+            b"bytes 5-4/10",
+            b"bytes 0-10/10",
+            b"bytes -1/10",
+            b"bytes 0-+1/10",
+            b"bytes 0-99999999999999999999/*",
+            b"bytes 0 - 1/10",
+        ] {
+            assert!(
+                matches!(
+                    ContentRange::parse(value),
+                    Err(WireError::InvalidContentRange)
+                ),
+                "{}",
+                String::from_utf8_lossy(value)
+            );
+        }
+    }
+}
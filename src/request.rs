@@ -5,25 +5,74 @@
 //!
 //! # Request Encoding
 //!
-//! The [`WireEncode`] and [`WireEncodeAsync`] traits are implemented for [`http::Request`],
-//! allowing you to serialize requests to bytes.
+//! With the `encode` feature, enabled by default, the [`WireEncode`] and
+//! [`WireEncodeAsync`] traits are implemented for [`http::Request`], allowing you to
+//! serialize requests to bytes.
 //!
+//! Any [`http::Method`] is accepted, including extension methods such as the WebDAV
+//! `PROPFIND` or a cache `PURGE`, which are written to the request line verbatim:
+//!
+//! ```rust
+//! # #[cfg(feature = "encode")] {
+//! use http_wire::WireEncode;
+//! use http_body_util::Empty;
+//! use bytes::Bytes;
+//!
+//! let request = http::Request::builder()
+//!     .method(http::Method::from_bytes(b"PURGE").unwrap())
+//!     .uri("/cached/page")
+//!     .header("Host", "example.com")
+//!     .body(Empty::<Bytes>::new())
+//!     .unwrap();
+//!
+//! let bytes = request.encode().unwrap();
+//! assert!(bytes.starts_with(b"PURGE /cached/page HTTP/1.1\r\n"));
+//! # }
+//! ```
 
 use bytes::Bytes;
-use http_body_util::Empty;
-use hyper_util::rt::TokioIo;
-use tokio::io::duplex;
-use tokio::sync::oneshot;
+#[cfg(feature = "encode")]
+use bytes::BytesMut;
+#[cfg(feature = "encode")]
+use http_body_util::Full;
+#[cfg(feature = "encode")]
+use std::future::Future;
+#[cfg(feature = "encode")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 pub use httparse::{Header, Request};
 
+use crate::WireDecode;
+use crate::cache_control::CacheControl;
+use crate::chunked::decode_trailers;
+use crate::decoder::{DecodeOptions, Limits};
 use crate::error::WireError;
-use crate::util::{is_chunked_slice, parse_chunked_body, parse_usize};
-use crate::wire::WireCapture;
-use crate::{WireDecode, WireEncode, WireEncodeAsync};
-use std::mem::MaybeUninit;
+use crate::owned::{DEFAULT_MAX_HEADERS, MAX_VEC_HEADERS, OwnedHeader, find_owned_header};
+#[cfg(feature = "encode")]
+use crate::util::find_head_end;
+#[cfg(feature = "std")]
+use crate::util::header_map;
+use crate::util::{
+    combine_header_values, connection_tokens, find_header, flag_incomplete_chunked,
+    has_chunked_encoding, head_error, head_lines, is_chunked_slice, is_hop_by_hop, is_tchar,
+    parse_content_length, parsed_headers_len, split_chunks, transfer_codings, unframed_body,
+    write_header, write_parsed_headers, write_request_line,
+};
+#[cfg(feature = "encode")]
+use crate::wire::{
+    BufferedBody, block_on_encode, collect_sized, is_sized, recorded_headers, split_vectored,
+    stream_request, write_request,
+};
+#[cfg(feature = "encode")]
+use crate::{WireEncode, WireEncodeAsync};
+use alloc::borrow::Cow;
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem::MaybeUninit;
 
 // Implementation of WireEncode for Request
+#[cfg(feature = "encode")]
 impl<B> WireEncode for http::Request<B>
 where
     B: http_body_util::BodyExt + Send + Sync + 'static,
@@ -31,91 +80,172 @@ where
     B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
     fn encode(self) -> Result<Bytes, WireError> {
-        // Create a minimal single-threaded runtime
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|e| WireError::Connection(Box::new(e)))?;
+        // Sized bodies are encoded in place; others block on the shared runtime
+        let sized = is_sized(self.body());
+        block_on_encode(sized, self.encode_async())?
+    }
 
-        // Block on the async encode method
-        rt.block_on(self.encode_async())
+    fn encode_into(self, buf: &mut BytesMut) -> Result<usize, WireError> {
+        let start = buf.len();
+        let sized = is_sized(self.body());
+        block_on_encode(sized, encode_request_into(self, buf))??;
+        Ok(buf.len() - start)
+    }
+
+    fn encode_vectored(self) -> Result<Vec<Bytes>, WireError> {
+        let sized = is_sized(self.body());
+        block_on_encode(sized, async {
+            let (parts, body) = self.into_parts();
+            match collect_sized(body).await? {
+                Ok(body) => {
+                    let request = http::Request::from_parts(parts, Full::new(body.clone()));
+                    split_vectored(request.encode_async().await?, Some(body))
+                }
+                Err(body) => split_vectored(
+                    http::Request::from_parts(parts, body)
+                        .encode_async()
+                        .await?,
+                    None,
+                ),
+            }
+        })?
     }
 }
 
+#[cfg(feature = "encode")]
 impl<B> WireEncodeAsync for http::Request<B>
 where
     B::Data: Send + Sync + 'static,
     B: http_body_util::BodyExt + Send + Sync + 'static,
     B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
-    #[inline]
+    /// The body is read frame by frame and the request is serialized directly, with
+    /// no I/O or task spawning, so this runs on any executor.
     async fn encode_async(self) -> Result<Bytes, WireError> {
-        use hyper::service::service_fn;
-        use std::convert::Infallible;
+        let mut out = BytesMut::new();
+        encode_request_into(self, &mut out).await?;
+        Ok(out.freeze())
+    }
 
-        // Check HTTP version - only HTTP/1.1 and HTTP/1.0 are supported
-        let version = self.version();
-        if version != http::Version::HTTP_11 && version != http::Version::HTTP_10 {
-            return Err(WireError::UnsupportedVersion);
-        }
+    async fn encode_to<W>(self, writer: &mut W) -> Result<usize, WireError>
+    where
+        W: AsyncWrite + Unpin + Send,
+    {
+        stream_request(writer, self).await
+    }
+}
 
-        let (client, server) = duplex(8192);
-        let capture_client = WireCapture::new(client);
-        let captured_ref = capture_client.captured.clone();
+/// Appends the wire bytes of a request to `out`.
+///
+/// This is the implementation of [`WireEncodeAsync::encode_async`]: the body is read
+/// into memory, then serialized like [`serialize_request`](crate::wire::serialize_request).
+#[cfg(feature = "encode")]
+pub(crate) async fn encode_request_into<B>(
+    request: http::Request<B>,
+    out: &mut BytesMut,
+) -> Result<(), WireError>
+where
+    B::Data: Send + Sync + 'static,
+    B: http_body_util::BodyExt + Send + Sync + 'static,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let (parts, body) = request.into_parts();
+    write_request(out, parts, BufferedBody::read(body).await?)
+}
 
-        let (tx, rx) = oneshot::channel::<Result<(), WireError>>();
+/// Writes a request to `writer` following the client side of the `100-continue` flow.
+///
+/// The request is encoded with an `Expect: 100-continue` header (added if missing).
+/// The head is written and flushed first; the body is written only once
+/// `await_continue` resolves, which the caller should arrange to happen when a
+/// `100 Continue` interim response is received (or when its own timeout expires).
+///
+/// Returns the total number of bytes written.
+///
+/// # Errors
+///
+/// Returns the same errors as [`WireEncodeAsync::encode_async`], or
+/// [`WireError::Connection`] if writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::request::encode_request_expect_continue;
+/// use http::Request;
+/// use http_body_util::Full;
+/// use bytes::Bytes;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let request = Request::builder()
+///     .method("PUT")
+///     .uri("/upload")
+///     .header("Host", "example.com")
+///     .body(Full::new(Bytes::from("hello")))
+///     .unwrap();
+///
+/// let mut out = Vec::new();
+/// // Resolve immediately; a real client waits for the server's 100 Continue
+/// let written = encode_request_expect_continue(&mut out, request, async {})
+///     .await
+///     .unwrap();
+/// assert_eq!(written, out.len());
+/// assert!(out.ends_with(b"\r\n\r\nhello"));
+/// # }
+/// ```
+#[cfg(feature = "encode")]
+pub async fn encode_request_expect_continue<W, B, F>(
+    writer: &mut W,
+    mut request: http::Request<B>,
+    await_continue: F,
+) -> Result<usize, WireError>
+where
+    W: AsyncWrite + Unpin,
+    http::Request<B>: WireEncodeAsync,
+    F: Future<Output = ()>,
+{
+    request
+        .headers_mut()
+        .entry(http::header::EXPECT)
+        .or_insert(http::HeaderValue::from_static("100-continue"));
 
-        // Spawn a mock server that will accept the connection and read the request
-        let server_handle = tokio::spawn(async move {
-            let tx = std::sync::Mutex::new(Some(tx));
-            let service = service_fn(move |_req: http::Request<hyper::body::Incoming>| {
-                // Signal that the request has been received
-                if let Some(tx) = tx.lock().unwrap().take() {
-                    let _ = tx.send(Ok(()));
-                }
-                async move {
-                    // Return a minimal response
-                    Ok::<_, Infallible>(http::Response::new(Empty::<Bytes>::new()))
-                }
-            });
+    let bytes = request.encode_async().await?;
+    let head_end = find_head_end(&bytes).ok_or(WireError::PartialHead)?;
+    let (head, body) = bytes.split_at(head_end);
+    let io_err = |e: std::io::Error| WireError::Connection(Box::new(e));
 
-            hyper::server::conn::http1::Builder::new()
-                .serve_connection(TokioIo::new(server), service)
-                .await
-        });
+    writer.write_all(head).await.map_err(io_err)?;
+    writer.flush().await.map_err(io_err)?;
 
-        // Send the request through the client side and capture what's written
-        let client_handle = tokio::spawn(async move {
-            let client_connection = hyper::client::conn::http1::Builder::new()
-                .handshake(TokioIo::new(capture_client))
-                .await;
-
-            match client_connection {
-                Ok((mut sender, connection)) => {
-                    // Spawn the connection driver
-                    tokio::spawn(connection);
-
-                    // Send the request
-                    sender
-                        .send_request(self)
-                        .await
-                        .map(|_| ())
-                        .map_err(|e| WireError::Connection(Box::new(e)))
-                }
-                Err(e) => Err(WireError::Connection(Box::new(e))),
-            }
-        });
+    await_continue.await;
 
-        // Wait for the server to receive the request
-        rx.await.map_err(|_| WireError::Sync)??;
+    writer.write_all(body).await.map_err(io_err)?;
+    writer.flush().await.map_err(io_err)?;
+    Ok(bytes.len())
+}
+
+/// The parts of a decoded request: method, path, minor version, headers and body.
+///
+/// Returned by [`FullRequest::as_parts`].
+pub type RequestParts<'a, 'buf> = (&'buf str, &'buf str, u8, &'a [Header<'buf>], &'buf [u8]);
 
-        // Cleanup
-        client_handle.abort();
-        server_handle.abort();
+/// The methods defined by RFC 9110 §9 and `PATCH` (RFC 5789), accepted by
+/// [`FullRequest::decode_strict`].
+pub const KNOWN_METHODS: &[&str] = &[
+    "GET", "HEAD", "POST", "PUT", "DELETE", "CONNECT", "OPTIONS", "TRACE", "PATCH",
+];
 
-        let result = captured_ref.lock().clone();
-        Ok(Bytes::from(result))
-    }
+/// How a connection should be handled after the current request.
+///
+/// Returned by [`FullRequest::connection_disposition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disposition {
+    /// The connection stays open for further requests.
+    KeepAlive,
+    /// The connection must be closed after the response.
+    Close,
+    /// The client asked to switch protocols via the `Upgrade` header.
+    Upgrade,
 }
 
 /// Decoder for determining HTTP request message length.
@@ -138,6 +268,54 @@ pub struct FullRequest<'headers, 'buf> {
     /// or [`decode`](WireDecode::decode). It contains the complete body content
     /// after decoding any transfer encodings (chunked or content-length).
     pub body: &'buf [u8],
+    /// The body length declared by the `Content-Length` header.
+    ///
+    /// Set by [`parse`](Self::parse) and [`decode`](WireDecode::decode) from the
+    /// header they use to frame the body. `None` if the header is absent, and for
+    /// chunked messages.
+    pub content_length: Option<usize>,
+}
+
+/// A decoded request that owns its data.
+///
+/// Produced by [`FullRequest::decode_owned`]. All fields are [`Bytes`] slices of the
+/// decoded buffer, so no data is copied and the request can be moved across tasks or
+/// threads independently of the original buffer's borrow.
+#[derive(Debug, Clone)]
+pub struct OwnedRequest {
+    /// The request method.
+    pub method: Bytes,
+    /// The request target, as sent.
+    pub path: Bytes,
+    /// The HTTP minor version (`0` for HTTP/1.0, `1` for HTTP/1.1).
+    pub version: u8,
+    /// The headers, in their original order.
+    pub headers: Vec<OwnedHeader>,
+    /// The request body, with the same framing semantics as [`FullRequest::body`].
+    pub body: Bytes,
+}
+
+impl OwnedRequest {
+    /// Creates an owned request from one decoded from `buf`, without copying.
+    pub(crate) fn from_full(buf: &Bytes, request: &FullRequest<'_, '_>) -> Self {
+        Self {
+            method: buf.slice_ref(request.head.method.unwrap_or_default().as_bytes()),
+            path: buf.slice_ref(request.head.path.unwrap_or_default().as_bytes()),
+            version: request.head.version.unwrap_or(1),
+            headers: request
+                .head
+                .headers
+                .iter()
+                .map(|h| OwnedHeader::from_header(buf, h))
+                .collect(),
+            body: buf.slice_ref(request.body),
+        }
+    }
+
+    /// Returns the value of the first header matching `name` (ASCII case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&Bytes> {
+        find_owned_header(&self.headers, name)
+    }
 }
 
 impl<'headers, 'buf> FullRequest<'headers, 'buf> {
@@ -151,6 +329,7 @@ impl<'headers, 'buf> FullRequest<'headers, 'buf> {
     ///
     /// * `buf` - The buffer containing the raw HTTP message bytes
     /// * `headers_len` - The length of the headers section in bytes (including the `\r\n\r\n` terminator)
+    /// * `options` - Limits applied while framing the body
     ///
     /// # Returns
     ///
@@ -160,30 +339,55 @@ impl<'headers, 'buf> FullRequest<'headers, 'buf> {
     ///
     /// Returns [`WireError::InvalidChunkedBody`] if chunked encoding is malformed,
     /// or [`WireError::IncompleteBody`] if the body is shorter than specified by `Content-Length`.
-    fn parse_core(&mut self, buf: &'buf [u8], headers_len: usize) -> Result<usize, WireError> {
+    fn parse_core(
+        &mut self,
+        buf: &'buf [u8],
+        headers_len: usize,
+        options: &DecodeOptions,
+    ) -> Result<usize, WireError> {
         let mut content_len: Option<usize> = None;
+        let mut has_content_len = false;
+        let mut has_transfer_encoding = false;
         let mut is_chunked = false;
 
         // Scan headers for Content-Length or Transfer-Encoding
         for header in self.head.headers.iter() {
             let name = header.name.as_bytes();
             if name.len() == 14 && name.eq_ignore_ascii_case(b"Content-Length") {
-                content_len = parse_usize(header.value);
+                let len = Some(parse_content_length(header.value)?);
+                if has_content_len && len != content_len {
+                    return Err(WireError::DuplicateContentLength);
+                }
+                content_len = len;
+                has_content_len = true;
             } else if name.len() == 17 && name.eq_ignore_ascii_case(b"Transfer-Encoding") {
+                has_transfer_encoding = true;
                 is_chunked = is_chunked_slice(header.value);
             }
         }
 
+        // Both framings at once is a request smuggling vector (RFC 9112 §6.3)
+        if has_transfer_encoding && has_content_len {
+            return Err(WireError::ConflictingFraming);
+        }
+        // So is a request body whose length cannot be determined (RFC 9112 §6.3)
+        if has_transfer_encoding && !is_chunked {
+            return Err(WireError::UnsupportedTransferCoding);
+        }
+        self.content_length = content_len;
+
         // Calculate body length
         if is_chunked {
-            let body_len =
-                parse_chunked_body(&buf[headers_len..]).ok_or(WireError::InvalidChunkedBody)?;
+            let body_len = options.chunked_body_len(buf, headers_len)?;
             self.body = &buf[headers_len..headers_len + body_len];
             Ok(headers_len + body_len)
         } else {
             // If content-length is missing, length is 0
             let body_len = content_len.unwrap_or(0);
-            let total = headers_len + body_len;
+            options.check_body_len(body_len)?;
+            let total = headers_len
+                .checked_add(body_len)
+                .ok_or(WireError::InvalidContentLength)?;
             if buf.len() >= total {
                 self.body = &buf[headers_len..total];
                 Ok(total)
@@ -213,14 +417,440 @@ impl<'headers, 'buf> FullRequest<'headers, 'buf> {
     /// # Errors
     ///
     /// Returns [`WireError::PartialHead`] if headers are incomplete,
-    /// [`WireError::HttparseError`] if header parsing fails,
+    /// [`WireError::Malformed`] if the head is malformed, [`WireError::HttparseError`] if
+    /// it has more headers than the storage can hold,
     /// or errors from [`parse_core`](Self::parse_core) for body-related issues.
     pub fn parse(&mut self, buf: &'buf [u8]) -> Result<usize, WireError> {
+        self.parse_with_options(buf, &DecodeOptions::new())
+    }
+
+    /// Parse an HTTP request, enforcing the limits in `options`.
+    ///
+    /// Behaves like [`parse`](Self::parse) with the additional checks described on
+    /// [`DecodeOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`parse`](Self::parse), plus the errors of the limits
+    /// set in `options`, such as [`WireError::HeadTooLarge`] for
+    /// [`DecodeOptions::max_head_bytes`] or [`WireError::InvalidMethod`] for
+    /// [`DecodeOptions::allowed_methods`]; each option documents its error.
+    pub fn parse_with_options(
+        &mut self,
+        buf: &'buf [u8],
+        options: &DecodeOptions,
+    ) -> Result<usize, WireError> {
+        if let Some(allowed) = options.allowed_methods {
+            check_method(buf, allowed)?;
+        }
         match self.head.parse(buf) {
-            Ok(httparse::Status::Complete(headers_len)) => self.parse_core(buf, headers_len),
-            Ok(httparse::Status::Partial) => Err(WireError::PartialHead),
-            Err(err) => Err(err.into()),
+            Ok(httparse::Status::Complete(headers_len)) => {
+                options.check_head_len(headers_len)?;
+                options.check_header_count(self.head.headers.len())?;
+                self.parse_core(buf, headers_len, options)
+            }
+            Ok(httparse::Status::Partial) => {
+                options.check_head_len(buf.len())?;
+                Err(WireError::PartialHead)
+            }
+            Err(err) => {
+                let err = head_error(err, buf, |headers, prefix| {
+                    httparse::Request::new(headers).parse(prefix).is_err()
+                });
+                Err(options.refine_head_error(err, buf))
+            }
+        }
+    }
+
+    /// Decodes a request like [`decode`](WireDecode::decode), enforcing the limits in
+    /// `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`parse_with_options`](Self::parse_with_options).
+    pub fn decode_with_options(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+        options: &DecodeOptions,
+    ) -> Result<(Self, usize), WireError> {
+        let mut full_request = FullRequest {
+            head: httparse::Request::new(headers),
+            body: &[],
+            content_length: None,
+        };
+
+        let total = full_request.parse_with_options(buf, options)?;
+        Ok((full_request, total))
+    }
+
+    /// Decodes a request like [`decode`](WireDecode::decode), accepting only the methods
+    /// in [`KNOWN_METHODS`] and reporting protocol violations with dedicated errors.
+    ///
+    /// A request line such as `GE T / HTTP/1.1` or one with control characters in the
+    /// method fails with [`WireError::InvalidMethod`] instead of being decoded or
+    /// reported as [`WireError::Malformed`], so tools inspecting traffic can tell bogus
+    /// methods apart. This is shorthand for
+    /// [`decode_with_options`](Self::decode_with_options) with
+    /// [`DecodeOptions::allowed_methods`] and [`DecodeOptions::strict`]; use that to
+    /// accept extension methods.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode), plus
+    /// [`WireError::InvalidMethod`] if the method is not a known one and
+    /// [`WireError::ObsoleteFolding`] if a header value is folded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireError;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let result = FullRequest::decode_strict(b"GE T / HTTP/1.1\r\n\r\n", &mut headers);
+    /// assert!(matches!(result, Err(WireError::InvalidMethod)));
+    ///
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode_strict(b"GET / HTTP/1.1\r\n\r\n", &mut headers).unwrap();
+    /// assert_eq!(request.head.method, Some("GET"));
+    /// ```
+    pub fn decode_strict(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+    ) -> Result<(Self, usize), WireError> {
+        Self::decode_with_options(
+            buf,
+            headers,
+            &DecodeOptions::new()
+                .allowed_methods(KNOWN_METHODS)
+                .strict(true),
+        )
+    }
+
+    /// Decodes a request like [`decode`](WireDecode::decode), enforcing every limit in
+    /// `limits`.
+    ///
+    /// [`decode`](WireDecode::decode) does not limit the message, so a peer that keeps
+    /// sending header lines without the terminating blank line, or announces a huge
+    /// `Content-Length`, gets [`WireError::PartialHead`] or
+    /// [`WireError::IncompleteBody`] for as long as the caller keeps buffering. This is
+    /// shorthand for [`decode_with_options`](Self::decode_with_options) with the
+    /// options converted from `limits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode), plus
+    /// [`WireError::HeadTooLarge`] if the head, complete or not, is longer than
+    /// [`Limits::max_head_bytes`], [`WireError::BodyTooLarge`] if the body is longer
+    /// than [`Limits::max_body_bytes`], [`WireError::TooManyChunks`] if a chunked body
+    /// has more than [`Limits::max_chunk_count`] chunks and
+    /// [`WireError::HttparseError`] if there are more than [`Limits::max_headers`]
+    /// headers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::{Limits, WireError};
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let limits = Limits {
+    ///     max_head_bytes: 32,
+    ///     ..Limits::default()
+    /// };
+    /// let mut headers = [httparse::EMPTY_HEADER; 64];
+    /// // A head that never ends is rejected once it passes the limit
+    /// let raw = b"GET / HTTP/1.1\r\nX-Filler: aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\r\n";
+    /// let result = FullRequest::decode_limited(raw, &mut headers, &limits);
+    /// assert!(matches!(result, Err(WireError::HeadTooLarge)));
+    /// ```
+    pub fn decode_limited(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+        limits: &Limits,
+    ) -> Result<(Self, usize), WireError> {
+        Self::decode_with_options(buf, headers, &DecodeOptions::from(*limits))
+    }
+
+    /// Decodes a request like [`decode`](WireDecode::decode), rejecting a header section
+    /// longer than `max_head_bytes`.
+    ///
+    /// [`decode`](WireDecode::decode) does not limit the head, so a peer that keeps
+    /// sending header lines without the terminating blank line gets
+    /// [`WireError::PartialHead`] for as long as the caller keeps buffering. This is
+    /// shorthand for [`decode_with_options`](Self::decode_with_options) with
+    /// [`DecodeOptions::max_head_bytes`]; see [`decode_limited`](Self::decode_limited)
+    /// to also limit the headers and the body.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode), plus
+    /// [`WireError::HeadTooLarge`] if the head, complete or not, is longer than
+    /// `max_head_bytes`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireError;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let mut headers = [httparse::EMPTY_HEADER; 64];
+    /// // A head that never ends is rejected once it passes the limit
+    /// let raw = b"GET / HTTP/1.1\r\nX-Filler: aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\r\n";
+    /// let result = FullRequest::decode_with_limits(raw, &mut headers, 32);
+    /// assert!(matches!(result, Err(WireError::HeadTooLarge)));
+    /// ```
+    pub fn decode_with_limits(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+        max_head_bytes: usize,
+    ) -> Result<(Self, usize), WireError> {
+        Self::decode_with_options(
+            buf,
+            headers,
+            &DecodeOptions::new().max_head_bytes(max_head_bytes),
+        )
+    }
+
+    /// Decodes a request like [`decode_owned`](Self::decode_owned), with header storage
+    /// sized to the head.
+    ///
+    /// The lines of the head are counted first, and a `Vec` with that many header
+    /// slots, up to 1024, is allocated for a single parse. Unlike
+    /// [`decode_owned`](Self::decode_owned), a head with more than 64 headers fits.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode), with
+    /// [`WireError::HttparseError`] wrapping [`httparse::Error::TooManyHeaders`] only
+    /// if the head has more than 1024 headers.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bytes::Bytes;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let mut raw = b"GET / HTTP/1.1\r\n".to_vec();
+    /// for i in 0..100 {
+    ///     raw.extend_from_slice(format!("X-Header-{i}: {i}\r\n").as_bytes());
+    /// }
+    /// raw.extend_from_slice(b"\r\n");
+    ///
+    /// let (request, _) = FullRequest::decode_vec(Bytes::from(raw)).unwrap();
+    /// assert_eq!(request.headers.len(), 100);
+    /// ```
+    pub fn decode_vec(buf: Bytes) -> Result<(OwnedRequest, usize), WireError> {
+        let (lines, _) = head_lines(&buf);
+        let mut headers = vec![httparse::EMPTY_HEADER; lines.min(MAX_VEC_HEADERS)];
+        let (request, total) = FullRequest::decode(&buf, &mut headers)?;
+        Ok((OwnedRequest::from_full(&buf, &request), total))
+    }
+
+    /// Returns an iterator over the requests pipelined in `buf`.
+    ///
+    /// Each item is a request decoded like [`decode_owned`](Self::decode_owned) and its
+    /// length, so every request has its own header storage for up to 64 headers and
+    /// can be kept after the next one is decoded. Iteration stops at the end of `buf`,
+    /// or silently at a trailing request that is incomplete ([`WireError::PartialHead`],
+    /// [`WireError::IncompleteBody`] or a chunked body cut short);
+    /// [`DecodeAll::consumed`] then gives the offset where it starts, so the caller can
+    /// keep those bytes until more data arrives.
+    ///
+    /// # Errors
+    ///
+    /// Any other decoding error is yielded once, after which the iterator ends. A
+    /// request with more than 64 headers is reported as [`WireError::HttparseError`]
+    /// wrapping [`httparse::Error::TooManyHeaders`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bytes::Bytes;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = Bytes::from_static(b"GET /a HTTP/1.1\r\nHost: x\r\n\r\nPOST /b HTTP/1.1\r\nHost: x\r\nContent-Length: 2\r\n\r\nhiGET /c HTT");
+    /// let mut requests = FullRequest::decode_all(raw.clone());
+    ///
+    /// let paths: Vec<_> = requests
+    ///     .by_ref()
+    ///     .map(|item| item.unwrap().0.path)
+    ///     .collect();
+    /// assert_eq!(paths, ["/a", "/b"]);
+    /// assert_eq!(&raw[requests.consumed()..], b"GET /c HTT");
+    /// ```
+    pub fn decode_all(buf: Bytes) -> DecodeAll {
+        DecodeAll {
+            buf,
+            consumed: 0,
+            done: false,
+        }
+    }
+
+    /// Decodes a request like [`decode`](WireDecode::decode), also returning its body with
+    /// the chunked framing removed.
+    ///
+    /// Chunk extensions and trailers are dropped from the returned body. A body that is
+    /// not chunked is returned as is. [`body`](Self::body) still holds the raw bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode), except that a chunked
+    /// body cut short returns [`WireError::IncompleteChunkedBody`], so the caller knows
+    /// to read more data. Malformed chunk framing returns
+    /// [`WireError::InvalidChunkedBody`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"POST /upload HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\nX-Checksum: 1\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, body, len) = FullRequest::decode_dechunked(raw, &mut headers).unwrap();
+    /// assert_eq!(body, b"hello world");
+    /// assert_eq!(len, raw.len());
+    /// ```
+    pub fn decode_dechunked(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+    ) -> Result<(Self, Vec<u8>, usize), WireError> {
+        let (request, total) =
+            Self::decode(buf, headers).map_err(|err| flag_incomplete_chunked(err, buf.len()))?;
+        let body = unframed_body(request.head.headers, request.body, false)?.into_owned();
+        Ok((request, body, total))
+    }
+
+    /// Parses the trailer section of a chunked body into `trailers`.
+    ///
+    /// Works like [`decode_trailers`] on [`body`](Self::body), with
+    /// a caller-supplied header array as for [`decode`](WireDecode::decode). Returns an
+    /// empty slice if the body is not chunked or has no trailers.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode_trailers`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"POST /upload HTTP/1.1\r\nHost: example.com\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nok\r\n0\r\nX-Checksum: 42\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// let mut trailers = [httparse::EMPTY_HEADER; 4];
+    /// let trailers = request.trailers(&mut trailers).unwrap();
+    /// assert_eq!(trailers[0].name, "X-Checksum");
+    /// assert_eq!(trailers[0].value, b"42");
+    /// ```
+    pub fn trailers<'t>(
+        &self,
+        trailers: &'t mut [Header<'buf>],
+    ) -> Result<&'t [Header<'buf>], WireError> {
+        if !has_chunked_encoding(self.head.headers) {
+            return Ok(&[]);
+        }
+        decode_trailers(self.body, trailers)
+    }
+
+    /// Decodes a request into an [`OwnedRequest`] that shares `buf`'s allocation.
+    ///
+    /// The method, request target, header names and values and the body are zero-copy
+    /// [`Bytes::slice_ref`] views into `buf`, so the request can be handed to APIs that
+    /// take ownership without keeping `buf` borrowed. A codec holding a
+    /// [`BytesMut`] can split off the buffered data and
+    /// [`freeze`](bytes::BytesMut::freeze) it. Up to 64 headers are supported.
+    ///
+    /// Returns the decoded request and the total length of the message in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bytes::BytesMut;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let mut read_buf = BytesMut::from(&b"POST /items HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello"[..]);
+    /// let (request, len) = FullRequest::decode_owned(read_buf.split().freeze()).unwrap();
+    ///
+    /// let handle = std::thread::spawn(move || request.body);
+    /// assert_eq!(handle.join().unwrap(), "hello");
+    /// assert_eq!(len, 48);
+    /// ```
+    pub fn decode_owned(buf: Bytes) -> Result<(OwnedRequest, usize), WireError> {
+        let mut headers = [httparse::EMPTY_HEADER; DEFAULT_MAX_HEADERS];
+        let (request, total) = FullRequest::decode(&buf, &mut headers)?;
+        Ok((OwnedRequest::from_full(&buf, &request), total))
+    }
+
+    /// Converts the request into an [`http::Request`] with an owned body.
+    ///
+    /// Method, request target, version and headers are copied as sent. With the
+    /// `encode` feature, the spelling and order of the headers are kept in
+    /// [`HeaderCase`](crate::wire::HeaderCase) and
+    /// [`HeaderOrder`](crate::wire::HeaderOrder) extensions. The body has its chunked
+    /// framing removed, like the body returned by
+    /// [`decode_dechunked`](Self::decode_dechunked), so encoding the result again
+    /// produces an equivalent message; trailers are dropped.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireError::InvalidHttp`] if the method, request target, or a header name or
+    ///   value is not accepted by `http`
+    /// - [`WireError::InvalidChunkedBody`] if the chunk framing is malformed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"POST /items?id=1 HTTP/1.1\r\nHost: example.com\r\nContent-Length: 5\r\n\r\nhello";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// let request = request.into_http().unwrap();
+    /// assert_eq!(request.method(), http::Method::POST);
+    /// assert_eq!(request.uri().query(), Some("id=1"));
+    /// assert_eq!(request.headers()["host"], "example.com");
+    /// assert_eq!(request.body(), "hello");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn into_http(&self) -> Result<http::Request<Bytes>, WireError> {
+        self.to_http_with(false)
+    }
+
+    /// Converts like [`into_http`](Self::into_http), with chunk lines allowed to end
+    /// with a bare LF if `bare_lf` is set, as for a message decoded with
+    /// [`DecodeOptions::allow_bare_lf`].
+    #[cfg(feature = "std")]
+    pub(crate) fn to_http_with(&self, bare_lf: bool) -> Result<http::Request<Bytes>, WireError> {
+        let version = match self.head.version {
+            Some(0) => http::Version::HTTP_10,
+            _ => http::Version::HTTP_11,
+        };
+        let mut builder = http::Request::builder()
+            .method(self.head.method.unwrap_or_default())
+            .uri(self.head.path.unwrap_or_default())
+            .version(version);
+        for header in self.head.headers.iter() {
+            let name =
+                http::HeaderName::from_bytes(header.name.as_bytes()).map_err(http::Error::from)?;
+            builder = builder.header(name, header.value);
         }
+        // Only the encoder reads these extensions
+        #[cfg(feature = "encode")]
+        {
+            let (case, order) = recorded_headers(self.head.headers)?;
+            builder = builder.extension(case).extension(order);
+        }
+        let body = unframed_body(self.head.headers, self.body, bare_lf)?;
+        Ok(builder.body(Bytes::from(body.into_owned()))?)
     }
 
     /// Parse an HTTP request using uninitialized headers storage (performance optimization).
@@ -243,7 +873,8 @@ impl<'headers, 'buf> FullRequest<'headers, 'buf> {
     /// # Errors
     ///
     /// Returns [`WireError::PartialHead`] if headers are incomplete,
-    /// [`WireError::HttparseError`] if header parsing fails,
+    /// [`WireError::Malformed`] if the head is malformed, [`WireError::HttparseError`] if
+    /// it has more headers than the storage can hold,
     /// or errors from [`parse_core`](Self::parse_core) for body-related issues.
     pub fn parse_uninit(
         &mut self,
@@ -251,62 +882,775 @@ impl<'headers, 'buf> FullRequest<'headers, 'buf> {
         headers: &'headers mut [MaybeUninit<Header<'buf>>],
     ) -> Result<usize, WireError> {
         match self.head.parse_with_uninit_headers(buf, headers) {
-            Ok(httparse::Status::Complete(headers_len)) => self.parse_core(buf, headers_len),
+            Ok(httparse::Status::Complete(headers_len)) => {
+                self.parse_core(buf, headers_len, &DecodeOptions::new())
+            }
             Ok(httparse::Status::Partial) => Err(WireError::PartialHead),
-            Err(err) => Err(err.into()),
+            Err(err) => Err(head_error(err, buf, |headers, prefix| {
+                httparse::Request::new(headers).parse(prefix).is_err()
+            })),
         }
     }
-}
 
-impl<'headers, 'buf> WireDecode<'headers, 'buf> for FullRequest<'headers, 'buf> {
-    fn decode(
-        buf: &'buf [u8],
-        headers: &'headers mut [Header<'buf>],
-    ) -> Result<(Self, usize), WireError> {
-        let mut full_request = FullRequest {
-            head: httparse::Request::new(headers),
-            body: &[],
-        };
+    /// Returns `true` if the client expects a `100 Continue` before sending the body.
+    ///
+    /// This is the case when the request carries `Expect: 100-continue` (case-insensitive).
+    /// Per RFC 9110, the expectation is ignored for HTTP/1.0 requests.
+    ///
+    /// A server that gets `true` here should send [`encode_continue`](crate::response::encode_continue)
+    /// before reading the body.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"PUT /upload HTTP/1.1\r\nHost: example.com\r\nExpect: 100-continue\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    /// assert!(request.expects_continue());
+    /// ```
+    pub fn expects_continue(&self) -> bool {
+        if self.head.version != Some(1) {
+            return false;
+        }
 
-        let total = full_request.parse(buf)?;
-        Ok((full_request, total))
+        find_header(self.head.headers, "Expect")
+            .is_some_and(|value| value.trim_ascii().eq_ignore_ascii_case(b"100-continue"))
     }
 
-    fn decode_uninit(
-        buf: &'buf [u8],
-        headers: &'headers mut [MaybeUninit<Header<'buf>>],
-    ) -> Result<(Self, usize), WireError> {
-        let mut full_request = FullRequest {
-            head: httparse::Request::new(&mut []),
-            body: &[],
-        };
+    /// Re-serializes the request for forwarding by a proxy.
+    ///
+    /// Hop-by-hop headers are dropped: `Connection`, `Keep-Alive`, `TE`, `Trailer`,
+    /// `Transfer-Encoding`, `Upgrade`, any `Proxy-*` header, and every header named
+    /// in a `Connection` header. The remaining headers are emitted verbatim, in their
+    /// original order and casing.
+    ///
+    /// Since `Transfer-Encoding` is removed, a chunked body is de-chunked and sent with
+    /// a `Content-Length` header instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidChunkedBody`] if a chunked body is malformed, rather
+    /// than forwarding it with framing the next hop would not expect.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\nConnection: close, X-Trace\r\nX-Trace: 1\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// let forwarded = request.reserialize_for_forwarding().unwrap();
+    /// assert_eq!(forwarded, b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n");
+    /// ```
+    pub fn reserialize_for_forwarding(&self) -> Result<Vec<u8>, WireError> {
+        let headers = &*self.head.headers;
+        let chunked = has_chunked_encoding(headers);
+        let body = unframed_body(headers, self.body, false)?;
 
-        let total = full_request.parse_uninit(buf, headers)?;
-        Ok((full_request, total))
+        let mut out = Vec::with_capacity(self.body.len() + 256);
+        write_request_line(
+            &mut out,
+            self.head.method.unwrap_or_default(),
+            self.head.path.unwrap_or_default(),
+            self.head.version.unwrap_or(1),
+        );
+        for header in headers.iter().filter(|h| !is_hop_by_hop(headers, h.name)) {
+            write_header(&mut out, header.name.as_bytes(), header.value);
+        }
+        if chunked {
+            write_header(
+                &mut out,
+                b"Content-Length",
+                body.len().to_string().as_bytes(),
+            );
+        }
+        out.extend_from_slice(b"\r\n");
+        out.extend_from_slice(&body);
+        Ok(out)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use http_body_util::{Empty, Full};
 
-    #[test]
-    fn test_request_sync_no_body() {
-        let request = http::Request::builder()
-            .method("GET")
-            .uri("/api/test")
-            .header("Host", "example.com")
-            .body(Empty::<Bytes>::new())
-            .unwrap();
+    /// Returns the ordered list of transfer codings applied to the body.
+    ///
+    /// Codings are collected from every `Transfer-Encoding` header in order, split on
+    /// commas, trimmed and lowercased. Parameters (e.g. `;q=1`) are kept as part of the
+    /// coding. Codings that are not valid UTF-8 are skipped.
+    ///
+    /// Per RFC 9112 §6.1, `chunked` must be the last coding when present; callers doing
+    /// strict validation can check that with this list.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: GZIP\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    /// assert_eq!(request.transfer_codings(), ["gzip", "chunked"]);
+    /// ```
+    pub fn transfer_codings(&self) -> Vec<Cow<'buf, str>> {
+        transfer_codings(self.head.headers)
+            .filter_map(|coding| core::str::from_utf8(coding).ok())
+            .map(|coding| {
+                if coding.bytes().any(|b| b.is_ascii_uppercase()) {
+                    Cow::Owned(coding.to_ascii_lowercase())
+                } else {
+                    Cow::Borrowed(coding)
+                }
+            })
+            .collect()
+    }
 
-        let bytes = request.encode().unwrap();
-        let output = String::from_utf8_lossy(&bytes);
+    /// Determines how the connection should be handled after this request.
+    ///
+    /// The decision follows RFC 9112 §9.3:
+    /// - a `close` connection option always yields [`Disposition::Close`]
+    /// - an HTTP/1.1 request with an `Upgrade` header and an `upgrade` connection
+    ///   option yields [`Disposition::Upgrade`]
+    /// - otherwise HTTP/1.1 defaults to [`Disposition::KeepAlive`], while HTTP/1.0
+    ///   keeps the connection alive only with an explicit `keep-alive` option
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::{Disposition, FullRequest};
+    ///
+    /// let raw = b"GET / HTTP/1.0\r\nHost: example.com\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    /// assert_eq!(request.connection_disposition(), Disposition::Close);
+    /// ```
+    pub fn connection_disposition(&self) -> Disposition {
+        let headers = &*self.head.headers;
+        let has_option = |option: &[u8]| {
+            connection_tokens(headers).any(|token| token.eq_ignore_ascii_case(option))
+        };
+
+        if has_option(b"close") {
+            return Disposition::Close;
+        }
+
+        let http11 = self.head.version == Some(1);
+        if http11 && has_option(b"upgrade") && find_header(headers, "Upgrade").is_some() {
+            return Disposition::Upgrade;
+        }
+
+        if http11 || has_option(b"keep-alive") {
+            Disposition::KeepAlive
+        } else {
+            Disposition::Close
+        }
+    }
+
+    /// Returns the body as a list of slices preserving the original chunk boundaries.
+    ///
+    /// For a chunked body, each element is the data of one chunk, in order, without
+    /// the size line, extensions or trailers. For any other body, the result holds the
+    /// whole body as a single element. An empty body yields no elements.
+    ///
+    /// All slices borrow from the original buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidChunkedBody`] if the chunked framing is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    /// assert_eq!(request.body_chunks().unwrap(), [&b"hello"[..], &b" world"[..]]);
+    /// ```
+    pub fn body_chunks(&self) -> Result<Vec<&'buf [u8]>, WireError> {
+        if has_chunked_encoding(self.head.headers) {
+            split_chunks(self.body, false)
+                .map_err(|offset| WireError::InvalidChunkedBody { offset })
+        } else if self.body.is_empty() {
+            Ok(Vec::new())
+        } else {
+            Ok(vec![self.body])
+        }
+    }
+
+    /// Returns the value of the first header named `name`, matched ASCII
+    /// case-insensitively.
+    ///
+    /// The value is returned as sent, without trimming. Use
+    /// [`headers_all`](Self::headers_all) for headers that may be repeated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\nAccept: text/html\r\naccept: application/json\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// assert_eq!(request.header("HOST"), Some(&b"example.com"[..]));
+    /// let accept: Vec<&[u8]> = request.headers_all("accept").collect();
+    /// assert_eq!(accept, [&b"text/html"[..], b"application/json"]);
+    /// ```
+    pub fn header(&self, name: &str) -> Option<&'buf [u8]> {
+        find_header(self.head.headers, name)
+    }
+
+    /// Returns the values of all headers named `name`, matched ASCII
+    /// case-insensitively, in the order they appear.
+    ///
+    /// This is the way to read headers such as `Set-Cookie`, whose values cannot be
+    /// combined into one.
+    pub fn headers_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'buf [u8]> + 'a {
+        self.head
+            .headers
+            .iter()
+            .filter(move |h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| h.value)
+    }
+
+    /// Returns the headers as an [`http::HeaderMap`].
+    ///
+    /// Only the headers are converted, which is cheaper than [`into_http`](Self::into_http)
+    /// when the typed accessors of `http` are all that is needed. Repeated headers keep
+    /// all their values, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidHttp`] if a header name or value is not accepted by
+    /// `http`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\nAccept: text/html\r\naccept: */*\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// let map = request.header_map().unwrap();
+    /// assert_eq!(map[http::header::HOST], "example.com");
+    /// assert_eq!(map.get_all(http::header::ACCEPT).iter().count(), 2);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn header_map(&self) -> Result<http::HeaderMap, WireError> {
+        header_map(self.head.headers)
+    }
+
+    /// Returns all values of a header combined into one, as allowed by RFC 9110 §5.3.
+    ///
+    /// The header name is matched ASCII case-insensitively. Values are trimmed and
+    /// joined with `, ` in the order they appear; `Cookie` values are joined with `; `
+    /// instead. `Set-Cookie` is never combined, since its values may contain commas:
+    /// only its first value is returned.
+    ///
+    /// A header that appears once is returned borrowed, without allocating.
+    /// Returns `None` if the header is absent.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"GET / HTTP/1.1\r\nAccept: text/html\r\naccept: application/json\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    /// assert_eq!(
+    ///     request.combined_header("Accept").unwrap().as_ref(),
+    ///     b"text/html, application/json"
+    /// );
+    /// ```
+    pub fn combined_header(&self, name: &str) -> Option<Cow<'buf, [u8]>> {
+        combine_header_values(self.head.headers, name)
+    }
+
+    /// Returns the host the request is addressed to, without port or userinfo.
+    ///
+    /// Per RFC 9112 §3.2.2, an absolute-form request target (`GET http://host/path`)
+    /// takes precedence and any `Host` header is ignored. Only a target that starts
+    /// with a scheme is in absolute form, so a URI in the query of an origin-form
+    /// target (`/r?u=http://host`) is not used. Otherwise the host comes from the
+    /// `Host` header. IPv6 literals keep their brackets (`[::1]`).
+    ///
+    /// Returns `None` if there is no usable host: the `Host` header is missing,
+    /// repeated, or not a valid authority.
+    pub fn effective_host(&self) -> Option<&'buf str> {
+        if let Some(authority) = absolute_form_authority(self.head.path?) {
+            return host_of_authority(authority);
+        }
+
+        let mut hosts = self
+            .head
+            .headers
+            .iter()
+            .filter(|h| h.name.eq_ignore_ascii_case("Host"));
+        let host = hosts.next()?;
+        if hosts.next().is_some() {
+            return None;
+        }
+        host_of_authority(core::str::from_utf8(host.value).ok()?.trim())
+    }
+
+    /// Checks the effective host of the request against an allowlist.
+    ///
+    /// The host is resolved with [`effective_host`](Self::effective_host) and compared
+    /// ASCII case-insensitively against each allowed name. Allowed names must not
+    /// include a port.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::HostNotAllowed`] if the host is missing, ambiguous, or not
+    /// in `allowed`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"GET / HTTP/1.1\r\nHost: API.example.com:8443\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// assert!(request.validate_host(&["api.example.com"]).is_ok());
+    /// assert!(request.validate_host(&["www.example.com"]).is_err());
+    /// ```
+    pub fn validate_host(&self, allowed: &[&str]) -> Result<(), WireError> {
+        let host = self.effective_host().ok_or(WireError::HostNotAllowed)?;
+        if allowed.iter().any(|a| a.eq_ignore_ascii_case(host)) {
+            Ok(())
+        } else {
+            Err(WireError::HostNotAllowed)
+        }
+    }
+
+    /// Returns the parsed `Cache-Control` directives of the request.
+    ///
+    /// Request directives such as `max-stale` and `only-if-cached` are included.
+    /// Multiple `Cache-Control` lines are combined. If the header is absent, no
+    /// directives are set. See [`CacheControl`] for the parsing rules.
+    pub fn cache_control(&self) -> CacheControl {
+        CacheControl::from_headers(self.head.headers)
+    }
+
+    /// Returns the method, path, minor version, headers and body in one tuple.
+    ///
+    /// This avoids unwrapping each `Option` field of [`head`](Self::head) separately.
+    /// The version is the minor version number (`1` for HTTP/1.1).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::PartialHead`] if the request line has not been parsed,
+    /// which cannot happen for a request returned by [`decode`](WireDecode::decode).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"POST /api HTTP/1.1\r\nContent-Length: 2\r\n\r\nhi";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// let (method, path, version, headers, body) = request.as_parts().unwrap();
+    /// assert_eq!((method, path, version), ("POST", "/api", 1));
+    /// assert_eq!(headers.len(), 1);
+    /// assert_eq!(body, b"hi");
+    /// ```
+    pub fn as_parts(&self) -> Result<RequestParts<'_, 'buf>, WireError> {
+        match (self.head.method, self.head.path, self.head.version) {
+            (Some(method), Some(path), Some(version)) => {
+                Ok((method, path, version, self.head.headers, self.body))
+            }
+            _ => Err(WireError::PartialHead),
+        }
+    }
+
+    /// Re-serializes the request with a different request-target.
+    ///
+    /// The method, version, headers and body are copied unchanged: header names and
+    /// values byte-for-byte in their original order, and the body with its original
+    /// framing, so a chunked body stays chunked. Only the request line is rebuilt.
+    /// This is the usual URL-rewriting step of a proxy, without a round-trip through
+    /// the `http` types.
+    ///
+    /// `new_target` is written verbatim.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidTarget`] if `new_target` is empty or contains a
+    /// space or a control character such as CR or LF.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"POST /old?x=1 HTTP/1.1\r\nHost: example.com\r\nContent-Length: 2\r\n\r\nhi";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// assert_eq!(
+    ///     request.with_target("/new?x=1").unwrap(),
+    ///     b"POST /new?x=1 HTTP/1.1\r\nHost: example.com\r\nContent-Length: 2\r\n\r\nhi"
+    /// );
+    /// assert!(request.with_target("/a HTTP/1.1\r\nX-Injected: 1\r\n").is_err());
+    /// ```
+    pub fn with_target(&self, new_target: &str) -> Result<Vec<u8>, WireError> {
+        if new_target.is_empty()
+            || new_target
+                .bytes()
+                .any(|b| b == b' ' || b.is_ascii_control())
+        {
+            return Err(WireError::InvalidTarget);
+        }
+        Ok(self.write_with_target(new_target))
+    }
+
+    /// Serializes the request with `target` in the request line, unchecked.
+    fn write_with_target(&self, target: &str) -> Vec<u8> {
+        let headers = &*self.head.headers;
+        let mut out =
+            Vec::with_capacity(target.len() + parsed_headers_len(headers) + self.body.len() + 32);
+        write_request_line(
+            &mut out,
+            self.head.method.unwrap_or_default(),
+            target,
+            self.head.version.unwrap_or(1),
+        );
+        write_parsed_headers(&mut out, headers);
+        out.extend_from_slice(self.body);
+        out
+    }
+
+    /// Re-serializes the request as it was decoded.
+    ///
+    /// Works like [`with_target`](Self::with_target) with the original request-target:
+    /// headers keep their order and casing, and the body its framing. Whitespace around
+    /// header values and bare LF line endings are normalized, since they are not kept
+    /// by the parser. Header values can be replaced in `head.headers` before calling
+    /// this to replay a modified capture.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"GET /a HTTP/1.1\r\nHOST: example.com\r\nx-trace: 1\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (mut request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    /// assert_eq!(request.to_wire(), raw);
+    ///
+    /// request.head.headers[1].value = b"2";
+    /// assert_eq!(
+    ///     request.to_wire(),
+    ///     b"GET /a HTTP/1.1\r\nHOST: example.com\r\nx-trace: 2\r\n\r\n"
+    /// );
+    /// ```
+    pub fn to_wire(&self) -> Vec<u8> {
+        // The decoded target needs no checks
+        self.write_with_target(self.head.path.unwrap_or_default())
+    }
+
+    /// Returns `true` if the request carries `Upgrade-Insecure-Requests: 1`.
+    ///
+    /// Browsers send this to signal that they prefer a secure (HTTPS) response.
+    pub fn upgrade_insecure_requests(&self) -> bool {
+        find_header(self.head.headers, "Upgrade-Insecure-Requests")
+            .is_some_and(|value| value.trim_ascii() == b"1")
+    }
+
+    /// Re-serializes the request with its body passed through `transform`.
+    ///
+    /// `transform` receives the body with any chunked framing removed and returns the
+    /// new body. The request line and headers are copied unchanged, except that
+    /// `Content-Length` and `Transfer-Encoding` are dropped and new framing headers
+    /// are appended:
+    ///
+    /// - if `Transfer-Encoding` lists codings other than `chunked`, such as `gzip`,
+    ///   they still apply to the body, so they are kept in a single
+    ///   `Transfer-Encoding` header that ends in `chunked` and the new body is sent as
+    ///   one chunk
+    /// - otherwise a `Content-Length` matching the new body is appended. It is omitted
+    ///   only when the original request had no framing headers and the new body is
+    ///   empty.
+    ///
+    /// This is the body-rewriting step of a proxy, e.g. injecting content into a
+    /// payload, without having to keep the length in sync by hand.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidChunkedBody`] if a chunked body is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::request::FullRequest;
+    ///
+    /// let raw = b"POST /echo HTTP/1.1\r\nHost: example.com\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nhi\r\n0\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+    ///
+    /// let rewritten = request
+    ///     .with_body_transform(|body| body.to_ascii_uppercase())
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     rewritten,
+    ///     b"POST /echo HTTP/1.1\r\nHost: example.com\r\nContent-Length: 2\r\n\r\nHI"
+    /// );
+    /// ```
+    pub fn with_body_transform<F>(&self, mut transform: F) -> Result<Vec<u8>, WireError>
+    where
+        F: FnMut(&[u8]) -> Vec<u8>,
+    {
+        let headers = &*self.head.headers;
+        let is_framing = |name: &str| {
+            name.eq_ignore_ascii_case("Content-Length")
+                || name.eq_ignore_ascii_case("Transfer-Encoding")
+        };
+        let framed = headers.iter().any(|h| is_framing(h.name));
+        let codings: Vec<&[u8]> = transfer_codings(headers)
+            .filter(|coding| !coding.eq_ignore_ascii_case(b"chunked"))
+            .collect();
+
+        let body = unframed_body(headers, self.body, false)?;
+        let body = transform(&body);
+
+        let mut out = Vec::with_capacity(body.len() + 256);
+        write_request_line(
+            &mut out,
+            self.head.method.unwrap_or_default(),
+            self.head.path.unwrap_or_default(),
+            self.head.version.unwrap_or(1),
+        );
+        for header in headers.iter().filter(|h| !is_framing(h.name)) {
+            write_header(&mut out, header.name.as_bytes(), header.value);
+        }
+        if !codings.is_empty() {
+            // A request body with transfer codings is framed by chunked, last (RFC 9112
+            // §6.1)
+            let mut value = codings.join(&b", "[..]);
+            value.extend_from_slice(b", chunked");
+            write_header(&mut out, b"Transfer-Encoding", &value);
+            out.extend_from_slice(b"\r\n");
+            if !body.is_empty() {
+                out.extend_from_slice(alloc::format!("{:x}\r\n", body.len()).as_bytes());
+                out.extend_from_slice(&body);
+                out.extend_from_slice(b"\r\n");
+            }
+            out.extend_from_slice(b"0\r\n\r\n");
+            return Ok(out);
+        }
+        if framed || !body.is_empty() {
+            write_header(
+                &mut out,
+                b"Content-Length",
+                body.len().to_string().as_bytes(),
+            );
+        }
+        out.extend_from_slice(b"\r\n");
+        out.extend_from_slice(&body);
+        Ok(out)
+    }
+}
+
+/// Checks the method at the start of `buf` against `allowed`, once it is complete.
+///
+/// Like httparse, skips empty lines before the request line.
+fn check_method(buf: &[u8], allowed: &[&str]) -> Result<(), WireError> {
+    let start = buf
+        .iter()
+        .position(|&b| b != b'\r' && b != b'\n')
+        .unwrap_or(buf.len());
+    let line = &buf[start..];
+    let len = line
+        .iter()
+        .position(|&b| !is_tchar(b))
+        .unwrap_or(line.len());
+    match line.get(len) {
+        None => Ok(()),
+        Some(b' ') if allowed.iter().any(|m| m.as_bytes() == &line[..len]) => Ok(()),
+        Some(_) => Err(WireError::InvalidMethod),
+    }
+}
+
+/// Returns the authority of an absolute-form request target (`scheme://authority...`),
+/// or `None` for any other form.
+fn absolute_form_authority(target: &str) -> Option<&str> {
+    let (scheme, rest) = target.split_once("://")?;
+    // scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." ) (RFC 3986 §3.1)
+    let mut scheme = scheme.bytes();
+    let valid = scheme.next()?.is_ascii_alphabetic()
+        && scheme.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'));
+    valid.then(|| rest.split(['/', '?', '#']).next().unwrap_or_default())
+}
+
+/// Extracts the host from an authority (`[userinfo@]host[:port]`).
+fn host_of_authority(authority: &str) -> Option<&str> {
+    let authority = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if authority.starts_with('[') {
+        // IPv6 literal: keep the brackets, drop the port
+        &authority[..=authority.find(']')?]
+    } else {
+        authority
+            .split_once(':')
+            .map_or(authority, |(host, _)| host)
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+/// Iterator over the requests pipelined in a buffer.
+///
+/// Returned by [`FullRequest::decode_all`].
+#[derive(Debug)]
+pub struct DecodeAll {
+    /// Buffer holding the requests.
+    buf: Bytes,
+    /// Total length of the requests yielded so far.
+    consumed: usize,
+    /// Set once an incomplete request or an error has been reached.
+    done: bool,
+}
+
+impl DecodeAll {
+    /// Returns the total length of the requests yielded so far.
+    ///
+    /// Once the iterator has ended without an error, `buf[consumed()..]` is the start of
+    /// an incomplete request, or empty.
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+}
+
+impl Iterator for DecodeAll {
+    type Item = Result<(OwnedRequest, usize), WireError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.consumed == self.buf.len() {
+            return None;
+        }
+        let input = self.buf.slice(self.consumed..);
+        let input_len = input.len();
+
+        match FullRequest::decode_owned(input) {
+            Ok((request, len)) => {
+                self.consumed += len;
+                Some(Ok((request, len)))
+            }
+            Err(err) => {
+                self.done = true;
+                match flag_incomplete_chunked(err, input_len) {
+                    WireError::PartialHead
+                    | WireError::IncompleteBody(_)
+                    | WireError::IncompleteChunkedBody => None,
+                    err => Some(Err(err)),
+                }
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for DecodeAll {}
+
+impl<'headers, 'buf> WireDecode<'headers, 'buf> for FullRequest<'headers, 'buf> {
+    fn decode(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+    ) -> Result<(Self, usize), WireError> {
+        let mut full_request = FullRequest {
+            head: httparse::Request::new(headers),
+            body: &[],
+            content_length: None,
+        };
+
+        let total = full_request.parse(buf)?;
+        Ok((full_request, total))
+    }
+
+    fn decode_uninit(
+        buf: &'buf [u8],
+        headers: &'headers mut [MaybeUninit<Header<'buf>>],
+    ) -> Result<(Self, usize), WireError> {
+        let mut full_request = FullRequest {
+            head: httparse::Request::new(&mut []),
+            body: &[],
+            content_length: None,
+        };
+
+        let total = full_request.parse_uninit(buf, headers)?;
+        Ok((full_request, total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "encode")]
+    use http_body_util::{Empty, Full};
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_request_sync_no_body() {
+        let request = http::Request::builder()
+            .method("GET")
+            .uri("/api/test")
+            .header("Host", "example.com")
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+
+        let bytes = request.encode().unwrap();
+        let output = String::from_utf8_lossy(&bytes);
 
         assert!(output.contains("GET /api/test HTTP/1.1"));
         assert!(output.contains("host: example.com"));
     }
 
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_request_sync_inside_runtime() {
+        // Empty and full bodies are encoded without the shared runtime
+        let request = http::Request::builder()
+            .uri("/a")
+            .header("Host", "example.com")
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+        assert_eq!(
+            request.encode().unwrap(),
+            "GET /a HTTP/1.1\r\nhost: example.com\r\n\r\n"
+        );
+
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/a")
+            .body(Full::new(Bytes::from("hi")))
+            .unwrap();
+        let mut buf = BytesMut::new();
+        assert_eq!(request.encode_into(&mut buf).unwrap(), buf.len());
+        assert!(buf.ends_with(b"content-length: 2\r\n\r\nhi"));
+    }
+
+    #[cfg(feature = "encode")]
     #[test]
     fn test_request_sync_with_body() {
         let body = r#"{"test":"data"}"#;
@@ -325,6 +1669,7 @@ mod tests {
         assert!(output.contains(body));
     }
 
+    #[cfg(feature = "encode")]
     #[test]
     fn test_request_sync_http2_rejected() {
         let request = http::Request::builder()
@@ -338,6 +1683,39 @@ mod tests {
         assert!(matches!(result, Err(WireError::UnsupportedVersion)));
     }
 
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_request_sync_http10() {
+        let request = http::Request::builder()
+            .method("GET")
+            .uri("/path")
+            .version(http::Version::HTTP_10)
+            .header("Host", "example.com")
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+
+        let bytes = request.encode().unwrap();
+        assert_eq!(bytes, "GET /path HTTP/1.0\r\nhost: example.com\r\n\r\n");
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_request_sync_preserves_empty_query() {
+        let request = http::Request::builder()
+            .method("GET")
+            .uri("/path?")
+            .header("Host", "example.com")
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+
+        let bytes = request.encode().unwrap();
+        let output = String::from_utf8_lossy(&bytes);
+
+        // "/path?" and "/path" are distinct request targets
+        assert!(output.starts_with("GET /path? HTTP/1.1\r\n"));
+    }
+
+    #[cfg(feature = "encode")]
     #[tokio::test]
     async fn test_request_to_wire() {
         let request = http::Request::builder()
@@ -354,6 +1732,7 @@ mod tests {
         assert!(output.contains("host: example.com"));
     }
 
+    #[cfg(feature = "encode")]
     #[tokio::test]
     async fn test_request_with_body_to_wire() {
         let body = r#"{"test":"data"}"#;
@@ -372,29 +1751,103 @@ mod tests {
         assert!(output.contains(body));
     }
 
+    #[cfg(feature = "encode")]
     #[tokio::test]
-    async fn test_http2_request_rejected() {
+    async fn test_encode_to_matches_encode_async() {
+        let requests = || {
+            [
+                http::Request::builder()
+                    .method("POST")
+                    .uri("/api/submit")
+                    .header("Host", "example.com")
+                    .body(Full::new(Bytes::from("data")))
+                    .unwrap(),
+                http::Request::builder()
+                    .method("CONNECT")
+                    .uri("example.com:443")
+                    .body(Full::new(Bytes::from("ignored")))
+                    .unwrap(),
+                http::Request::builder()
+                    .method("PUT")
+                    .uri("/v")
+                    .version(http::Version::HTTP_10)
+                    .header("Content-Length", "2")
+                    .body(Full::new(Bytes::from("abc")))
+                    .unwrap(),
+            ]
+        };
+
+        for (request, expected) in requests().into_iter().zip(requests()) {
+            let mut out = Vec::new();
+            let written = request.encode_to(&mut out).await.unwrap();
+            assert_eq!(written, out.len());
+            assert_eq!(out, expected.encode_async().await.unwrap());
+        }
+
         let request = http::Request::builder()
-            .method("GET")
-            .uri("/")
             .version(http::Version::HTTP_2)
             .body(Empty::<Bytes>::new())
             .unwrap();
-
-        let result = request.encode_async().await;
+        let result = request.encode_to(&mut Vec::new()).await;
         assert!(matches!(result, Err(WireError::UnsupportedVersion)));
     }
 
-    #[test]
-    fn test_decode_request_no_body() {
-        let raw = b"GET /api/users HTTP/1.1\r\nHost: example.com\r\n\r\n";
-        let mut headers = [httparse::EMPTY_HEADER; 16];
-        let result = FullRequest::decode(raw, &mut headers);
-        assert!(result.is_ok());
-    }
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_encode_to_streams_body_frames() {
+        // Each frame reaches the writer before the next one is produced
+        let (tx, rx) = futures::channel::mpsc::unbounded::<Result<_, std::io::Error>>();
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/upload")
+            .body(http_body_util::StreamBody::new(rx))
+            .unwrap();
+        let (mut writer, mut reader) = tokio::io::duplex(1024);
+        let encode = tokio::spawn(async move { request.encode_to(&mut writer).await });
 
-    #[test]
-    fn test_decode_request_with_content_length() {
+        let mut received = [0; 64];
+        tx.unbounded_send(Ok(hyper::body::Frame::data(Bytes::from("hello"))))
+            .unwrap();
+        let mut len = 0;
+        while !received[..len].ends_with(b"5\r\nhello\r\n") {
+            len += tokio::io::AsyncReadExt::read(&mut reader, &mut received[len..])
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            &received[..len],
+            b"POST /upload HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n5\r\nhello\r\n"
+        );
+
+        drop(tx);
+        let written = encode.await.unwrap().unwrap();
+        assert_eq!(written, len + 5);
+    }
+
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_http2_request_rejected() {
+        let request = http::Request::builder()
+            .method("GET")
+            .uri("/")
+            .version(http::Version::HTTP_2)
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+
+        let result = request.encode_async().await;
+        assert!(matches!(result, Err(WireError::UnsupportedVersion)));
+    }
+
+    #[test]
+    fn test_decode_request_no_body() {
+        let raw = b"GET /api/users HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode(raw, &mut headers);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_decode_request_with_content_length() {
         let raw = b"POST /api/users HTTP/1.1\r\nHost: example.com\r\nContent-Length: 14\r\n\r\n{\"name\":\"foo\"}";
         let mut headers = [httparse::EMPTY_HEADER; 16];
         let result = FullRequest::decode(raw, &mut headers);
@@ -408,7 +1861,485 @@ mod tests {
             b"POST /api/users HTTP/1.1\r\nHost: example.com\r\nContent-Length: 13\r\n\r\nhello";
         let mut headers = [httparse::EMPTY_HEADER; 16];
         let result = FullRequest::decode(raw, &mut headers);
-        assert!(matches!(result, Err(WireError::IncompleteBody(_))));
+        assert_eq!(result.err(), Some(WireError::IncompleteBody(8)));
+    }
+
+    #[test]
+    fn test_decode_request_content_length_strict() {
+        for value in ["123 ", "123abc", "+123", "-1", " 123", "1 2", "5, 5", ""] {
+            assert_eq!(
+                parse_content_length(value.as_bytes()),
+                Err(WireError::InvalidContentLength),
+                "{value:?}"
+            );
+        }
+        assert_eq!(parse_content_length(b"0123"), Ok(123));
+
+        for value in ["123abc", "+5", "0x5", "abc"] {
+            let raw = format!("POST / HTTP/1.1\r\nContent-Length: {value}\r\n\r\nhello");
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let result = FullRequest::decode(raw.as_bytes(), &mut headers);
+            assert_eq!(result.err(), Some(WireError::InvalidContentLength));
+        }
+
+        // httparse strips the whitespace around a field value
+        let raw = b"POST / HTTP/1.1\r\nContent-Length:  5 \r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[test]
+    fn test_decode_request_content_length_overflow() {
+        for length in ["99999999999999999999999999", "18446744073709551615"] {
+            let raw = format!("POST / HTTP/1.1\r\nContent-Length: {length}\r\n\r\nhello");
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let result = FullRequest::decode(raw.as_bytes(), &mut headers);
+            assert_eq!(result.err(), Some(WireError::InvalidContentLength));
+        }
+
+        // Long numbers that fit are still parsed
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 00000000000000000000000005\r\n\r\nhello";
+        let (request, len) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.body, b"hello");
+        assert_eq!(len, raw.len());
+    }
+
+    #[test]
+    fn test_decode_request_conflicting_framing() {
+        for raw in [
+            &b"POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n"[..],
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\ncontent-length: 0\r\n\r\n0\r\n\r\n",
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\nContent-Length: 3\r\n\r\nabc",
+            b"POST / HTTP/1.1\r\nContent-Length: 3\r\nTransfer-Encoding: chunked, gzip\r\n\r\nabc",
+        ] {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let result = FullRequest::decode(raw, &mut headers);
+            assert_eq!(result.err(), Some(WireError::ConflictingFraming));
+        }
+
+        // Either header alone frames the body
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n";
+        assert_eq!(FullRequest::decode(raw, &mut headers).unwrap().1, raw.len());
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc";
+        assert_eq!(FullRequest::decode(raw, &mut headers).unwrap().1, raw.len());
+    }
+
+    #[test]
+    fn test_decode_request_duplicate_content_length() {
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\nhello!";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode(raw, &mut headers);
+        assert_eq!(result.err(), Some(WireError::DuplicateContentLength));
+
+        // Repeating the same value is allowed
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 5\r\nX: y\r\ncontent-length: 5\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, len) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.body, b"hello");
+        assert_eq!(len, raw.len());
+    }
+
+    #[test]
+    fn test_decode_request_head_limit() {
+        let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (_, len) = FullRequest::decode_with_limits(raw, &mut headers, raw.len()).unwrap();
+        assert_eq!(len, raw.len());
+
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode_with_limits(raw, &mut headers, raw.len() - 1);
+        assert_eq!(result.err(), Some(WireError::HeadTooLarge));
+
+        // An incomplete head is only rejected once it passes the limit
+        let partial = &raw[..20];
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode_with_limits(partial, &mut headers, 20);
+        assert_eq!(result.err(), Some(WireError::PartialHead));
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode_with_limits(partial, &mut headers, 19);
+        assert_eq!(result.err(), Some(WireError::HeadTooLarge));
+
+        // The body does not count towards the limit
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n0123456789";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert!(FullRequest::decode_with_limits(raw, &mut headers, 40).is_ok());
+    }
+
+    #[test]
+    fn test_decode_limited() {
+        let limits = Limits {
+            max_headers: 2,
+            max_head_bytes: 256,
+            max_body_bytes: 16,
+            max_chunk_count: 2,
+        };
+        let decode = |raw: &[u8]| {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            FullRequest::decode_limited(raw, &mut headers, &limits).map(|(_, len)| len)
+        };
+
+        assert_eq!(decode(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n"), Ok(30));
+        assert_eq!(
+            decode(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\nC: 3\r\n\r\n"),
+            Err(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        );
+
+        // A large Content-Length is rejected before the body arrives
+        let body = b"0123456789abcdef";
+        let raw = [&b"POST / HTTP/1.1\r\nContent-Length: 16\r\n\r\n"[..], body].concat();
+        assert_eq!(decode(&raw), Ok(55));
+        assert_eq!(
+            decode(b"POST / HTTP/1.1\r\nContent-Length: 17\r\n\r\n"),
+            Err(WireError::BodyTooLarge)
+        );
+
+        // A chunked body counts with its framing, complete or not
+        let chunked = |body: &[u8]| {
+            decode(
+                &[
+                    &b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"[..],
+                    body,
+                ]
+                .concat(),
+            )
+        };
+        assert_eq!(chunked(b"5\r\nabcde\r\n0\r\n\r\n"), Ok(62));
+        assert_eq!(
+            chunked(b"7\r\nabcdefg\r\n0\r\n\r\n"),
+            Err(WireError::BodyTooLarge)
+        );
+        assert_eq!(
+            chunked(b"100\r\nabcdefghijklmnop"),
+            Err(WireError::BodyTooLarge)
+        );
+        assert_eq!(
+            chunked(b"1\r\na"),
+            Err(WireError::InvalidChunkedBody { offset: 51 })
+        );
+        assert_eq!(
+            chunked(b"1\r\na\r\n1\r\nb\r\n1\r\nc\r\n"),
+            Err(WireError::TooManyChunks { offset: 59 })
+        );
+    }
+
+    #[test]
+    fn test_decode_vec_grows_headers() {
+        let raw = |count: usize| {
+            let mut raw = b"GET / HTTP/1.1\r\n".to_vec();
+            for i in 0..count {
+                raw.extend_from_slice(format!("X-{i}: {i}\r\n").as_bytes());
+            }
+            raw.extend_from_slice(b"\r\n");
+            raw
+        };
+
+        let small = raw(3);
+        let (request, len) = FullRequest::decode_vec(Bytes::from(small.clone())).unwrap();
+        assert_eq!(request.headers.len(), 3);
+        assert_eq!(len, small.len());
+
+        let mut large = raw(1024);
+        let len = large.len();
+        // Lines in the body do not count
+        large.extend_from_slice(&b"\n".repeat(10_000));
+        let (request, total) = FullRequest::decode_vec(Bytes::from(large)).unwrap();
+        assert_eq!(request.headers[1023].value, "1023");
+        assert_eq!(total, len);
+
+        let too_many = raw(1025);
+        let result = FullRequest::decode_vec(Bytes::from(too_many));
+        assert_eq!(
+            result.err(),
+            Some(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        );
+    }
+
+    #[test]
+    fn test_header_lookup() {
+        let raw = b"GET / HTTP/1.1\r\nX-Tag: a\r\nHost: example.com\r\nx-tag: b\r\nX-TAG: \r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        assert_eq!(request.header("x-tag"), Some(&b"a"[..]));
+        assert_eq!(request.header("Missing"), None);
+        let tags: Vec<&[u8]> = request.headers_all("X-Tag").collect();
+        assert_eq!(tags, [&b"a"[..], b"b", b""]);
+        assert_eq!(request.headers_all("Missing").count(), 0);
+    }
+
+    #[test]
+    fn test_close_delimited_ignored_for_requests() {
+        let options = DecodeOptions::new().assume_close_delimited(true);
+        let raw = b"GET / HTTP/1.1\r\nHost: x\r\n\r\nGET /next HTTP/1.1\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, len) = FullRequest::decode_with_options(raw, &mut headers, &options).unwrap();
+        assert!(request.body.is_empty());
+        assert_eq!(len, 27);
+    }
+
+    #[test]
+    fn test_decode_request_content_length_field() {
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.content_length, Some(5));
+
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n";
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.content_length, None);
+
+        let mut headers = [const { MaybeUninit::uninit() }; 16];
+        let raw = b"GET / HTTP/1.1\r\nHost: x\r\n\r\n";
+        let (request, _) = FullRequest::decode_uninit(raw, &mut headers).unwrap();
+        assert_eq!(request.content_length, None);
+    }
+
+    #[test]
+    fn test_decode_request_bare_lf_chunks() {
+        let lenient = DecodeOptions::new().allow_bare_lf(true);
+        let raw =
+            b"POST / HTTP/1.1\nTransfer-Encoding: chunked\n\n3\nabc\n2\r\nde\r\n0\nX-Sum: 1\n\nGET";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, len) = FullRequest::decode_with_options(raw, &mut headers, &lenient).unwrap();
+        assert_eq!(len, raw.len() - 3);
+        assert_eq!(request.body, b"3\nabc\n2\r\nde\r\n0\nX-Sum: 1\n\n");
+
+        // Rejected by default
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode(raw, &mut headers);
+        assert!(matches!(result, Err(WireError::InvalidChunkedBody { .. })));
+
+        // Incomplete trailers are still incomplete
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\nX-Sum: 1\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode_with_options(raw, &mut headers, &lenient);
+        assert_eq!(
+            result.err(),
+            Some(WireError::InvalidChunkedBody { offset: raw.len() })
+        );
+    }
+
+    #[test]
+    fn test_decode_request_transfer_coding_list() {
+        let body = "3\r\nabc\r\n0\r\n\r\n";
+        for (coding, chunked) in [
+            ("gzip, chunked", true),
+            ("gzip,chunked", true),
+            ("  gzip ,\tChunked  ", true),
+            ("gzip, chunked,", true),
+            ("chunked, gzip", false),
+            ("chunkedx", false),
+            ("gzip", false),
+            ("", false),
+        ] {
+            let raw = format!("POST / HTTP/1.1\r\nTransfer-Encoding: {coding}\r\n\r\n{body}");
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let result = FullRequest::decode(raw.as_bytes(), &mut headers);
+            if chunked {
+                assert_eq!(result.unwrap().0.body, body.as_bytes(), "{coding}");
+            } else {
+                // Without chunked last, the length of a request body is unknown
+                assert_eq!(
+                    result.err(),
+                    Some(WireError::UnsupportedTransferCoding),
+                    "{coding}"
+                );
+            }
+        }
+
+        // Only the last header can end with the final coding
+        let raw = format!(
+            "POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n{body}"
+        );
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw.as_bytes(), &mut headers).unwrap();
+        assert_eq!(request.body, body.as_bytes());
+        assert_eq!(request.body_chunks().unwrap(), [b"abc"]);
+    }
+
+    #[test]
+    fn test_decode_all_pipelined() {
+        let raw = Bytes::from_static(b"GET /a HTTP/1.1\r\nHost: x\r\nAccept: */*\r\n\r\nPOST /b HTTP/1.1\r\nHost: x\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\nPOST /c HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nab");
+        let mut requests = FullRequest::decode_all(raw.clone());
+
+        let (first, first_len) = requests.next().unwrap().unwrap();
+        assert_eq!(first.path, "/a");
+        assert_eq!(first.headers.len(), 2);
+        let (second, second_len) = requests.next().unwrap().unwrap();
+        assert_eq!(second.path, "/b");
+        assert_eq!(second.body, &b"3\r\nabc\r\n0\r\n\r\n"[..]);
+
+        // Earlier requests stay usable
+        assert_eq!(first.header("accept").unwrap(), "*/*");
+
+        // The chunked body of the third request is cut short
+        assert!(requests.next().is_none());
+        assert_eq!(requests.consumed(), first_len + second_len);
+        assert!(raw[requests.consumed()..].starts_with(b"POST /c"));
+        assert!(requests.next().is_none());
+    }
+
+    #[test]
+    fn test_decode_all_errors() {
+        let mut requests = FullRequest::decode_all(Bytes::from_static(
+            b"GET /a HTTP/1.1\r\n\r\nGET /b HTTP/1.1\r\nContent-Length: 99999999999999999999999\r\n\r\nGET /c HTTP/1.1\r\n\r\n",
+        ));
+        assert!(requests.next().unwrap().is_ok());
+        assert_eq!(
+            requests.next().unwrap().err(),
+            Some(WireError::InvalidContentLength)
+        );
+        assert!(requests.next().is_none());
+
+        // Each request has its own header slots, however many came before it
+        let mut raw = b"GET / HTTP/1.1\r\nHost: x\r\n\r\n".repeat(100);
+        raw.extend_from_slice(b"GET / HTTP/1.1\r\n");
+        raw.extend_from_slice(&b"A: 1\r\n".repeat(65));
+        raw.extend_from_slice(b"\r\n");
+        let results: Vec<_> = FullRequest::decode_all(Bytes::from(raw))
+            .map(|item| item.map(|(_, len)| len))
+            .collect();
+        assert_eq!(results.len(), 101);
+        assert!(results[..100].iter().all(|len| *len == Ok(27)));
+        assert_eq!(
+            results[100],
+            Err(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        );
+    }
+
+    #[test]
+    fn test_decode_request_malformed_offset() {
+        let cases: [(&[u8], usize, &str); 3] = [
+            (
+                b"GET / HTTP/1.1\r\nHost: x\r\nBad Header: 1\r\n\r\n",
+                28,
+                "invalid header name",
+            ),
+            (
+                b"GET / HTTP/1.1\r\nX: a\x01b\r\n\r\n",
+                20,
+                "invalid header value",
+            ),
+            (b"GET / HTTX/1.1\r\n\r\n", 9, "invalid HTTP version"),
+        ];
+        for (raw, offset, reason) in cases {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            assert_eq!(
+                FullRequest::decode(raw, &mut headers).err(),
+                Some(WireError::Malformed { offset, reason })
+            );
+        }
+
+        // The search stays within the head, whatever follows it
+        let mut raw = b"GET / HTTP/1.1\r\nBad Header: 1\r\n\r\n".to_vec();
+        raw.extend_from_slice(&[b'\n'; 100_000]);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert_eq!(
+            FullRequest::decode(&raw, &mut headers).err(),
+            Some(WireError::Malformed {
+                offset: 19,
+                reason: "invalid header name"
+            })
+        );
+
+        // Running out of header slots is not a syntax error
+        let mut headers = [httparse::EMPTY_HEADER; 1];
+        assert_eq!(
+            FullRequest::decode(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n", &mut headers).err(),
+            Some(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        );
+    }
+
+    #[test]
+    fn test_to_wire_round_trip() {
+        let raw = b"PUT /a?b HTTP/1.0\r\nhost: x\r\nTransfer-Encoding: chunked\r\n\r\n2;ext=1\r\nhi\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.to_wire(), raw);
+    }
+
+    #[test]
+    fn test_decode_strict_methods() {
+        let strict = |raw: &[u8]| {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            FullRequest::decode_strict(raw, &mut headers).map(|(_, len)| len)
+        };
+        assert_eq!(strict(b"GET / HTTP/1.1\r\n\r\n"), Ok(18));
+        assert_eq!(strict(b"\r\nPATCH / HTTP/1.1\r\n\r\n"), Ok(22));
+        for raw in [
+            &b"GE T / HTTP/1.1\r\n\r\n"[..],
+            b"G\x01ET / HTTP/1.1\r\n\r\n",
+            b"get / HTTP/1.1\r\n\r\n",
+            b"PURGE / HTTP/1.1\r\n\r\n",
+            b" / HTTP/1.1\r\n\r\n",
+        ] {
+            assert_eq!(strict(raw), Err(WireError::InvalidMethod));
+        }
+        // An unfinished method may still become a known one
+        assert_eq!(strict(b"GE"), Err(WireError::PartialHead));
+
+        let options = DecodeOptions::new().allowed_methods(&["PURGE"]);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let raw = b"PURGE /cached HTTP/1.1\r\n\r\n";
+        let (request, _) = FullRequest::decode_with_options(raw, &mut headers, &options).unwrap();
+        assert_eq!(request.head.method, Some("PURGE"));
+    }
+
+    #[test]
+    fn test_decode_owned_shares_buffer() {
+        let buf = Bytes::from_static(
+            b"PUT /items/1 HTTP/1.0\r\nHost: example.com\r\nContent-Length: 4\r\n\r\ndata",
+        );
+        let (request, len) = FullRequest::decode_owned(buf.clone()).unwrap();
+
+        assert_eq!(len, buf.len());
+        assert_eq!(request.method, "PUT");
+        assert_eq!(request.path, "/items/1");
+        assert_eq!(request.version, 0);
+        assert_eq!(request.headers.len(), 2);
+        assert_eq!(request.header("host").unwrap(), "example.com");
+        assert_eq!(request.body, "data");
+
+        // The body is a view into the original allocation
+        let range = buf.as_ptr_range();
+        assert!(range.contains(&request.body.as_ptr()));
+
+        let buf = Bytes::from_static(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhel");
+        let result = FullRequest::decode_owned(buf);
+        assert_eq!(result.err(), Some(WireError::IncompleteBody(2)));
+    }
+
+    #[test]
+    fn test_decode_request_obsolete_folding() {
+        let folded = [
+            (&b"GET / HTTP/1.1\r\nX-Foo:\r\n bar\r\n\r\n"[..], 24),
+            (b"GET / HTTP/1.1\r\nX-Foo: a\r\n\tb\r\nHost: x\r\n\r\n", 26),
+        ];
+        for (raw, offset) in folded {
+            // Rejected either way, with a dedicated error in strict mode
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            assert!(matches!(
+                FullRequest::decode(raw, &mut headers),
+                Err(WireError::Malformed { offset: o, .. }) if o == offset
+            ));
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            assert_eq!(
+                FullRequest::decode_strict(raw, &mut headers).err(),
+                Some(WireError::ObsoleteFolding { offset })
+            );
+        }
+
+        // Other malformed heads keep their error in strict mode
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert!(matches!(
+            FullRequest::decode_strict(b"GET / HTTP/1.1\r\nX Foo: bar\r\n\r\n", &mut headers),
+            Err(WireError::Malformed { .. })
+        ));
     }
 
     #[test]
@@ -416,7 +2347,7 @@ mod tests {
         let raw = b"POST /api/users HTTP/1.1\r\nHost: example.com\r\n";
         let mut headers = [httparse::EMPTY_HEADER; 16];
         let result = FullRequest::decode(raw, &mut headers);
-        assert!(matches!(result, Err(WireError::PartialHead)));
+        assert_eq!(result.err(), Some(WireError::PartialHead));
     }
 
     #[test]
@@ -441,7 +2372,9 @@ mod tests {
         let raw = b"POST /api/data HTTP/1.1\r\nHost: example.com\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n";
         let mut headers = [httparse::EMPTY_HEADER; 16];
         let result = FullRequest::decode(raw, &mut headers);
-        assert!(matches!(result, Err(WireError::InvalidChunkedBody)));
+        assert!(
+            matches!(result, Err(WireError::InvalidChunkedBody { offset }) if offset == raw.len())
+        );
     }
 
     #[test]
@@ -480,4 +2413,762 @@ mod tests {
         let (req, _) = result.unwrap();
         assert_eq!(req.body, b"{\"name\":\"foo\"}");
     }
+
+    #[test]
+    fn test_expects_continue() {
+        let raw = b"PUT /upload HTTP/1.1\r\nHost: example.com\r\nExpect: 100-Continue\r\nContent-Length: 5\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(request.expects_continue());
+    }
+
+    #[test]
+    fn test_expects_continue_absent_or_http10() {
+        let raw = b"PUT /upload HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(!request.expects_continue());
+
+        let raw = b"PUT /upload HTTP/1.0\r\nExpect: 100-continue\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(!request.expects_continue());
+    }
+
+    #[test]
+    fn test_reserialize_for_forwarding_strips_hop_by_hop() {
+        let raw = b"GET /api HTTP/1.1\r\nHost: example.com\r\nConnection: keep-alive, X-Secret\r\nKeep-Alive: timeout=5\r\nX-Secret: 42\r\nProxy-Authorization: Basic abc\r\nTE: trailers\r\nUpgrade: websocket\r\nAccept: */*\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        let forwarded = request.reserialize_for_forwarding().unwrap();
+        assert_eq!(
+            forwarded,
+            b"GET /api HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_reserialize_for_forwarding_dechunks_body() {
+        let raw = b"POST /api HTTP/1.1\r\nHost: example.com\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (mut request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        let forwarded = request.reserialize_for_forwarding().unwrap();
+        assert_eq!(
+            forwarded,
+            b"POST /api HTTP/1.1\r\nHost: example.com\r\nContent-Length: 11\r\n\r\nhello world"
+        );
+
+        // A malformed chunked body is an error, not forwarded as is
+        request.body = b"5\r\nhel";
+        assert!(matches!(
+            request.reserialize_for_forwarding(),
+            Err(WireError::InvalidChunkedBody { .. })
+        ));
+    }
+
+    #[test]
+    fn test_reserialize_for_forwarding_keeps_content_length_body() {
+        let raw = b"PUT /x HTTP/1.0\r\nContent-Length: 5\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        assert_eq!(request.reserialize_for_forwarding().unwrap(), raw);
+    }
+
+    #[test]
+    fn test_transfer_codings_list() {
+        let raw = b"POST / HTTP/1.1\r\nHost: example.com\r\nTransfer-Encoding: gzip , Deflate\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.transfer_codings(), ["gzip", "deflate", "chunked"]);
+    }
+
+    #[test]
+    fn test_transfer_codings_empty() {
+        let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(request.transfer_codings().is_empty());
+    }
+
+    #[test]
+    fn test_connection_disposition() {
+        let cases: [(&[u8], Disposition); 6] = [
+            (b"GET / HTTP/1.1\r\n\r\n", Disposition::KeepAlive),
+            (b"GET / HTTP/1.1\r\nConnection: Close\r\n\r\n", Disposition::Close),
+            (b"GET / HTTP/1.0\r\n\r\n", Disposition::Close),
+            (b"GET / HTTP/1.0\r\nConnection: keep-alive\r\n\r\n", Disposition::KeepAlive),
+            (
+                b"GET /ws HTTP/1.1\r\nConnection: keep-alive, Upgrade\r\nUpgrade: websocket\r\n\r\n",
+                Disposition::Upgrade,
+            ),
+            // Upgrade header without the matching connection option is ignored
+            (b"GET /ws HTTP/1.1\r\nUpgrade: websocket\r\n\r\n", Disposition::KeepAlive),
+        ];
+
+        for (raw, expected) in cases {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+            assert_eq!(request.connection_disposition(), expected);
+        }
+    }
+
+    #[test]
+    fn test_body_chunks_chunked() {
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3;ext=1\r\nabc\r\n1\r\nd\r\n0\r\nX-Trailer: 1\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.body_chunks().unwrap(), [&b"abc"[..], &b"d"[..]]);
+    }
+
+    #[test]
+    fn test_body_chunks_content_length() {
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(request.body_chunks().unwrap(), [&b"hello"[..]]);
+
+        let raw = b"GET / HTTP/1.1\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(request.body_chunks().unwrap().is_empty());
+    }
+
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_encode_request_expect_continue_waits_for_continue() {
+        use tokio::io::AsyncReadExt;
+
+        let request = http::Request::builder()
+            .method("PUT")
+            .uri("/upload")
+            .header("Host", "example.com")
+            .body(Full::new(Bytes::from("hello")))
+            .unwrap();
+
+        let (mut client, mut server) = tokio::io::duplex(4096);
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        let writer = tokio::spawn(async move {
+            encode_request_expect_continue(&mut client, request, async {
+                let _ = rx.await;
+            })
+            .await
+        });
+
+        // Only the head is sent before the continue signal
+        let mut buf = vec![0u8; 4096];
+        let n = server.read(&mut buf).await.unwrap();
+        let head = String::from_utf8_lossy(&buf[..n]).into_owned();
+        assert!(head.starts_with("PUT /upload HTTP/1.1\r\n"));
+        assert!(head.contains("expect: 100-continue\r\n"));
+        assert!(head.ends_with("\r\n\r\n"));
+
+        tx.send(()).unwrap();
+        let written = writer.await.unwrap().unwrap();
+
+        let m = server.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..m], b"hello");
+        assert_eq!(written, n + m);
+    }
+
+    #[test]
+    fn test_decode_request_chunked_trailers_end_of_buffer() {
+        let raw = b"POST /api/data HTTP/1.1\r\nHost: example.com\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\nX-Checksum: abc\r\nX-Other: 1\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (_, len) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(len, raw.len());
+
+        // Dropping the final CRLF leaves the trailer section incomplete
+        let truncated = &raw[..raw.len() - 2];
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode(truncated, &mut headers);
+        assert!(matches!(
+            result,
+            Err(WireError::InvalidChunkedBody { offset }) if offset == truncated.len()
+        ));
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_request_sync_extension_methods() {
+        for method in [
+            "PROPFIND",
+            "PROPPATCH",
+            "MKCOL",
+            "COPY",
+            "MOVE",
+            "LOCK",
+            "FOOBAR",
+        ] {
+            let request = http::Request::builder()
+                .method(http::Method::from_bytes(method.as_bytes()).unwrap())
+                .uri("/path")
+                .header("Host", "example.com")
+                .body(Empty::<Bytes>::new())
+                .unwrap();
+
+            let bytes = request.encode().unwrap();
+            let expected = format!("{method} /path HTTP/1.1\r\n");
+            assert!(bytes.starts_with(expected.as_bytes()), "{method}");
+
+            // The decoder reads the method back unchanged
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let (decoded, _) = FullRequest::decode(&bytes, &mut headers).unwrap();
+            assert_eq!(decoded.head.method, Some(method));
+        }
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_request_sync_extension_method_with_body() {
+        let body = r#"<?xml version="1.0"?><propfind xmlns="DAV:"><allprop/></propfind>"#;
+        let request = http::Request::builder()
+            .method("PROPFIND")
+            .uri("/files/")
+            .header("Host", "example.com")
+            .header("Depth", "1")
+            .body(Full::new(Bytes::from(body)))
+            .unwrap();
+
+        let bytes = request.encode().unwrap();
+        let output = String::from_utf8_lossy(&bytes);
+
+        assert!(output.starts_with("PROPFIND /files/ HTTP/1.1\r\n"));
+        assert!(output.ends_with(body));
+    }
+
+    #[test]
+    fn test_combined_header() {
+        let raw = b"GET / HTTP/1.1\r\nVia: 1.1 a\r\nHost: example.com\r\nVia:  1.1 b \r\nCookie: a=1\r\nCookie: b=2\r\nSet-Cookie: x=1, y\r\nSet-Cookie: z=2\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        assert_eq!(
+            request.combined_header("via").unwrap().as_ref(),
+            b"1.1 a, 1.1 b"
+        );
+        assert_eq!(
+            request.combined_header("Cookie").unwrap().as_ref(),
+            b"a=1; b=2"
+        );
+        assert_eq!(
+            request.combined_header("Set-Cookie").unwrap().as_ref(),
+            b"x=1, y"
+        );
+        assert!(matches!(
+            request.combined_header("Host"),
+            Some(Cow::Borrowed(b"example.com"))
+        ));
+        assert!(request.combined_header("Accept").is_none());
+    }
+
+    #[test]
+    fn test_effective_host() {
+        let cases: [(&[u8], Option<&str>); 9] = [
+            (
+                b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n",
+                Some("example.com"),
+            ),
+            (
+                b"GET / HTTP/1.1\r\nHost: example.com:8080\r\n\r\n",
+                Some("example.com"),
+            ),
+            (b"GET / HTTP/1.1\r\nHost: [::1]:8080\r\n\r\n", Some("[::1]")),
+            // Absolute-form overrides the Host header
+            (
+                b"GET http://user@origin.example:81/p?q HTTP/1.1\r\nHost: other.example\r\n\r\n",
+                Some("origin.example"),
+            ),
+            // A URI in the query of an origin-form target is not the target
+            (
+                b"GET /r?u=http://admin.example HTTP/1.1\r\nHost: public.example\r\n\r\n",
+                Some("public.example"),
+            ),
+            (
+                b"GET /http://admin.example/ HTTP/1.1\r\nHost: public.example\r\n\r\n",
+                Some("public.example"),
+            ),
+            (b"GET / HTTP/1.1\r\n\r\n", None),
+            (
+                b"GET / HTTP/1.1\r\nHost: a.example\r\nHost: b.example\r\n\r\n",
+                None,
+            ),
+            (b"GET / HTTP/1.1\r\nHost: \r\n\r\n", None),
+        ];
+
+        for (raw, expected) in cases {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+            assert_eq!(request.effective_host(), expected);
+        }
+    }
+
+    #[test]
+    fn test_validate_host() {
+        let raw = b"GET http://Admin.Example/ HTTP/1.1\r\nHost: public.example\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        assert!(request.validate_host(&["admin.example"]).is_ok());
+        assert!(matches!(
+            request.validate_host(&["public.example"]),
+            Err(WireError::HostNotAllowed)
+        ));
+
+        let raw = b"GET / HTTP/1.1\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(matches!(
+            request.validate_host(&["public.example"]),
+            Err(WireError::HostNotAllowed)
+        ));
+
+        // The query cannot smuggle in an allowed host
+        let raw = b"GET /r?u=http://admin.example HTTP/1.1\r\nHost: public.example\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(matches!(
+            request.validate_host(&["admin.example"]),
+            Err(WireError::HostNotAllowed)
+        ));
+    }
+
+    #[test]
+    fn test_request_cache_control() {
+        let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\nCache-Control: max-stale=30, only-if-cached\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        let cc = request.cache_control();
+        assert_eq!(cc.max_stale, Some(Some(30)));
+        assert!(cc.only_if_cached);
+        assert!(!cc.no_cache);
+    }
+
+    #[test]
+    fn test_decode_request_chunked_framing_matches_verify() {
+        // Chunk data not followed by CRLF, and a chunk size with a non-hex byte
+        for (body, error) in [
+            (
+                &b"3\r\nabcXY0\r\n\r\n"[..],
+                WireError::InvalidChunkedBody { offset: 53 },
+            ),
+            (
+                b" g3\r\nabc\r\n0\r\n\r\n",
+                WireError::InvalidChunkSize { offset: 47 },
+            ),
+        ] {
+            let raw = [
+                &b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n"[..],
+                body,
+            ]
+            .concat();
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let result = FullRequest::decode(&raw, &mut headers);
+            assert_eq!(result.err(), Some(error));
+            assert!(crate::verify_chunked_body(body).is_err());
+        }
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_decode_request_chunked_error_offset() {
+        // Bare LF ends the second chunk-size line
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n3\nabc\r\n0\r\n\r\n";
+        let head_len = find_head_end(raw).unwrap();
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode(raw, &mut headers);
+        assert!(matches!(
+            result,
+            Err(WireError::InvalidChunkedBody { offset }) if offset == head_len + 11
+        ));
+    }
+
+    #[test]
+    fn test_as_parts() {
+        let raw = b"GET /index.html HTTP/1.0\r\nHost: example.com\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        let (method, path, version, headers, body) = request.as_parts().unwrap();
+        assert_eq!(method, "GET");
+        assert_eq!(path, "/index.html");
+        assert_eq!(version, 0);
+        assert_eq!(headers[0].name, "Host");
+        assert!(body.is_empty());
+
+        // A request that has not been parsed has no parts
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let request = FullRequest {
+            head: httparse::Request::new(&mut headers),
+            body: &[],
+            content_length: None,
+        };
+        assert!(matches!(request.as_parts(), Err(WireError::PartialHead)));
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_vectored_reuses_full_body() {
+        let body = Bytes::from("hello world");
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/upload")
+            .header("Host", "example.com")
+            .body(Full::new(body.clone()))
+            .unwrap();
+        let expected = request.clone().encode().unwrap();
+
+        let parts = request.encode_vectored().unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[1].as_ptr(), body.as_ptr());
+        assert_eq!(parts.concat(), expected);
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_parts() {
+        let request = || {
+            http::Request::builder()
+                .method("POST")
+                .uri("/upload")
+                .header("Host", "example.com")
+                .body(Full::new(Bytes::from("\r\n\r\n")))
+                .unwrap()
+        };
+        let (head, body) = request().encode_parts().unwrap();
+        assert_eq!(
+            &head[..],
+            b"POST /upload HTTP/1.1\r\nhost: example.com\r\ncontent-length: 4\r\n\r\n"
+        );
+        assert_eq!(&body[..], b"\r\n\r\n");
+        assert_eq!([head, body].concat(), request().encode().unwrap());
+
+        let request = http::Request::builder()
+            .uri("/")
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+        let (head, body) = request.encode_parts().unwrap();
+        assert_eq!(&head[..], b"GET / HTTP/1.1\r\n\r\n");
+        assert!(body.is_empty());
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_vectored_streaming_and_empty_bodies() {
+        let stream = futures::stream::iter([
+            Ok::<_, std::convert::Infallible>(hyper::body::Frame::data(Bytes::from("ab"))),
+            Ok(hyper::body::Frame::data(Bytes::from("cd"))),
+        ]);
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/stream")
+            .body(http_body_util::StreamBody::new(stream))
+            .unwrap();
+
+        let parts = request.encode_vectored().unwrap();
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0].ends_with(b"transfer-encoding: chunked\r\n\r\n"));
+        assert_eq!(&parts[1][..], b"2\r\nab\r\n2\r\ncd\r\n0\r\n\r\n");
+
+        let request = http::Request::builder()
+            .uri("/")
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+        let parts = request.encode_vectored().unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(&parts[0][..], b"GET / HTTP/1.1\r\n\r\n");
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_decode_with_options_max_chunks() {
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\n1\r\nb\r\n1\r\nc\r\n0\r\n\r\n";
+        let head_len = find_head_end(raw).unwrap();
+
+        let options = DecodeOptions::new().max_chunks(3);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (_, len) = FullRequest::decode_with_options(raw, &mut headers, &options).unwrap();
+        assert_eq!(len, raw.len());
+
+        let options = DecodeOptions::new().max_chunks(2);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode_with_options(raw, &mut headers, &options);
+        assert!(matches!(
+            result,
+            Err(WireError::TooManyChunks { offset }) if offset == head_len + 12
+        ));
+
+        // An empty chunked body has no data chunks
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n";
+        let options = DecodeOptions::new().max_chunks(0);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert!(FullRequest::decode_with_options(raw, &mut headers, &options).is_ok());
+    }
+
+    #[test]
+    fn test_decode_request_chunk_size_overflow() {
+        // 2^64 + 3 would wrap to 3 and frame "abc" as the chunk data
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n10000000000000003\r\nabc\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode(raw, &mut headers);
+        assert_eq!(
+            result.err(),
+            Some(WireError::InvalidChunkSize { offset: 47 })
+        );
+
+        // The largest size does not overflow the end of the chunk either
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\nFFFFFFFFFFFFFFFF\r\nabc\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode(raw, &mut headers);
+        assert_eq!(
+            result.err(),
+            Some(WireError::InvalidChunkSize { offset: 47 })
+        );
+
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n400\r\n";
+        let options = DecodeOptions::new().max_chunk_size(0x3ff);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode_with_options(raw, &mut headers, &options);
+        assert_eq!(result.err(), Some(WireError::ChunkTooLarge { offset: 55 }));
+
+        // A chunk at the limit is accepted, so only the end of the body is missing
+        let options = DecodeOptions::new().max_chunk_size(3);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullRequest::decode_with_options(&raw[..55], &mut headers, &options);
+        assert_eq!(
+            result.err(),
+            Some(WireError::InvalidChunkedBody { offset: 55 })
+        );
+    }
+
+    #[test]
+    fn test_with_target_keeps_chunked_body() {
+        let raw = b"PUT /v1/items/7 HTTP/1.0\r\nHost: example.com\r\nX-Empty: \r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        let rewritten = request.with_target("/v2/items/7").unwrap();
+        assert_eq!(
+            rewritten,
+            b"PUT /v2/items/7 HTTP/1.0\r\nHost: example.com\r\nX-Empty: \r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n"
+        );
+
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (decoded, len) = FullRequest::decode(&rewritten, &mut headers).unwrap();
+        assert_eq!(decoded.head.path, Some("/v2/items/7"));
+        assert_eq!(len, rewritten.len());
+
+        for target in ["", "/a b", "/a\r\nX-Injected: 1", "/a\nb", "/a\tb", "/a\0"] {
+            assert_eq!(
+                request.with_target(target).err(),
+                Some(WireError::InvalidTarget),
+                "{target:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_upgrade_insecure_requests() {
+        let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\nUpgrade-Insecure-Requests: 1\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(request.upgrade_insecure_requests());
+
+        let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\nUpgrade-Insecure-Requests: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(!request.upgrade_insecure_requests());
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_async_without_tokio() {
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("/submit")
+            .header("Host", "example.com")
+            .body(Full::new(Bytes::from("data")))
+            .unwrap();
+
+        let bytes = futures::executor::block_on(request.encode_async()).unwrap();
+        assert_eq!(
+            &bytes[..],
+            b"POST /submit HTTP/1.1\r\nhost: example.com\r\ncontent-length: 4\r\n\r\ndata"
+        );
+
+        let request = http::Request::builder()
+            .uri("/")
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+        let bytes = futures::executor::block_on(request.encode_async()).unwrap();
+        assert_eq!(&bytes[..], b"GET / HTTP/1.1\r\n\r\n");
+    }
+
+    #[test]
+    fn test_with_body_transform_updates_content_length() {
+        let raw = b"POST /page HTTP/1.1\r\nHost: example.com\r\ncontent-length: 13\r\nX-Id: 7\r\n\r\n<body></body>";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+
+        let rewritten = request
+            .with_body_transform(|body| {
+                String::from_utf8_lossy(body)
+                    .replace("<body>", "<body><script src=\"/x.js\"></script>")
+                    .into_bytes()
+            })
+            .unwrap();
+
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (reparsed, len) = FullRequest::decode(&rewritten, &mut headers).unwrap();
+        assert_eq!(len, rewritten.len());
+        assert_eq!(
+            reparsed.body,
+            b"<body><script src=\"/x.js\"></script></body>"
+        );
+        assert_eq!(
+            reparsed
+                .head
+                .headers
+                .iter()
+                .map(|h| h.name)
+                .collect::<Vec<_>>(),
+            ["Host", "X-Id", "Content-Length"]
+        );
+    }
+
+    #[test]
+    fn test_with_body_transform_empty_bodies() {
+        let raw = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(
+            request.with_body_transform(|body| body.to_vec()).unwrap(),
+            raw
+        );
+
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert_eq!(
+            request.with_body_transform(|_| Vec::new()).unwrap(),
+            b"POST / HTTP/1.1\r\nContent-Length: 0\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_with_body_transform_transfer_codings() {
+        // Codings other than chunked still apply to the body and are kept
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip\r\nTransfer-Encoding: Chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (mut request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        let rewritten = request
+            .with_body_transform(|body| body.to_ascii_uppercase())
+            .unwrap();
+        assert_eq!(
+            rewritten,
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: gzip, chunked\r\n\r\n3\r\nABC\r\n0\r\n\r\n"
+        );
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (reparsed, _) = FullRequest::decode(&rewritten, &mut headers).unwrap();
+        assert_eq!(reparsed.transfer_codings(), ["gzip", "chunked"]);
+
+        // A malformed chunked body is an error
+        request.body = b"3\r\nab";
+        assert!(matches!(
+            request.with_body_transform(|body| body.to_vec()),
+            Err(WireError::InvalidChunkedBody { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_dechunked() {
+        let raw =
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3;ext=1\r\nabc\r\n0\r\n\r\nGET";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, body, len) = FullRequest::decode_dechunked(raw, &mut headers).unwrap();
+        assert_eq!(body, b"abc");
+        assert_eq!(request.body, b"3;ext=1\r\nabc\r\n0\r\n\r\n");
+        assert_eq!(len, raw.len() - 3);
+
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (_, body, _) = FullRequest::decode_dechunked(raw, &mut headers).unwrap();
+        assert_eq!(body, b"abc");
+
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhel";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert!(matches!(
+            FullRequest::decode_dechunked(raw, &mut headers),
+            Err(WireError::IncompleteChunkedBody)
+        ));
+
+        let raw = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n3\nabc\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert!(matches!(
+            FullRequest::decode_dechunked(raw, &mut headers),
+            Err(WireError::InvalidChunkedBody { .. })
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_into_http() {
+        let raw = b"PUT /upload HTTP/1.0\r\nHost: example.com\r\nX-Tag: a\r\nX-Tag: b\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n2\r\nde\r\n0\r\nX-Sum: 1\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        let request = request.into_http().unwrap();
+
+        assert_eq!(request.method(), http::Method::PUT);
+        assert_eq!(request.uri(), "/upload");
+        assert_eq!(request.version(), http::Version::HTTP_10);
+        let tags: Vec<_> = request.headers().get_all("x-tag").iter().collect();
+        assert_eq!(tags, ["a", "b"]);
+        assert_eq!(request.headers()["transfer-encoding"], "chunked");
+        assert_eq!(request.body(), "abcde");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_into_http_invalid_target() {
+        // httparse accepts `<` in the request target, `http::Uri` does not
+        let raw = b"GET /<a> HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (request, _) = FullRequest::decode(raw, &mut headers).unwrap();
+        assert!(matches!(
+            request.into_http(),
+            Err(WireError::InvalidHttp(_))
+        ));
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_into_appends() {
+        let request = || {
+            http::Request::builder()
+                .method("POST")
+                .uri("/items")
+                .header("Host", "example.com")
+                .body(Full::new(Bytes::from("payload")))
+                .unwrap()
+        };
+
+        let mut buf = BytesMut::from(&b"prefix"[..]);
+        let written = request().encode_into(&mut buf).unwrap();
+        assert_eq!(written, buf.len() - 6);
+        assert_eq!(&buf[..6], b"prefix");
+        assert_eq!(buf[6..], request().encode().unwrap());
+
+        let request = http::Request::builder()
+            .version(http::Version::HTTP_2)
+            .body(Empty::<Bytes>::new())
+            .unwrap();
+        assert!(matches!(
+            request.encode_into(&mut buf),
+            Err(WireError::UnsupportedVersion)
+        ));
+        assert_eq!(buf.len(), 6 + written);
+    }
 }
@@ -5,10 +5,12 @@
 //!
 //! # Response Encoding
 //!
-//! The [`WireEncode`] and [`WireEncodeAsync`] traits are implemented for [`http::Response`],
-//! allowing you to serialize responses to bytes.
+//! With the `encode` feature, enabled by default, the [`WireEncode`] and
+//! [`WireEncodeAsync`] traits are implemented for [`http::Response`], allowing you to
+//! serialize responses to bytes.
 //!
 //! ```rust
+//! # #[cfg(feature = "encode")] {
 //! use http_wire::WireEncode;
 //! use http::Response;
 //! use http_body_util::Full;
@@ -16,114 +18,338 @@
 //!
 //! let response = Response::new(Full::new(Bytes::from("Hello")));
 //! let wire_bytes = response.encode().unwrap();
+//! # }
 //! ```
 //!
 //! # Response Decoding
 //!
 //! Use [`FullResponse`] to decode HTTP responses from raw bytes.
 
-use bytes::Bytes;
-use http::Request;
-use http_body_util::Empty;
-use hyper::service::service_fn;
-use hyper_util::rt::TokioIo;
-use tokio::io::duplex;
-use tokio::sync::oneshot;
+use bytes::{BufMut, Bytes, BytesMut};
+#[cfg(feature = "encode")]
+use http_body_util::Full;
+use std::borrow::Cow;
+use std::mem::MaybeUninit;
+#[cfg(feature = "encode")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
 
+use crate::WireDecode;
+use crate::cache_control::CacheControl;
+use crate::chunked::decode_trailers;
+use crate::content_coding::{DEFAULT_MAX_DECOMPRESSED_LEN, decode_content};
+use crate::decoder::{DecodeOptions, Limits};
 use crate::error::WireError;
-use crate::util::{is_chunked_slice, parse_chunked_body, parse_usize};
-use crate::wire::WireCapture;
-use crate::{WireDecode, WireEncode, WireEncodeAsync};
+use crate::link::Link;
+use crate::owned::{DEFAULT_MAX_HEADERS, MAX_VEC_HEADERS, OwnedHeader, find_owned_header};
+use crate::range::ContentRange;
+use crate::security::{StrictTransportSecurity, XFrameOptions};
+#[cfg(feature = "encode")]
+use crate::util::find_head_end;
+#[cfg(feature = "encoding")]
+use crate::util::header_param;
+use crate::util::{
+    combine_header_values, find_header, flag_incomplete_chunked, has_chunked_encoding, head_error,
+    head_lines, header_map, is_chunked_slice, parse_content_length, parsed_headers_len,
+    transfer_codings, unframed_body, write_header, write_parsed_headers, write_status_line,
+};
+#[cfg(feature = "encode")]
+use crate::wire::{
+    BufferedBody, block_on_encode, collect_sized, is_sized, recorded_headers, split_vectored,
+    stream_response, write_response,
+};
+#[cfg(feature = "encode")]
+use crate::{WireEncode, WireEncodeAsync};
 
 pub use httparse::{Header, Response};
 
 // Implementation of WireEncode for Response
+#[cfg(feature = "encode")]
 impl<B> WireEncode for http::Response<B>
 where
-    B: hyper::body::Body + Clone + Send + Sync + 'static,
+    B: hyper::body::Body + Send + Sync + 'static,
     B::Data: Send + Sync + 'static,
     B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
     fn encode(self) -> Result<Bytes, WireError> {
-        // Create a minimal single-threaded runtime
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .map_err(|e| WireError::Connection(Box::new(e)))?;
+        // Sized bodies are encoded in place; others block on the shared runtime
+        let sized = is_sized(self.body());
+        block_on_encode(sized, self.encode_async())?
+    }
+
+    fn encode_into(self, buf: &mut BytesMut) -> Result<usize, WireError> {
+        let start = buf.len();
+        let sized = is_sized(self.body());
+        block_on_encode(sized, encode_response_into(self, buf))??;
+        Ok(buf.len() - start)
+    }
 
-        // Block on the async encode method
-        rt.block_on(self.encode_async())
+    fn encode_vectored(self) -> Result<Vec<Bytes>, WireError> {
+        let sized = is_sized(self.body());
+        block_on_encode(sized, async {
+            let (parts, body) = self.into_parts();
+            match collect_sized(body).await? {
+                Ok(body) => {
+                    let response = http::Response::from_parts(parts, Full::new(body.clone()));
+                    split_vectored(response.encode_async().await?, Some(body))
+                }
+                Err(body) => split_vectored(
+                    http::Response::from_parts(parts, body)
+                        .encode_async()
+                        .await?,
+                    None,
+                ),
+            }
+        })?
     }
 }
 
+#[cfg(feature = "encode")]
 impl<B> WireEncodeAsync for http::Response<B>
 where
     B::Data: Send + Sync + 'static,
-    B: hyper::body::Body + Clone + Send + Sync + 'static,
+    B: hyper::body::Body + Send + Sync + 'static,
     B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
 {
+    /// The body is read frame by frame and the response is serialized directly, with
+    /// no I/O or task spawning, so this runs on any executor.
     async fn encode_async(self) -> Result<Bytes, WireError> {
-        use std::convert::Infallible;
+        let mut out = BytesMut::new();
+        encode_response_into(self, &mut out).await?;
+        Ok(out.freeze())
+    }
 
-        // Check HTTP version - only HTTP/1.1 and HTTP/1.0 are supported
-        let version = self.version();
-        if version != http::Version::HTTP_11 && version != http::Version::HTTP_10 {
-            return Err(WireError::UnsupportedVersion);
-        }
+    /// Works like [`write_to`].
+    async fn encode_to<W>(self, writer: &mut W) -> Result<usize, WireError>
+    where
+        W: AsyncWrite + Unpin + Send,
+    {
+        stream_response(writer, self).await
+    }
+}
 
-        let (client, server) = duplex(8192);
-        let capture_server = WireCapture::new(server);
-        let captured_ref = capture_server.captured.clone();
+/// Appends the wire bytes of a response to `out`.
+///
+/// This is the implementation of [`WireEncodeAsync::encode_async`]: the body is read
+/// into memory, then serialized like [`serialize_response`](crate::wire::serialize_response).
+#[cfg(feature = "encode")]
+pub(crate) async fn encode_response_into<B>(
+    response: http::Response<B>,
+    out: &mut BytesMut,
+) -> Result<(), WireError>
+where
+    B::Data: Send + Sync + 'static,
+    B: hyper::body::Body + Send + Sync + 'static,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let (parts, body) = response.into_parts();
+    write_response(out, parts, BufferedBody::read(body).await?)
+}
 
-        let (tx, rx) = oneshot::channel::<Result<(), WireError>>();
+/// Returns the wire bytes of an interim `100 Continue` response.
+///
+/// A server sends this after receiving a request for which
+/// [`FullRequest::expects_continue`](crate::request::FullRequest::expects_continue)
+/// returns `true`, and before reading the request body.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::response::encode_continue;
+///
+/// assert_eq!(&encode_continue()[..], b"HTTP/1.1 100 Continue\r\n\r\n");
+/// ```
+pub fn encode_continue() -> Bytes {
+    Bytes::from_static(b"HTTP/1.1 100 Continue\r\n\r\n")
+}
 
-        let handle = tokio::spawn(async move {
-            let service = service_fn(move |_req: Request<hyper::body::Incoming>| {
-                let res = self.clone();
-                async move { Ok::<_, Infallible>(res) }
-            });
+/// Encodes a response as sent in reply to a `HEAD` request.
+///
+/// The response is serialized with the same headers it would have for a `GET`,
+/// including the `Content-Length` computed from its body, but the body itself is
+/// omitted from the output, as required by RFC 9110 §9.3.2.
+///
+/// # Errors
+///
+/// Returns the same errors as [`WireEncode::encode`].
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::response::encode_head_response;
+/// use http::Response;
+/// use http_body_util::Full;
+/// use bytes::Bytes;
+///
+/// let response = Response::new(Full::new(Bytes::from("hello")));
+/// let bytes = encode_head_response(response).unwrap();
+/// let output = String::from_utf8_lossy(&bytes);
+/// assert!(output.contains("content-length: 5\r\n"));
+/// assert!(output.ends_with("\r\n\r\n"));
+/// ```
+#[cfg(feature = "encode")]
+pub fn encode_head_response<B>(response: http::Response<B>) -> Result<Bytes, WireError>
+where
+    http::Response<B>: WireEncode,
+{
+    let mut bytes = response.encode()?;
+    let head_end = find_head_end(&bytes).ok_or(WireError::PartialHead)?;
+    bytes.truncate(head_end);
+    Ok(bytes)
+}
 
-            hyper::server::conn::http1::Builder::new()
-                .serve_connection(TokioIo::new(capture_server), service)
-                .await
-        });
+/// Encodes a `101 Switching Protocols` response for a protocol upgrade.
+///
+/// The output is the status line, `connection: upgrade`, `upgrade: <protocol>`, any
+/// extra `headers`, and the blank line. A 101 response never has a body, so no
+/// `Content-Length` is added; framing and upgrade headers in `headers` are skipped.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::response::encode_switching_protocols;
+///
+/// let bytes = encode_switching_protocols("websocket", &http::HeaderMap::new());
+/// assert_eq!(
+///     &bytes[..],
+///     b"HTTP/1.1 101 Switching Protocols\r\nconnection: upgrade\r\nupgrade: websocket\r\n\r\n"
+/// );
+/// ```
+pub fn encode_switching_protocols(protocol: &str, headers: &http::HeaderMap) -> Bytes {
+    use http::header::{CONNECTION, CONTENT_LENGTH, TRANSFER_ENCODING, UPGRADE};
 
-        let req = hyper::Request::builder()
-            .method("GET")
-            .uri("/")
-            .header("host", "localhost")
-            .body(Empty::<Bytes>::new())
-            .unwrap();
+    let mut buf = BytesMut::with_capacity(128);
+    buf.put_slice(b"HTTP/1.1 101 Switching Protocols\r\n");
+    write_header(&mut buf, b"connection", b"upgrade");
+    write_header(&mut buf, b"upgrade", protocol.as_bytes());
+    for (name, value) in headers {
+        if [CONNECTION, UPGRADE, CONTENT_LENGTH, TRANSFER_ENCODING].contains(name) {
+            continue;
+        }
+        write_header(&mut buf, name.as_str().as_bytes(), value.as_bytes());
+    }
+    buf.put_slice(b"\r\n");
+    buf.freeze()
+}
 
-        tokio::spawn(async move {
-            let client_connection = hyper::client::conn::http1::Builder::new()
-                .handshake(TokioIo::new(client))
-                .await;
-
-            match client_connection {
-                Ok((mut sender, connection)) => {
-                    tokio::spawn(connection);
-                    // When send_request completes, the response has been received
-                    let result = sender
-                        .send_request(req)
-                        .await
-                        .map(|_| ())
-                        .map_err(|e| WireError::Connection(Box::new(e)));
-                    let _ = tx.send(result);
-                }
-                Err(e) => {
-                    let _ = tx.send(Err(WireError::Connection(Box::new(e))));
-                }
-            }
-        });
+/// Writes a `101 Switching Protocols` response and hands the writer back.
+///
+/// This is the server side of a protocol upgrade: the response produced by
+/// [`encode_switching_protocols`] is written and flushed, and the returned writer is
+/// positioned for the raw data of the new protocol.
+///
+/// # Errors
+///
+/// Returns [`WireError::Connection`] if writing to `writer` fails.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::response::write_switching_protocols;
+/// use tokio::io::AsyncWriteExt;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut writer = write_switching_protocols(Vec::new(), "h2c", &http::HeaderMap::new())
+///     .await
+///     .unwrap();
+/// writer.write_all(b"raw protocol data").await.unwrap();
+/// assert!(writer.ends_with(b"\r\n\r\nraw protocol data"));
+/// # }
+/// ```
+#[cfg(feature = "encode")]
+pub async fn write_switching_protocols<W>(
+    mut writer: W,
+    protocol: &str,
+    headers: &http::HeaderMap,
+) -> Result<W, WireError>
+where
+    W: AsyncWrite + Unpin,
+{
+    let bytes = encode_switching_protocols(protocol, headers);
+    let io_err = |e: std::io::Error| WireError::Connection(Box::new(e));
 
-        // Wait for completion
-        rx.await.map_err(|_| WireError::Sync)??;
-        let _ = handle.await;
+    writer.write_all(&bytes).await.map_err(io_err)?;
+    writer.flush().await.map_err(io_err)?;
+    Ok(writer)
+}
 
-        let result = captured_ref.lock().clone();
-        Ok(Bytes::from(result))
+/// Writes a response to `writer`, reading its body one frame at a time.
+///
+/// The status line and headers are written first, then each data frame as it arrives,
+/// so the body is never held in memory as a whole. The framing is the same as
+/// [`WireEncode::encode`]: a body without a known size and without a `Content-Length`
+/// header is sent with chunked encoding, ending with its trailers. The writer is
+/// flushed at the end.
+///
+/// Returns the number of bytes written.
+///
+/// # Errors
+///
+/// Returns [`WireError::Connection`] if reading the body or writing to `writer` fails,
+/// and [`WireError::UnsupportedVersion`] for versions other than HTTP/1.0 and HTTP/1.1.
+///
+/// # Example
+///
+/// ```rust
+/// use bytes::Bytes;
+/// use http_body_util::StreamBody;
+/// use http_wire::response::write_to;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let frames = futures::stream::iter(["hello ", "world"].map(|chunk| {
+///     Ok::<_, std::io::Error>(hyper::body::Frame::data(Bytes::from(chunk)))
+/// }));
+/// let response = http::Response::builder()
+///     .header("Date", "Thu, 01 Jan 1970 00:00:00 GMT")
+///     .body(StreamBody::new(frames))
+///     .unwrap();
+///
+/// let mut out = Vec::new();
+/// let written = write_to(response, &mut out).await.unwrap();
+/// assert_eq!(written, out.len());
+/// assert!(out.ends_with(b"transfer-encoding: chunked\r\n\r\n6\r\nhello \r\n5\r\nworld\r\n0\r\n\r\n"));
+/// # }
+/// ```
+#[cfg(feature = "encode")]
+pub async fn write_to<W, B>(response: http::Response<B>, writer: &mut W) -> Result<usize, WireError>
+where
+    W: AsyncWrite + Unpin,
+    B: hyper::body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    stream_response(writer, response).await
+}
+
+/// Reads the status code from the start of a response without parsing headers.
+///
+/// Expects the buffer to start with `HTTP/1.x ` followed by three digits and a space
+/// or line terminator. This is a cheap check for hot paths, such as deciding whether
+/// to retry on a 5xx, that don't need the full response.
+///
+/// Returns `None` if the status line is malformed or not yet complete in `buf`.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::response::peek_status;
+///
+/// assert_eq!(peek_status(b"HTTP/1.1 503 Service Unavailable\r\n"), Some(503));
+/// assert_eq!(peek_status(b"HTTP/1.1 50"), None);
+/// ```
+pub fn peek_status(buf: &[u8]) -> Option<u16> {
+    // "HTTP/1.x " + "DDD" + one delimiter byte
+    let line = buf.get(..13)?;
+    if &line[..7] != b"HTTP/1." || !line[7].is_ascii_digit() || line[8] != b' ' {
+        return None;
     }
+    if !matches!(line[12], b' ' | b'\r' | b'\n') {
+        return None;
+    }
+
+    line[9..12].iter().try_fold(0u16, |code, &b| {
+        b.is_ascii_digit().then(|| code * 10 + u16::from(b - b'0'))
+    })
 }
 
 /// Decoder for extracting HTTP response status code and message length.
@@ -159,6 +385,57 @@ pub struct FullResponse<'headers, 'buf> {
     /// or [`decode`](WireDecode::decode). It contains the complete body content
     /// after decoding any transfer encodings (chunked or content-length).
     pub body: &'buf [u8],
+    /// The body length declared by the `Content-Length` header.
+    ///
+    /// Set by [`parse`](Self::parse) and [`decode`](WireDecode::decode) from the
+    /// header they use to frame the body. `None` if the header is absent, and
+    /// for chunked messages and responses that cannot have a body (1xx, 204 and 304).
+    pub content_length: Option<usize>,
+    /// The reason phrase as sent, before UTF-8 validation.
+    reason: &'buf [u8],
+}
+
+/// A decoded response that owns its data.
+///
+/// Produced by [`FullResponse::decode_owned`]. All fields are [`Bytes`] slices of the
+/// decoded buffer, so no data is copied and the response can be moved across tasks
+/// or threads independently of the original buffer's borrow.
+#[derive(Debug, Clone)]
+pub struct OwnedResponse {
+    /// The HTTP minor version (`0` for HTTP/1.0, `1` for HTTP/1.1).
+    pub version: u8,
+    /// The status code.
+    pub code: u16,
+    /// The reason phrase as sent, possibly empty and not necessarily UTF-8 (see
+    /// [`FullResponse::reason_bytes`]).
+    pub reason: Bytes,
+    /// The headers, in their original order.
+    pub headers: Vec<OwnedHeader>,
+    /// The response body, with the same framing semantics as [`FullResponse::body`].
+    pub body: Bytes,
+}
+
+impl OwnedResponse {
+    /// Creates an owned response from one decoded from `buf`, without copying.
+    pub(crate) fn from_full(buf: &Bytes, response: &FullResponse<'_, '_>) -> Self {
+        Self {
+            version: response.head.version.unwrap_or(1),
+            code: response.head.code.unwrap_or(200),
+            reason: buf.slice_ref(response.reason_bytes().unwrap_or_default()),
+            headers: response
+                .head
+                .headers
+                .iter()
+                .map(|h| OwnedHeader::from_header(buf, h))
+                .collect(),
+            body: buf.slice_ref(response.body),
+        }
+    }
+
+    /// Returns the value of the first header matching `name` (ASCII case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&Bytes> {
+        find_owned_header(&self.headers, name)
+    }
 }
 
 impl<'headers, 'buf> FullResponse<'headers, 'buf> {
@@ -180,55 +457,833 @@ impl<'headers, 'buf> FullResponse<'headers, 'buf> {
     /// # Errors
     ///
     /// Returns [`WireError::PartialHead`] if headers are incomplete,
-    /// [`WireError::HttparseError`] if header parsing fails,
+    /// [`WireError::Malformed`] if the head is malformed, [`WireError::HttparseError`] if
+    /// it has more headers than the storage can hold,
     /// [`WireError::InvalidChunkedBody`] if chunked encoding is malformed,
     /// or [`WireError::IncompleteBody`] if the body is shorter than specified by `Content-Length`.
     pub fn parse(&mut self, buf: &'buf [u8]) -> Result<usize, WireError> {
+        self.parse_with_options(buf, &DecodeOptions::new())
+    }
+
+    /// Parse an HTTP response, enforcing the limits in `options`.
+    ///
+    /// Behaves like [`parse`](Self::parse) with the additional checks described on
+    /// [`DecodeOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`parse`](Self::parse), plus the errors of the limits
+    /// set in `options`, such as [`WireError::HeadTooLarge`] for
+    /// [`DecodeOptions::max_head_bytes`]; each option documents its error.
+    pub fn parse_with_options(
+        &mut self,
+        buf: &'buf [u8],
+        options: &DecodeOptions,
+    ) -> Result<usize, WireError> {
         match self.head.parse(buf) {
             Ok(httparse::Status::Complete(headers_len)) => {
-                let code = self.head.code.unwrap_or(200);
+                options.check_head_len(headers_len)?;
+                options.check_header_count(self.head.headers.len())?;
+                self.parse_core(buf, headers_len, options)
+            }
+            Ok(httparse::Status::Partial) => {
+                options.check_head_len(buf.len())?;
+                Err(WireError::PartialHead)
+            }
+            Err(err) => {
+                let err = head_error(err, buf, |headers, prefix| {
+                    httparse::Response::new(headers).parse(prefix).is_err()
+                });
+                Err(options.refine_head_error(err, buf))
+            }
+        }
+    }
 
-                // Fast path for responses that never have a body (1xx, 204, 304)
-                if code == 204 || code == 304 || (100..200).contains(&code) {
-                    self.body = &[];
-                    return Ok(headers_len);
-                }
+    /// Parse an HTTP response using uninitialized headers storage.
+    ///
+    /// Behaves exactly like [`parse`](Self::parse), but the parsed headers are
+    /// stored in `headers`, which does not need to be initialized beforehand.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`parse`](Self::parse).
+    pub fn parse_uninit(
+        &mut self,
+        buf: &'buf [u8],
+        headers: &'headers mut [MaybeUninit<Header<'buf>>],
+    ) -> Result<usize, WireError> {
+        match httparse::ParserConfig::default().parse_response_with_uninit_headers(
+            &mut self.head,
+            buf,
+            headers,
+        ) {
+            Ok(httparse::Status::Complete(headers_len)) => {
+                self.parse_core(buf, headers_len, &DecodeOptions::new())
+            }
+            Ok(httparse::Status::Partial) => Err(WireError::PartialHead),
+            Err(err) => Err(head_error(err, buf, |headers, prefix| {
+                httparse::Response::new(headers).parse(prefix).is_err()
+            })),
+        }
+    }
 
-                let mut content_len: Option<usize> = None;
-                let mut is_chunked = false;
-
-                // Scan headers for Content-Length or Transfer-Encoding
-                for header in self.head.headers.iter() {
-                    let name = header.name.as_bytes();
-                    if name.len() == 14 && name.eq_ignore_ascii_case(b"Content-Length") {
-                        content_len = parse_usize(header.value);
-                    } else if name.len() == 17 && name.eq_ignore_ascii_case(b"Transfer-Encoding") {
-                        is_chunked = is_chunked_slice(header.value);
-                    }
-                }
+    /// Processes the body of a response whose head, `headers_len` bytes long, has
+    /// been parsed.
+    fn parse_core(
+        &mut self,
+        buf: &'buf [u8],
+        headers_len: usize,
+        options: &DecodeOptions,
+    ) -> Result<usize, WireError> {
+        self.reason = raw_reason(buf);
+        let code = self.head.code.unwrap_or(200);
+
+        // Fast path for responses that never have a body (1xx, 204, 304)
+        if code == 204 || code == 304 || (100..200).contains(&code) {
+            self.body = &[];
+            self.content_length = None;
+            return Ok(headers_len);
+        }
+
+        let mut content_len: Option<usize> = None;
+        let mut has_content_len = false;
+        let mut has_transfer_encoding = false;
+        let mut is_chunked = false;
 
-                // Calculate body length
-                if is_chunked {
-                    let body_len = parse_chunked_body(&buf[headers_len..])
-                        .ok_or(WireError::InvalidChunkedBody)?;
-                    self.body = &buf[headers_len..headers_len + body_len];
-                    Ok(headers_len + body_len)
-                } else {
-                    // If content-length is missing, length is 0
-                    let body_len = content_len.unwrap_or(0);
-                    let total = headers_len + body_len;
-                    if buf.len() >= total {
-                        self.body = &buf[headers_len..total];
-                        Ok(total)
-                    } else {
-                        Err(WireError::IncompleteBody(total - buf.len()))
-                    }
+        // Scan headers for Content-Length or Transfer-Encoding
+        for header in self.head.headers.iter() {
+            let name = header.name.as_bytes();
+            if name.len() == 14 && name.eq_ignore_ascii_case(b"Content-Length") {
+                let len = Some(parse_content_length(header.value)?);
+                if has_content_len && len != content_len {
+                    return Err(WireError::DuplicateContentLength);
                 }
+                content_len = len;
+                has_content_len = true;
+            } else if name.len() == 17 && name.eq_ignore_ascii_case(b"Transfer-Encoding") {
+                has_transfer_encoding = true;
+                is_chunked = is_chunked_slice(header.value);
             }
-            Ok(httparse::Status::Partial) => Err(WireError::PartialHead),
-            Err(err) => Err(err.into()),
         }
+
+        // Both framings at once is a request smuggling vector (RFC 9112 §6.3)
+        if has_transfer_encoding && has_content_len {
+            return Err(WireError::ConflictingFraming);
+        }
+        self.content_length = content_len;
+
+        // A response to HEAD has the framing headers of a body it does not carry
+        if options.head_response {
+            self.body = &[];
+            return Ok(headers_len);
+        }
+
+        // Codings that do not end in chunked can only be framed by the connection
+        // closing (RFC 9112 §6.3), which the caller must opt into. A chunked coding
+        // followed by another one is never valid.
+        if has_transfer_encoding
+            && !is_chunked
+            && (!options.close_delimited
+                || transfer_codings(self.head.headers)
+                    .any(|coding| coding.eq_ignore_ascii_case(b"chunked")))
+        {
+            return Err(WireError::UnsupportedTransferCoding);
+        }
+
+        // Calculate body length
+        if is_chunked {
+            let body_len = options.chunked_body_len(buf, headers_len)?;
+            self.body = &buf[headers_len..headers_len + body_len];
+            Ok(headers_len + body_len)
+        } else if !has_content_len && options.close_delimited {
+            // The body runs until the connection closes, so it is all of `buf`
+            options.check_body_len(buf.len() - headers_len)?;
+            self.body = &buf[headers_len..];
+            Ok(buf.len())
+        } else {
+            // If content-length is missing, length is 0
+            let body_len = content_len.unwrap_or(0);
+            options.check_body_len(body_len)?;
+            let total = headers_len
+                .checked_add(body_len)
+                .ok_or(WireError::InvalidContentLength)?;
+            if buf.len() >= total {
+                self.body = &buf[headers_len..total];
+                Ok(total)
+            } else {
+                Err(WireError::IncompleteBody(total - buf.len()))
+            }
+        }
+    }
+
+    /// Decodes a response like [`decode`](WireDecode::decode), enforcing the limits in
+    /// `options`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`parse_with_options`](Self::parse_with_options).
+    pub fn decode_with_options(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+        options: &DecodeOptions,
+    ) -> Result<(Self, usize), WireError> {
+        let mut full_response = FullResponse {
+            head: httparse::Response::new(headers),
+            body: &[],
+            content_length: None,
+            reason: &[],
+        };
+
+        let total = full_response.parse_with_options(buf, options)?;
+        Ok((full_response, total))
+    }
+
+    /// Decodes a response like [`decode`](WireDecode::decode), enforcing every limit in
+    /// `limits`.
+    ///
+    /// See [`FullRequest::decode_limited`](crate::request::FullRequest::decode_limited).
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as
+    /// [`FullRequest::decode_limited`](crate::request::FullRequest::decode_limited).
+    pub fn decode_limited(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+        limits: &Limits,
+    ) -> Result<(Self, usize), WireError> {
+        Self::decode_with_options(buf, headers, &DecodeOptions::from(*limits))
+    }
+
+    /// Decodes a response like [`decode`](WireDecode::decode), rejecting a header section
+    /// longer than `max_head_bytes`.
+    ///
+    /// See [`FullRequest::decode_with_limits`](crate::request::FullRequest::decode_with_limits).
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode), plus
+    /// [`WireError::HeadTooLarge`] if the head, complete or not, is longer than
+    /// `max_head_bytes`.
+    pub fn decode_with_limits(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+        max_head_bytes: usize,
+    ) -> Result<(Self, usize), WireError> {
+        Self::decode_with_options(
+            buf,
+            headers,
+            &DecodeOptions::new().max_head_bytes(max_head_bytes),
+        )
+    }
+
+    /// Decodes a response to a request with the given `method`.
+    ///
+    /// Whether a response has a body depends on the request it answers: a response
+    /// to `HEAD` never has one, even with a `Content-Length`, so [`decode`](WireDecode::decode)
+    /// would wait for body bytes that never arrive. For `HEAD` this decodes with
+    /// [`DecodeOptions::response_to_head`]; any other method decodes like
+    /// [`decode`](WireDecode::decode). Methods are compared case-sensitively.
+    ///
+    /// Responses with a 1xx, 204 or 304 status have no body whatever the method.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`parse`](Self::parse).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 1234\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    ///
+    /// let (response, len) = FullResponse::decode_for_method(raw, &mut headers, "HEAD").unwrap();
+    /// assert_eq!(response.content_length, Some(1234));
+    /// assert!(response.body.is_empty());
+    /// assert_eq!(len, raw.len());
+    /// ```
+    pub fn decode_for_method(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+        method: &str,
+    ) -> Result<(Self, usize), WireError> {
+        let options = DecodeOptions::new().response_to_head(method == "HEAD");
+        Self::decode_with_options(buf, headers, &options)
+    }
+
+    /// Decodes a response like [`decode_owned`](Self::decode_owned), with header storage
+    /// sized to the head.
+    ///
+    /// See [`FullRequest::decode_vec`](crate::request::FullRequest::decode_vec).
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode), with
+    /// [`WireError::HttparseError`] wrapping [`httparse::Error::TooManyHeaders`] only
+    /// if the head has more than 1024 headers.
+    pub fn decode_vec(buf: Bytes) -> Result<(OwnedResponse, usize), WireError> {
+        let (lines, _) = head_lines(&buf);
+        let mut headers = vec![httparse::EMPTY_HEADER; lines.min(MAX_VEC_HEADERS)];
+        let (response, total) = FullResponse::decode(&buf, &mut headers)?;
+        Ok((OwnedResponse::from_full(&buf, &response), total))
+    }
+
+    /// Decodes a response like [`decode`](WireDecode::decode), also returning its body with
+    /// the chunked framing removed.
+    ///
+    /// Chunk extensions and trailers are dropped from the returned body. A body that is
+    /// not chunked is returned as is. [`body`](Self::body) still holds the raw bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode), except that a chunked
+    /// body cut short returns [`WireError::IncompleteChunkedBody`], so the caller knows
+    /// to read more data. Malformed chunk framing returns
+    /// [`WireError::InvalidChunkedBody`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\nX-Checksum: 1\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, body, len) = FullResponse::decode_dechunked(raw, &mut headers).unwrap();
+    /// assert_eq!(body, b"hello world");
+    /// assert_eq!(len, raw.len());
+    /// ```
+    pub fn decode_dechunked(
+        buf: &'buf [u8],
+        headers: &'headers mut [Header<'buf>],
+    ) -> Result<(Self, Vec<u8>, usize), WireError> {
+        let (response, total) =
+            Self::decode(buf, headers).map_err(|err| flag_incomplete_chunked(err, buf.len()))?;
+        let body = unframed_body(response.head.headers, response.body, false)?.into_owned();
+        Ok((response, body, total))
+    }
+
+    /// Parses the trailer section of a chunked body into `trailers`.
+    ///
+    /// Works like [`decode_trailers`] on [`body`](Self::body), with
+    /// a caller-supplied header array as for [`decode`](WireDecode::decode). Returns an
+    /// empty slice if the body is not chunked or has no trailers.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode_trailers`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nTrailer: grpc-status\r\n\r\n2\r\nok\r\n0\r\ngrpc-status: 0\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// let mut trailers = [httparse::EMPTY_HEADER; 4];
+    /// let trailers = response.trailers(&mut trailers).unwrap();
+    /// assert_eq!(trailers[0].name, "grpc-status");
+    /// assert_eq!(trailers[0].value, b"0");
+    /// ```
+    pub fn trailers<'t>(
+        &self,
+        trailers: &'t mut [Header<'buf>],
+    ) -> Result<&'t [Header<'buf>], WireError> {
+        if !has_chunked_encoding(self.head.headers) {
+            return Ok(&[]);
+        }
+        decode_trailers(self.body, trailers)
+    }
+
+    /// Converts the response into an [`http::Response`] with an owned body.
+    ///
+    /// Status, version and headers are copied as sent. With the `encode` feature, the
+    /// spelling and order of the headers are kept in
+    /// [`HeaderCase`](crate::wire::HeaderCase) and [`HeaderOrder`](crate::wire::HeaderOrder)
+    /// extensions, and a reason phrase other than the canonical one in a
+    /// `hyper::ext::ReasonPhrase` extension, all of which
+    /// [`encode`](crate::WireEncode::encode) writes back. The body has its chunked framing
+    /// removed, like the body returned by [`decode_dechunked`](Self::decode_dechunked);
+    /// trailers are dropped.
+    ///
+    /// # Errors
+    ///
+    /// - [`WireError::InvalidHttp`] if the status code is outside `100..=599`, or a
+    ///   header name or value is not accepted by `http`
+    /// - [`WireError::InvalidChunkedBody`] if the chunk framing is malformed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// # #[cfg(feature = "encode")]
+    /// use http_wire::WireEncode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 Fine\r\nContent-Length: 5\r\n\r\nhello";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// let response = response.into_http().unwrap();
+    /// assert_eq!(response.status(), http::StatusCode::OK);
+    /// assert_eq!(response.body(), "hello");
+    ///
+    /// # #[cfg(feature = "encode")] {
+    /// let encoded = response.map(http_body_util::Full::new).encode().unwrap();
+    /// assert!(encoded.starts_with(b"HTTP/1.1 200 Fine\r\n"));
+    /// # }
+    /// ```
+    pub fn into_http(&self) -> Result<http::Response<Bytes>, WireError> {
+        self.to_http_with(false)
+    }
+
+    /// Converts like [`into_http`](Self::into_http), with chunk lines allowed to end
+    /// with a bare LF if `bare_lf` is set, as for a message decoded with
+    /// [`DecodeOptions::allow_bare_lf`].
+    pub(crate) fn to_http_with(&self, bare_lf: bool) -> Result<http::Response<Bytes>, WireError> {
+        let version = match self.head.version {
+            Some(0) => http::Version::HTTP_10,
+            _ => http::Version::HTTP_11,
+        };
+        // `http` accepts any code up to 999, but only 1xx to 5xx are defined (RFC 9110
+        // §15), so a higher code is converted as an invalid one
+        let code = self.head.code.unwrap_or_default();
+        let status = http::StatusCode::from_u16(if code < 600 { code } else { 0 })
+            .map_err(http::Error::from)?;
+        let mut builder = http::Response::builder().status(status).version(version);
+        for header in self.head.headers.iter() {
+            let name =
+                http::HeaderName::from_bytes(header.name.as_bytes()).map_err(http::Error::from)?;
+            builder = builder.header(name, header.value);
+        }
+
+        // Only the encoder reads these extensions
+        #[cfg(feature = "encode")]
+        {
+            let (case, order) = recorded_headers(self.head.headers)?;
+            builder = builder.extension(case).extension(order);
+
+            let canonical = status.canonical_reason();
+            if canonical.map(str::as_bytes) != Some(self.reason)
+                && let Ok(reason) = hyper::ext::ReasonPhrase::try_from(self.reason)
+            {
+                builder = builder.extension(reason);
+            }
+        }
+
+        let body = unframed_body(self.head.headers, self.body, bare_lf)?;
+        Ok(builder.body(Bytes::from(body.into_owned()))?)
+    }
+
+    /// Decodes a response into an [`OwnedResponse`] that shares `buf`'s allocation.
+    ///
+    /// Header names, values, the reason phrase and the body are zero-copy
+    /// [`Bytes::slice_ref`] views into `buf`. Up to 64 headers are supported.
+    ///
+    /// Returns the decoded response and the total length of the message in bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode`](WireDecode::decode).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use bytes::Bytes;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let buf = Bytes::from_static(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello");
+    /// let (response, len) = FullResponse::decode_owned(buf.clone()).unwrap();
+    /// drop(buf);
+    ///
+    /// let handle = std::thread::spawn(move || response.body);
+    /// assert_eq!(handle.join().unwrap(), "hello");
+    /// assert_eq!(len, 43);
+    /// ```
+    pub fn decode_owned(buf: Bytes) -> Result<(OwnedResponse, usize), WireError> {
+        let mut headers = [httparse::EMPTY_HEADER; DEFAULT_MAX_HEADERS];
+        let (response, total) = FullResponse::decode(&buf, &mut headers)?;
+        Ok((OwnedResponse::from_full(&buf, &response), total))
+    }
+
+    /// Returns the body transcoded to a UTF-8 `String`.
+    ///
+    /// The source encoding is taken from the `charset` parameter of the
+    /// `Content-Type` header and resolved with the WHATWG encoding labels, so common
+    /// names such as `latin1`, `iso-8859-1`, `windows-1252` or `utf-16le` are
+    /// understood. Without a `charset` parameter the body is assumed to be UTF-8.
+    /// The declared charset is authoritative: a matching byte order mark is removed,
+    /// but never used to switch encodings. A chunked body is de-chunked first.
+    ///
+    /// Requires the `encoding` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::UnsupportedCharset`] for an unknown charset,
+    /// [`WireError::InvalidText`] if the body is not valid in the charset, or
+    /// [`WireError::InvalidChunkedBody`] for malformed chunked framing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=ISO-8859-1\r\nContent-Length: 4\r\n\r\ncaf\xe9";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    /// assert_eq!(response.body_utf8().unwrap(), "café");
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn body_utf8(&self) -> Result<String, WireError> {
+        let charset = find_header(self.head.headers, "Content-Type")
+            .and_then(|value| header_param(value, "charset"));
+
+        let encoding = match charset {
+            Some(label) => encoding_rs::Encoding::for_label(label).ok_or_else(|| {
+                WireError::UnsupportedCharset(String::from_utf8_lossy(label).into_owned())
+            })?,
+            None => encoding_rs::UTF_8,
+        };
+
+        let body = unframed_body(self.head.headers, self.body, false)?;
+        let (text, malformed) = encoding.decode_with_bom_removal(&body);
+        if malformed {
+            return Err(WireError::InvalidText(encoding.name()));
+        }
+        Ok(text.into_owned())
     }
+
+    /// Returns `true` for an informational (1xx) response.
+    ///
+    /// Interim responses such as `100 Continue` or `103 Early Hints` have no body and
+    /// are followed by another response to the same request, so a client skips them by
+    /// advancing past their length and decoding again. The exception is
+    /// `101 Switching Protocols`, after which the connection no longer carries HTTP/1.x.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let mut raw: &[u8] = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+    /// let response = loop {
+    ///     let mut headers = [httparse::EMPTY_HEADER; 16];
+    ///     let (response, len) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///     if !response.is_informational() {
+    ///         break (response.head.code, response.body);
+    ///     }
+    ///     raw = &raw[len..];
+    /// };
+    /// assert_eq!(response, (Some(200), &b"ok"[..]));
+    /// ```
+    pub fn is_informational(&self) -> bool {
+        self.head
+            .code
+            .is_some_and(|code| (100..200).contains(&code))
+    }
+
+    /// Returns the value of the first header named `name`, matched ASCII
+    /// case-insensitively.
+    ///
+    /// The value is returned as sent, without trimming. Use
+    /// [`headers_all`](Self::headers_all) for headers that may be repeated.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nSet-Cookie: a=1\r\nContent-Length: 0\r\nset-cookie: b=2\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// assert_eq!(response.header("content-length"), Some(&b"0"[..]));
+    /// let cookies: Vec<&[u8]> = response.headers_all("Set-Cookie").collect();
+    /// assert_eq!(cookies, [&b"a=1"[..], b"b=2"]);
+    /// ```
+    pub fn header(&self, name: &str) -> Option<&'buf [u8]> {
+        find_header(self.head.headers, name)
+    }
+
+    /// Returns the values of all headers named `name`, matched ASCII
+    /// case-insensitively, in the order they appear.
+    ///
+    /// This is the way to read headers such as `Set-Cookie`, whose values cannot be
+    /// combined into one.
+    pub fn headers_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'buf [u8]> + 'a {
+        self.head
+            .headers
+            .iter()
+            .filter(move |h| h.name.eq_ignore_ascii_case(name))
+            .map(|h| h.value)
+    }
+
+    /// Returns the headers as an [`http::HeaderMap`].
+    ///
+    /// Only the headers are converted, which is cheaper than [`into_http`](Self::into_http)
+    /// when the typed accessors of `http` are all that is needed. Repeated headers keep
+    /// all their values, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidHttp`] if a header name or value is not accepted by
+    /// `http`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\nok";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// let map = response.header_map().unwrap();
+    /// assert_eq!(map[http::header::CONTENT_TYPE], "text/plain");
+    /// ```
+    pub fn header_map(&self) -> Result<http::HeaderMap, WireError> {
+        header_map(self.head.headers)
+    }
+
+    /// Returns the parsed `Cache-Control` directives of the response.
+    ///
+    /// Multiple `Cache-Control` lines are combined. If the header is absent, no
+    /// directives are set. See [`CacheControl`] for the parsing rules.
+    pub fn cache_control(&self) -> CacheControl {
+        CacheControl::from_headers(self.head.headers)
+    }
+
+    /// Returns the parsed `Content-Range` header, if present.
+    ///
+    /// Returns `None` if the header is absent, and `Some(Err(_))` if it is present but
+    /// malformed. See [`ContentRange`] for the accepted forms.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 0-4/11\r\nContent-Length: 5\r\n\r\nhello";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// let range = response.content_range().unwrap().unwrap();
+    /// assert_eq!(range.range, Some((0, 4)));
+    /// assert_eq!(range.complete_length, Some(11));
+    /// ```
+    pub fn content_range(&self) -> Option<Result<ContentRange, WireError>> {
+        find_header(self.head.headers, "Content-Range").map(ContentRange::parse)
+    }
+
+    /// Returns the parsed `Strict-Transport-Security` header, if present.
+    ///
+    /// Returns `None` if the header is absent, and `Some(Err(_))` if it is present but
+    /// invalid. When the header is repeated, only the first one is used, as browsers
+    /// do (RFC 6797 §8.1).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nStrict-Transport-Security: max-age=63072000; includeSubDomains\r\nContent-Length: 0\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// let hsts = response.strict_transport_security().unwrap().unwrap();
+    /// assert_eq!(hsts.max_age, 63072000);
+    /// assert!(hsts.include_subdomains);
+    /// ```
+    pub fn strict_transport_security(&self) -> Option<Result<StrictTransportSecurity, WireError>> {
+        find_header(self.head.headers, "Strict-Transport-Security")
+            .map(StrictTransportSecurity::parse)
+    }
+
+    /// Returns the `Content-Security-Policy` directives as sent.
+    ///
+    /// Multiple headers are joined with `, `, which is how a list of policies is
+    /// serialized; each of them is enforced. Returns `None` if the header is absent or
+    /// not valid UTF-8.
+    pub fn content_security_policy(&self) -> Option<Cow<'buf, str>> {
+        match combine_header_values(self.head.headers, "Content-Security-Policy")? {
+            Cow::Borrowed(value) => std::str::from_utf8(value).ok().map(Cow::Borrowed),
+            Cow::Owned(value) => String::from_utf8(value).ok().map(Cow::Owned),
+        }
+    }
+
+    /// Returns the parsed `X-Frame-Options` header, if present.
+    pub fn x_frame_options(&self) -> Option<XFrameOptions> {
+        find_header(self.head.headers, "X-Frame-Options").map(XFrameOptions::parse)
+    }
+
+    /// Returns the reason phrase exactly as sent, or `None` if no response was parsed.
+    ///
+    /// `head.reason` is a `&str`, and httparse reports an empty reason when the phrase
+    /// contains non-ASCII bytes (obs-text). This returns the raw bytes instead, so that a
+    /// proxy or analyzer sees what the server actually sent.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 \xC0K\r\nContent-Length: 0\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// assert_eq!(response.head.reason, Some(""));
+    /// assert_eq!(response.reason_bytes(), Some(&b"\xC0K"[..]));
+    /// ```
+    pub fn reason_bytes(&self) -> Option<&'buf [u8]> {
+        self.head.code.map(|_| self.reason)
+    }
+
+    /// Re-serializes the response as it was decoded.
+    ///
+    /// The status line is rebuilt from the version, status code and the reason phrase
+    /// as sent (see [`reason_bytes`](Self::reason_bytes)). Headers are written
+    /// byte-for-byte in their original order and casing, and the body with its original
+    /// framing, so a chunked body stays chunked. Whitespace around header values and
+    /// bare LF line endings are normalized, since they are not kept by the parser.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\nX-Cache: MISS\r\n\r\nnope";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// assert_eq!(response.to_wire(), raw);
+    /// ```
+    pub fn to_wire(&self) -> Vec<u8> {
+        let headers = &*self.head.headers;
+        let mut out = Vec::with_capacity(
+            self.reason.len() + parsed_headers_len(headers) + self.body.len() + 32,
+        );
+        write_status_line(
+            &mut out,
+            self.head.version.unwrap_or(1),
+            self.head.code.unwrap_or_default(),
+            self.reason,
+        );
+        write_parsed_headers(&mut out, headers);
+        out.put_slice(self.body);
+        out
+    }
+
+    /// Returns the body with its transfer and content codings removed.
+    ///
+    /// A chunked body is de-chunked first, then the codings listed in
+    /// `Content-Encoding` are undone in reverse order. The body is borrowed when there
+    /// is nothing to decode.
+    ///
+    /// Supported codings depend on enabled features:
+    ///
+    /// - `identity`, always
+    /// - `gzip`, `x-gzip` and `deflate`, with the `compression` feature
+    /// - `br`, with the `brotli` or `compression` feature
+    ///
+    /// Decompression stops at 64 MiB of output, so a small compressed body cannot
+    /// expand to fill memory; see
+    /// [`decode_body_decompressed_with_limit`](Self::decode_body_decompressed_with_limit)
+    /// to choose the limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::UnsupportedEncoding`] for any other coding,
+    /// [`WireError::InvalidContentEncoding`] if the body cannot be decoded,
+    /// [`WireError::BodyTooLarge`] if it decompresses to more than the limit, or
+    /// [`WireError::InvalidChunkedBody`] for malformed chunked framing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::{WireDecode, WireError};
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: x-custom\r\nContent-Length: 5\r\n\r\nhello";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// assert!(matches!(
+    ///     response.decode_body_decompressed(),
+    ///     Err(WireError::UnsupportedEncoding(coding)) if coding == "x-custom"
+    /// ));
+    /// ```
+    pub fn decode_body_decompressed(&self) -> Result<Cow<'buf, [u8]>, WireError> {
+        self.decode_body_decompressed_with_limit(DEFAULT_MAX_DECOMPRESSED_LEN)
+    }
+
+    /// Returns the body with its transfer and content codings removed, failing once a
+    /// content coding yields more than `max_len` bytes.
+    ///
+    /// Works like [`decode_body_decompressed`](Self::decode_body_decompressed), which
+    /// uses a limit of 64 MiB.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as
+    /// [`decode_body_decompressed`](Self::decode_body_decompressed), with
+    /// [`WireError::BodyTooLarge`] for output beyond `max_len`.
+    pub fn decode_body_decompressed_with_limit(
+        &self,
+        max_len: usize,
+    ) -> Result<Cow<'buf, [u8]>, WireError> {
+        let body = unframed_body(self.head.headers, self.body, false)?;
+        decode_content(self.head.headers, body, max_len)
+    }
+
+    /// Returns the links of all `Link` headers, in order.
+    ///
+    /// See [`Link::parse`] for the parsing rules. Returns an empty vector if there is
+    /// no `Link` header.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use http_wire::WireDecode;
+    /// use http_wire::response::FullResponse;
+    ///
+    /// let raw = b"HTTP/1.1 200 OK\r\nLink: </items?page=3>; rel=\"next\"\r\nLink: </items?page=1>; rel=\"prev\"\r\nContent-Length: 0\r\n\r\n";
+    /// let mut headers = [httparse::EMPTY_HEADER; 16];
+    /// let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+    ///
+    /// let next = response.links().into_iter().find(|link| link.has_rel("next"));
+    /// assert_eq!(next.unwrap().uri, "/items?page=3");
+    /// ```
+    pub fn links(&self) -> Vec<Link> {
+        Link::from_headers(self.head.headers)
+    }
+}
+
+/// Returns the reason phrase of the status line at the start of `buf`.
+///
+/// `buf` must start with a status line accepted by httparse, possibly preceded by
+/// empty lines, which httparse skips.
+fn raw_reason(buf: &[u8]) -> &[u8] {
+    let start = buf
+        .iter()
+        .position(|&b| b != b'\r' && b != b'\n')
+        .unwrap_or(buf.len());
+    let line = &buf[start..];
+    let end = line.iter().position(|&b| b == b'\n').unwrap_or(line.len());
+    let line = line[..end].strip_suffix(b"\r").unwrap_or(&line[..end]);
+    // "HTTP/1.x DDD " is 13 bytes
+    line.get(13..).unwrap_or_default()
 }
 
 impl<'headers, 'buf> WireDecode<'headers, 'buf> for FullResponse<'headers, 'buf> {
@@ -239,22 +1294,38 @@ impl<'headers, 'buf> WireDecode<'headers, 'buf> for FullResponse<'headers, 'buf>
         let mut full_response = FullResponse {
             head: httparse::Response::new(headers),
             body: &[],
+            content_length: None,
+            reason: &[],
         };
 
         let total = full_response.parse(buf)?;
         Ok((full_response, total))
     }
 
-    // decode_uninit is not implemented for FullResponse because httparse::Response
-    // does not provide parse_with_uninit_headers method.
-    // The default implementation will panic with an appropriate message.
+    fn decode_uninit(
+        buf: &'buf [u8],
+        headers: &'headers mut [MaybeUninit<Header<'buf>>],
+    ) -> Result<(Self, usize), WireError> {
+        let mut full_response = FullResponse {
+            head: httparse::Response::new(&mut []),
+            body: &[],
+            content_length: None,
+            reason: &[],
+        };
+
+        let total = full_response.parse_uninit(buf, headers)?;
+        Ok((full_response, total))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "encode")]
     use http::Response;
+    #[cfg(feature = "encode")]
     use http_body_util::Full;
+    #[cfg(feature = "encode")]
     #[test]
     fn test_response_sync_ok() {
         let response = http::Response::builder()
@@ -270,6 +1341,7 @@ mod tests {
         assert!(output.contains("Hello"));
     }
 
+    #[cfg(feature = "encode")]
     #[test]
     fn test_response_sync_404() {
         let response = http::Response::builder()
@@ -284,6 +1356,7 @@ mod tests {
         assert!(output.contains("Not Found"));
     }
 
+    #[cfg(feature = "encode")]
     #[test]
     fn test_response_sync_http2_rejected() {
         let response = http::Response::builder()
@@ -296,6 +1369,7 @@ mod tests {
         assert!(matches!(result, Err(WireError::UnsupportedVersion)));
     }
 
+    #[cfg(feature = "encode")]
     #[tokio::test]
     async fn test_http1_capture() {
         let response = Response::builder()
@@ -312,6 +1386,7 @@ mod tests {
         assert!(output.contains("Hello World"));
     }
 
+    #[cfg(feature = "encode")]
     #[tokio::test]
     async fn test_response_is_complete() {
         let body = "Hello World";
@@ -336,6 +1411,7 @@ mod tests {
         assert_eq!(parts.len(), 2, "Response should have headers and body");
         assert!(parts[1].contains(body), "Body should contain the payload");
     }
+    #[cfg(feature = "encode")]
     #[tokio::test]
     async fn test_response_to_wire() {
         let response = Response::builder()
@@ -351,6 +1427,7 @@ mod tests {
         assert!(output.contains("Hello"));
     }
 
+    #[cfg(feature = "encode")]
     #[tokio::test]
     async fn test_response_with_status_to_wire() {
         let response = Response::builder()
@@ -358,56 +1435,291 @@ mod tests {
             .body(Full::new(Bytes::from("Not Found")))
             .unwrap();
 
-        let bytes = response.encode_async().await.unwrap();
-        let output = String::from_utf8_lossy(&bytes);
+        let bytes = response.encode_async().await.unwrap();
+        let output = String::from_utf8_lossy(&bytes);
+
+        assert!(output.contains("HTTP/1.1 404"));
+        assert!(output.contains("Not Found"));
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_response_sync_http10() {
+        let response = Response::builder()
+            .version(http::Version::HTTP_10)
+            .header("Date", "Sun, 06 Nov 1994 08:49:37 GMT")
+            .body(Full::new(Bytes::from("ok")))
+            .unwrap();
+
+        let bytes = response.encode().unwrap();
+        assert_eq!(
+            bytes,
+            "HTTP/1.0 200 OK\r\ndate: Sun, 06 Nov 1994 08:49:37 GMT\r\ncontent-length: 2\r\n\r\nok"
+        );
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_response_sync_single_use_body() {
+        // A stream built from an async block can't be cloned, like a body read off a
+        // connection
+        let body = http_body_util::StreamBody::new(futures::stream::once(async {
+            Ok::<_, std::convert::Infallible>(hyper::body::Frame::data(Bytes::from("once")))
+        }));
+        let response = Response::builder()
+            .header("Date", "Sun, 06 Nov 1994 08:49:37 GMT")
+            .body(body)
+            .unwrap();
+
+        let bytes = response.encode().unwrap();
+        assert_eq!(
+            bytes,
+            "HTTP/1.1 200 OK\r\ndate: Sun, 06 Nov 1994 08:49:37 GMT\r\ntransfer-encoding: chunked\r\n\r\n4\r\nonce\r\n0\r\n\r\n"
+        );
+    }
+
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_http2_response_rejected() {
+        let response = Response::builder()
+            .status(200)
+            .version(http::Version::HTTP_2)
+            .body(Full::new(Bytes::from("Hello")))
+            .unwrap();
+
+        let result = response.encode_async().await;
+        assert!(matches!(result, Err(WireError::UnsupportedVersion)));
+    }
+
+    #[test]
+    fn test_decode_response_no_body() {
+        let raw = b"HTTP/1.1 204 No Content\r\nServer: test\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode(raw, &mut headers);
+        assert!(result.is_ok());
+        let (response, len) = result.unwrap();
+        assert_eq!(response.head.code, Some(204));
+        assert_eq!(len, raw.len());
+        assert_eq!(response.body.len(), 0);
+    }
+
+    #[test]
+    fn test_decode_bodiless_status_ignores_content_length() {
+        // The bytes after the head belong to the next response, not to a body
+        let raw = b"HTTP/1.1 304 Not Modified\r\nContent-Length: 21\r\n\r\nHTTP/1.1 200 OK\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(response.head.code, Some(304));
+        assert_eq!(response.body, b"");
+        assert_eq!(len, 49);
+
+        // No body bytes are waited for
+        let raw = b"HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nContent-Length: 1234\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!((response.body, len), (&b""[..], raw.len()));
+
+        let raw = b"HTTP/1.1 204 No Content\r\nContent-Length: 5\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!((response.body, len), (&b""[..], raw.len() - 5));
+    }
+
+    #[test]
+    fn test_decode_response_with_content_length() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode(raw, &mut headers);
+        assert!(result.is_ok());
+        let (response, len) = result.unwrap();
+        assert_eq!(response.head.code, Some(200));
+        assert_eq!(len, raw.len());
+        assert_eq!(response.body, b"hello");
+    }
+
+    #[test]
+    fn test_decode_response_incomplete_body() {
+        // Content-Length says 10, but body is only 5 bytes
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode(raw, &mut headers);
+        assert!(matches!(result, Err(WireError::IncompleteBody(_))));
+    }
+
+    #[test]
+    fn test_decode_response_content_length_overflow() {
+        // Would wrap to 5 with unchecked arithmetic on 64-bit targets
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 18446744073709551621\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode(raw, &mut headers);
+        assert_eq!(result.err(), Some(WireError::InvalidContentLength));
+    }
+
+    #[test]
+    fn test_decode_response_conflicting_framing() {
+        for raw in [
+            &b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nContent-Length: 5\r\n\r\n5\r\nhello\r\n0\r\n\r\n"[..],
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\nContent-Length: 5\r\n\r\nhello",
+        ] {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let result = FullResponse::decode(raw, &mut headers);
+            assert_eq!(result.err(), Some(WireError::ConflictingFraming));
+        }
+
+        // A status without a body is not framed by either header
+        let raw =
+            b"HTTP/1.1 304 Not Modified\r\nTransfer-Encoding: chunked\r\nContent-Length: 5\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert_eq!(
+            FullResponse::decode(raw, &mut headers).unwrap().1,
+            raw.len()
+        );
+    }
+
+    #[test]
+    fn test_decode_response_duplicate_content_length() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\nContent-Length: 5\r\n\r\nhello!";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode(raw, &mut headers);
+        assert_eq!(result.err(), Some(WireError::DuplicateContentLength));
+    }
+
+    #[test]
+    fn test_decode_response_head_limit() {
+        let raw = b"HTTP/1.1 200 OK\r\nX-Filler: aaaaaaaaaaaaaaaa";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode_with_limits(raw, &mut headers, 32);
+        assert_eq!(result.err(), Some(WireError::HeadTooLarge));
+
+        let limits = Limits {
+            max_head_bytes: 32,
+            ..Limits::default()
+        };
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode_limited(raw, &mut headers, &limits);
+        assert_eq!(result.err(), Some(WireError::HeadTooLarge));
+    }
+
+    #[test]
+    fn test_decode_vec_response() {
+        let mut raw = b"HTTP/1.1 200 OK\r\n".to_vec();
+        for i in 0..40 {
+            raw.extend_from_slice(format!("Set-Cookie: c{i}=1\r\n").as_bytes());
+        }
+        raw.extend_from_slice(b"Content-Length: 2\r\n\r\nok");
+
+        let len = raw.len();
+        let (response, total) = FullResponse::decode_vec(Bytes::from(raw)).unwrap();
+        assert_eq!(response.headers.len(), 41);
+        assert_eq!(response.body, "ok");
+        assert_eq!(total, len);
+    }
+
+    #[test]
+    fn test_decode_response_close_delimited() {
+        let options = DecodeOptions::new().assume_close_delimited(true);
+        let raw = b"HTTP/1.1 200 OK\r\nServer: old\r\n\r\nsome body";
+
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) =
+            FullResponse::decode_with_options(raw, &mut headers, &options).unwrap();
+        assert_eq!(response.body, b"some body");
+        assert_eq!(len, raw.len());
+
+        // Without the option the body is empty
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert!(response.body.is_empty());
+        assert_eq!(len, raw.len() - 9);
 
-        assert!(output.contains("HTTP/1.1 404"));
-        assert!(output.contains("Not Found"));
-    }
+        // Framing headers and bodiless statuses still take precedence
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nsomeHTTP/1.1";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode_with_options(raw, &mut headers, &options).unwrap();
+        assert_eq!(response.body, b"some");
+        let raw = b"HTTP/1.1 204 No Content\r\n\r\nHTTP/1.1";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode_with_options(raw, &mut headers, &options).unwrap();
+        assert!(response.body.is_empty());
 
-    #[tokio::test]
-    async fn test_http2_response_rejected() {
-        let response = Response::builder()
-            .status(200)
-            .version(http::Version::HTTP_2)
-            .body(Full::new(Bytes::from("Hello")))
-            .unwrap();
+        // A coding that does not end in chunked is only read until close with the
+        // option, and never when chunked comes before it
+        for coding in ["gzip", "chunked, gzip", "gzip, chunked, br"] {
+            let raw = format!("HTTP/1.1 200 OK\r\nTransfer-Encoding: {coding}\r\n\r\nsome body");
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let result = FullResponse::decode(raw.as_bytes(), &mut headers);
+            assert_eq!(
+                result.err(),
+                Some(WireError::UnsupportedTransferCoding),
+                "{coding}"
+            );
 
-        let result = response.encode_async().await;
-        assert!(matches!(result, Err(WireError::UnsupportedVersion)));
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let result = FullResponse::decode_with_options(raw.as_bytes(), &mut headers, &options);
+            if coding == "gzip" {
+                let (response, len) = result.unwrap();
+                assert_eq!(response.body, b"some body");
+                assert_eq!(len, raw.len());
+            } else {
+                assert_eq!(
+                    result.err(),
+                    Some(WireError::UnsupportedTransferCoding),
+                    "{coding}"
+                );
+            }
+        }
     }
 
     #[test]
-    fn test_decode_response_no_body() {
-        let raw = b"HTTP/1.1 204 No Content\r\nServer: test\r\n\r\n";
+    fn test_decode_response_content_length_field() {
         let mut headers = [httparse::EMPTY_HEADER; 16];
-        let result = FullResponse::decode(raw, &mut headers);
-        assert!(result.is_ok());
-        let (response, len) = result.unwrap();
-        assert_eq!(response.head.code, Some(204));
-        assert_eq!(len, raw.len());
-        assert_eq!(response.body.len(), 0);
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(response.content_length, Some(2));
+        assert_eq!(response.content_length, Some(response.body.len()));
+
+        // A 304 repeats the length of the representation but has no body
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let raw = b"HTTP/1.1 304 Not Modified\r\nContent-Length: 2\r\n\r\n";
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(response.content_length, None);
     }
 
     #[test]
-    fn test_decode_response_with_content_length() {
-        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+    fn test_decode_response_malformed_offset() {
         let mut headers = [httparse::EMPTY_HEADER; 16];
-        let result = FullResponse::decode(raw, &mut headers);
-        assert!(result.is_ok());
-        let (response, len) = result.unwrap();
-        assert_eq!(response.head.code, Some(200));
-        assert_eq!(len, raw.len());
-        assert_eq!(response.body, b"hello");
+        assert_eq!(
+            FullResponse::decode(b"HTTP/1.1 2x0 OK\r\n\r\n", &mut headers).err(),
+            Some(WireError::Malformed {
+                offset: 10,
+                reason: "invalid response status"
+            })
+        );
+
+        let mut headers = [MaybeUninit::uninit(); 16];
+        assert_eq!(
+            FullResponse::decode_uninit(b"HTTP/1.1 200 OK\r\nA\r\n\r\n", &mut headers).err(),
+            Some(WireError::Malformed {
+                offset: 18,
+                reason: "invalid header name"
+            })
+        );
     }
 
     #[test]
-    fn test_decode_response_incomplete_body() {
-        // Content-Length says 10, but body is only 5 bytes
-        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nhello";
+    fn test_to_wire_round_trip() {
+        let raw = b"HTTP/1.0 200 \xC0K\r\nTransfer-Encoding: chunked\r\nSet-Cookie: a=1\r\nSet-Cookie: b=2\r\n\r\n2\r\nhi\r\n0\r\nX-Sum: 1\r\n\r\n";
         let mut headers = [httparse::EMPTY_HEADER; 16];
-        let result = FullResponse::decode(raw, &mut headers);
-        assert!(matches!(result, Err(WireError::IncompleteBody(_))));
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(response.to_wire(), raw);
+
+        // Value whitespace and bare LF are not kept
+        let raw = b"HTTP/1.1 204 No Content\nServer:   x  \n\n";
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(
+            response.to_wire(),
+            b"HTTP/1.1 204 No Content\r\nServer: x\r\n\r\n"
+        );
     }
 
     #[test]
@@ -446,7 +1758,9 @@ mod tests {
         let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n";
         let mut headers = [httparse::EMPTY_HEADER; 16];
         let result = FullResponse::decode(raw, &mut headers);
-        assert!(matches!(result, Err(WireError::InvalidChunkedBody)));
+        assert!(
+            matches!(result, Err(WireError::InvalidChunkedBody { offset }) if offset == raw.len())
+        );
     }
 
     #[test]
@@ -499,6 +1813,69 @@ mod tests {
         assert_eq!(response.body.len(), 0);
     }
 
+    #[test]
+    fn test_decode_response_obsolete_folding() {
+        let raw = b"HTTP/1.1 200 OK\r\nX-Foo: a\r\n  b\r\nContent-Length: 0\r\n\r\n";
+        let options = DecodeOptions::new().strict(true);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode_with_options(raw, &mut headers, &options);
+        assert_eq!(
+            result.err(),
+            Some(WireError::ObsoleteFolding { offset: 27 })
+        );
+    }
+
+    #[test]
+    fn test_decode_for_method() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHTTP/1.1 404 Not Found\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) = FullResponse::decode_for_method(raw, &mut headers, "HEAD").unwrap();
+        assert_eq!(response.content_length, Some(5));
+        assert_eq!(response.body, b"");
+        assert_eq!(len, 38);
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 1234\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode_for_method(raw, &mut headers, "GET");
+        assert_eq!(result.err(), Some(WireError::IncompleteBody(1234)));
+
+        // A chunked response to HEAD has no chunks either
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) = FullResponse::decode_for_method(raw, &mut headers, "HEAD").unwrap();
+        assert_eq!((response.body, len), (&b""[..], raw.len()));
+
+        // The framing headers are still validated
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 1\r\nContent-Length: 2\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode_for_method(raw, &mut headers, "HEAD");
+        assert_eq!(result.err(), Some(WireError::DuplicateContentLength));
+    }
+
+    #[test]
+    fn test_decode_interim_responses() {
+        let raw = b"HTTP/1.1 100 Continue\r\n\r\n\
+            HTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
+        let mut rest = &raw[..];
+        let mut interim = Vec::new();
+
+        let (code, body) = loop {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let (response, len) = FullResponse::decode(rest, &mut headers).unwrap();
+            rest = &rest[len..];
+            if !response.is_informational() {
+                break (response.head.code, response.body);
+            }
+            assert!(response.body.is_empty());
+            interim.push(response.head.code.unwrap());
+        };
+
+        assert_eq!(interim, [100, 103]);
+        assert_eq!((code, body), (Some(200), &b"hello"[..]));
+        assert!(rest.is_empty());
+    }
+
     #[test]
     fn test_full_response_fields_access() {
         // Test completo che accede a tutti i campi della FullResponse
@@ -528,14 +1905,546 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(
-        expected = "decode_uninit is not available for this type due to missing parse_with_uninit_headers method"
-    )]
-    fn test_decode_response_uninit_panics() {
-        // FullResponse does not support decode_uninit because httparse::Response
-        // does not have parse_with_uninit_headers method
+    fn test_encode_continue_roundtrip() {
+        let bytes = encode_continue();
+        let mut headers = [httparse::EMPTY_HEADER; 4];
+        let (response, len) = FullResponse::decode(&bytes, &mut headers).unwrap();
+        assert_eq!(response.head.code, Some(100));
+        assert_eq!(len, bytes.len());
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_head_response_omits_body() {
+        let response = Response::builder()
+            .status(200)
+            .header("Content-Type", "text/plain")
+            .body(Full::new(Bytes::from("Hello World")))
+            .unwrap();
+
+        let bytes = encode_head_response(response).unwrap();
+        let output = String::from_utf8_lossy(&bytes);
+
+        assert!(output.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(output.contains("content-length: 11\r\n"));
+        assert!(output.ends_with("\r\n\r\n"));
+        assert!(!output.contains("Hello World"));
+    }
+
+    #[test]
+    fn test_peek_status() {
+        assert_eq!(peek_status(b"HTTP/1.1 200 OK\r\n\r\n"), Some(200));
+        assert_eq!(peek_status(b"HTTP/1.0 404 Not Found\r\n"), Some(404));
+        assert_eq!(peek_status(b"HTTP/1.1 204\r\n"), Some(204));
+        // Incomplete status line
+        assert_eq!(peek_status(b"HTTP/1.1 20"), None);
+        assert_eq!(peek_status(b"HTTP/1.1 200"), None);
+        // Malformed status line
+        assert_eq!(peek_status(b"HTTP/2.0 200 OK\r\n"), None);
+        assert_eq!(peek_status(b"HTTP/1.1 2x0 OK\r\n"), None);
+        assert_eq!(peek_status(b"HTTP/1.1 2000 OK\r\n"), None);
+        assert_eq!(peek_status(b"GET / HTTP/1.1\r\n"), None);
+    }
+
+    #[test]
+    fn test_decode_response_chunked_trailers_end_of_buffer() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\ngrpc-status: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(len, raw.len());
+        assert!(response.body.ends_with(b"grpc-status: 0\r\n\r\n"));
+
+        // A single-byte trailer block is the shortest case the scan must catch
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n0\r\nX:\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (_, len) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(len, raw.len());
+    }
+
+    #[test]
+    fn test_decode_owned_shares_buffer() {
+        let buf = Bytes::from_static(
+            b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\nContent-Length: 9\r\n\r\nNot Found",
+        );
+        let (response, len) = FullResponse::decode_owned(buf.clone()).unwrap();
+
+        assert_eq!(len, buf.len());
+        assert_eq!(response.version, 1);
+        assert_eq!(response.code, 404);
+        assert_eq!(response.reason, "Not Found");
+        assert_eq!(response.headers.len(), 2);
+        assert_eq!(response.headers[0].name, "Content-Type");
+        assert_eq!(response.header("content-type").unwrap(), "text/plain");
+        assert_eq!(response.body, "Not Found");
+
+        // The body is a view into the original allocation
+        let range = buf.as_ptr_range();
+        assert!(range.contains(&response.body.as_ptr()));
+    }
+
+    #[test]
+    fn test_decode_owned_keeps_non_utf8_reason() {
+        let buf = Bytes::from_static(b"HTTP/1.1 200 \xC0K\r\nContent-Length: 0\r\n\r\n");
+        let (response, _) = FullResponse::decode_owned(buf.clone()).unwrap();
+        assert_eq!(response.reason, &b"\xC0K"[..]);
+        assert!(buf.as_ptr_range().contains(&response.reason.as_ptr()));
+    }
+
+    #[test]
+    fn test_decode_owned_incomplete() {
+        let buf = Bytes::from_static(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhel");
+        let result = FullResponse::decode_owned(buf);
+        assert!(matches!(result, Err(WireError::IncompleteBody(2))));
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn test_body_utf8_transcodes() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=\"windows-1252\"\r\nContent-Length: 6\r\n\r\n\x93hi\x94 \x80";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(response.body_utf8().unwrap(), "\u{201c}hi\u{201d} \u{20ac}");
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-16le\r\nTransfer-Encoding: chunked\r\n\r\n4\r\nh\x00i\x00\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(response.body_utf8().unwrap(), "hi");
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn test_body_utf8_errors() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=klingon\r\nContent-Length: 2\r\n\r\nhi";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert!(matches!(
+            response.body_utf8(),
+            Err(WireError::UnsupportedCharset(label)) if label == "klingon"
+        ));
+
+        // No charset: the body must be valid UTF-8
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n\xff\xfe";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert!(matches!(
+            response.body_utf8(),
+            Err(WireError::InvalidText(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_switching_protocols() {
+        let mut extra = http::HeaderMap::new();
+        extra.insert(
+            "sec-websocket-accept",
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=".parse().unwrap(),
+        );
+        extra.insert("content-length", "0".parse().unwrap());
+        extra.insert("upgrade", "ignored".parse().unwrap());
+
+        let bytes = encode_switching_protocols("websocket", &extra);
+        assert_eq!(
+            &bytes[..],
+            b"HTTP/1.1 101 Switching Protocols\r\n\
+              connection: upgrade\r\n\
+              upgrade: websocket\r\n\
+              sec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+              \r\n"
+        );
+
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, len) = FullResponse::decode(&bytes, &mut headers).unwrap();
+        assert_eq!(response.head.code, Some(101));
+        assert_eq!(len, bytes.len());
+        assert!(response.body.is_empty());
+    }
+
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_write_switching_protocols_returns_writer() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        let mut client = write_switching_protocols(client, "h2c", &http::HeaderMap::new())
+            .await
+            .unwrap();
+        client.write_all(b"PRI * HTTP/2.0").await.unwrap();
+        drop(client);
+
+        let mut received = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut server, &mut received)
+            .await
+            .unwrap();
+        assert_eq!(
+            received,
+            b"HTTP/1.1 101 Switching Protocols\r\nconnection: upgrade\r\nupgrade: h2c\r\n\r\nPRI * HTTP/2.0"
+        );
+    }
+
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_write_to_matches_encode() {
+        let response = || {
+            http::Response::builder()
+                .header("Date", "Sun, 06 Nov 1994 08:49:37 GMT")
+                .header("Content-Type", "text/plain")
+                .body(Full::new(Bytes::from("hello")))
+                .unwrap()
+        };
+
+        let mut out = Vec::new();
+        let written = write_to(response(), &mut out).await.unwrap();
+        assert_eq!(written, out.len());
+        assert_eq!(out, response().encode_async().await.unwrap());
+    }
+
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_write_to_streams_chunks_and_trailers() {
+        let mut trailers = http::HeaderMap::new();
+        trailers.insert("x-checksum", "abc".parse().unwrap());
+        trailers.insert("x-undeclared", "1".parse().unwrap());
+        let frames = vec![
+            hyper::body::Frame::data(Bytes::from("hello")),
+            hyper::body::Frame::data(Bytes::from(" world!")),
+            hyper::body::Frame::trailers(trailers),
+        ];
+        let body = http_body_util::StreamBody::new(futures::stream::iter(
+            frames.into_iter().map(Ok::<_, std::convert::Infallible>),
+        ));
+        let response = http::Response::builder()
+            .header("Date", "Sun, 06 Nov 1994 08:49:37 GMT")
+            .header("Trailer", "X-Checksum")
+            .body(body)
+            .unwrap();
+
+        let (mut writer, mut reader) = tokio::io::duplex(1024);
+        write_to(response, &mut writer).await.unwrap();
+        drop(writer);
+
+        let mut received = Vec::new();
+        tokio::io::AsyncReadExt::read_to_end(&mut reader, &mut received)
+            .await
+            .unwrap();
+        assert_eq!(
+            received,
+            b"HTTP/1.1 200 OK\r\ndate: Sun, 06 Nov 1994 08:49:37 GMT\r\ntrailer: X-Checksum\r\ntransfer-encoding: chunked\r\n\r\n5\r\nhello\r\n7\r\n world!\r\n0\r\nx-checksum: abc\r\n\r\n"
+        );
+    }
+
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_write_to_stops_at_content_length() {
+        // The failing frame after the declared length is never read
+        let frames = futures::stream::iter([
+            Ok(hyper::body::Frame::data(Bytes::from("abcdef"))),
+            Err(std::io::Error::other("not read")),
+        ]);
+        let response = http::Response::builder()
+            .header("Content-Length", "3")
+            .body(http_body_util::StreamBody::new(frames))
+            .unwrap();
+
+        let mut out = Vec::new();
+        write_to(response, &mut out).await.unwrap();
+        assert!(out.ends_with(b"\r\n\r\nabc"));
+
+        let response =
+            http::Response::new(http_body_util::StreamBody::new(futures::stream::iter([
+                Err::<hyper::body::Frame<Bytes>, _>(std::io::Error::other("broken")),
+            ])));
+        assert!(matches!(
+            write_to(response, &mut Vec::new()).await,
+            Err(WireError::Connection(_))
+        ));
+    }
+
+    #[test]
+    fn test_content_range() {
+        let raw = b"HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */2048\r\nContent-Length: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        let range = response.content_range().unwrap().unwrap();
+        assert!(range.is_unsatisfied());
+        assert_eq!(range.complete_length, Some(2048));
+
+        let raw = b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 9-3/10\r\nContent-Length: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert!(matches!(
+            response.content_range(),
+            Some(Err(WireError::InvalidContentRange))
+        ));
+
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert!(response.content_range().is_none());
+    }
+
+    #[test]
+    fn test_decode_with_options_max_chunks() {
+        let raw =
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\n1\r\nb\r\n0\r\n\r\n";
+        let options = DecodeOptions::new().max_chunks(1);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let result = FullResponse::decode_with_options(raw, &mut headers, &options);
+        assert!(matches!(result, Err(WireError::TooManyChunks { .. })));
+
+        // Without a limit the same response decodes
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) =
+            FullResponse::decode_with_options(raw, &mut headers, &DecodeOptions::new()).unwrap();
+        assert_eq!(response.body, b"1\r\na\r\n1\r\nb\r\n0\r\n\r\n");
+    }
+
+    #[test]
+    fn test_header_map() {
+        let raw = b"HTTP/1.1 200 OK\r\nSet-Cookie: a=1\r\nX-Id: 7\r\nset-cookie: b=2\r\nContent-Length: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+
+        let map = response.header_map().unwrap();
+        assert_eq!(map.len(), 4);
+        let cookies: Vec<_> = map.get_all(http::header::SET_COOKIE).iter().collect();
+        assert_eq!(cookies, ["a=1", "b=2"]);
+        assert_eq!(map["x-id"], "7");
+        assert_eq!(map, *response.into_http().unwrap().headers());
+    }
+
+    #[test]
+    fn test_security_headers() {
+        let raw = b"HTTP/1.1 200 OK\r\n\
+            Strict-Transport-Security: max-age=300; preload\r\n\
+            Content-Security-Policy: default-src 'self'\r\n\
+            content-security-policy: img-src *\r\n\
+            X-Frame-Options: SAMEORIGIN\r\n\
+            Content-Length: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+
+        let hsts = response.strict_transport_security().unwrap().unwrap();
+        assert_eq!(hsts.max_age, 300);
+        assert!(hsts.preload && !hsts.include_subdomains);
+        assert_eq!(
+            response.content_security_policy().as_deref(),
+            Some("default-src 'self', img-src *")
+        );
+        assert_eq!(response.x_frame_options(), Some(XFrameOptions::SameOrigin));
+
+        let raw =
+            b"HTTP/1.1 200 OK\r\nStrict-Transport-Security: preload\r\nContent-Length: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert!(matches!(
+            response.strict_transport_security(),
+            Some(Err(WireError::InvalidStrictTransportSecurity))
+        ));
+        assert!(response.content_security_policy().is_none());
+        assert!(response.x_frame_options().is_none());
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_async_without_tokio() {
+        let response = Response::new(Full::new(Bytes::from("hello")));
+        let bytes = futures::executor::block_on(response.encode_async()).unwrap();
+        let output = String::from_utf8_lossy(&bytes);
+        assert!(output.starts_with("HTTP/1.1 200 OK\r\ncontent-length: 5\r\ndate: "));
+        assert!(output.ends_with(" GMT\r\n\r\nhello"));
+    }
+
+    #[cfg(feature = "encode")]
+    #[tokio::test]
+    async fn test_encode_sized_body_inside_runtime() {
+        // A sized body is encoded without blocking on the shared runtime, which would
+        // panic inside this one
+        let response = Response::builder()
+            .header("Date", "Sun, 06 Nov 1994 08:49:37 GMT")
+            .body(Full::new(Bytes::from("hello")))
+            .unwrap();
+        assert_eq!(
+            response.encode().unwrap(),
+            "HTTP/1.1 200 OK\r\ndate: Sun, 06 Nov 1994 08:49:37 GMT\r\ncontent-length: 5\r\n\r\nhello"
+        );
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_large_body() {
+        let body = Bytes::from(vec![b'x'; 4 * 1024 * 1024]);
+        let response = Response::new(Full::new(body.clone()));
+        let bytes = response.encode().unwrap();
+
+        let head_end = find_head_end(&bytes).unwrap();
+        assert!(bytes[..head_end].ends_with(b"\r\n\r\n"));
+        assert_eq!(&bytes[head_end..], &body[..]);
+    }
+
+    #[test]
+    fn test_reason_bytes() {
+        let cases: [(&[u8], &[u8]); 4] = [
+            (b"HTTP/1.1 404 Not Found\r\n\r\n", b"Not Found"),
+            (b"\r\nHTTP/1.1 200 Gr\xFC\xDFe\r\n\r\n", b"Gr\xFC\xDFe"),
+            (b"HTTP/1.1 204 \r\n\r\n", b""),
+            (b"HTTP/1.0 304\n\n", b""),
+        ];
+        for (raw, reason) in cases {
+            let mut headers = [httparse::EMPTY_HEADER; 4];
+            let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+            assert_eq!(response.reason_bytes(), Some(reason));
+        }
+    }
+
+    #[cfg(feature = "brotli")]
+    #[test]
+    fn test_decode_body_decompressed_chunked_brotli() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Encoding: br\r\nTransfer-Encoding: chunked\r\n\r\n\
+            4\r\n\x0b\x02\x80h\r\n5\r\nello\x03\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(&response.decode_body_decompressed().unwrap()[..], b"hello");
+        assert_eq!(
+            response.decode_body_decompressed_with_limit(4).err(),
+            Some(WireError::BodyTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_decode_body_decompressed_identity() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(&response.decode_body_decompressed().unwrap()[..], b"hello");
+    }
+
+    #[test]
+    fn test_decode_dechunked() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nab\r\n1\r\nc\r\n0\r\ngrpc-status: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, body, len) = FullResponse::decode_dechunked(raw, &mut headers).unwrap();
+        assert_eq!(body, b"abc");
+        assert_eq!(response.head.code, Some(200));
+        assert_eq!(len, raw.len());
+
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nab\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        assert!(matches!(
+            FullResponse::decode_dechunked(raw, &mut headers),
+            Err(WireError::IncompleteChunkedBody)
+        ));
+    }
+
+    #[test]
+    fn test_decode_uninit_matches_decode() {
+        let inputs: [&[u8]; 4] = [
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nX-Id: 1\r\n\r\nhelloextra",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n",
+            b"HTTP/1.0 304 Not Modified\r\nETag: \"x\"\r\n\r\n",
+            b"HTTP/1.1 404 \r\n\r\n",
+        ];
+
+        for raw in inputs {
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let (expected, expected_len) = FullResponse::decode(raw, &mut headers).unwrap();
+            let mut uninit = [const { MaybeUninit::uninit() }; 16];
+            let (response, len) = FullResponse::decode_uninit(raw, &mut uninit).unwrap();
+
+            assert_eq!(len, expected_len);
+            assert_eq!(response.head.code, expected.head.code);
+            assert_eq!(response.head.reason, expected.head.reason);
+            assert_eq!(response.head.version, expected.head.version);
+            assert_eq!(response.head.headers, expected.head.headers);
+            assert_eq!(response.body, expected.body);
+            assert_eq!(response.reason_bytes(), expected.reason_bytes());
+        }
+    }
+
+    #[test]
+    fn test_decode_uninit_errors() {
+        let mut headers = [const { MaybeUninit::uninit() }; 16];
+        assert!(matches!(
+            FullResponse::decode_uninit(b"HTTP/1.1 200 OK\r\nContent-Len", &mut headers),
+            Err(WireError::PartialHead)
+        ));
+
+        let mut headers = [const { MaybeUninit::uninit() }; 1];
+        assert!(matches!(
+            FullResponse::decode_uninit(b"HTTP/1.1 200 OK\r\nA: 1\r\nB: 2\r\n\r\n", &mut headers),
+            Err(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        ));
+    }
+
+    #[test]
+    fn test_trailers() {
+        let raw = b"HTTP/1.1 200 OK\r\nContent-Type: application/grpc\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\ngrpc-status: 0\r\ngrpc-message: done\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        let mut trailers = [httparse::EMPTY_HEADER; 4];
+        let trailers = response.trailers(&mut trailers).unwrap();
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(
+            (trailers[1].name, trailers[1].value),
+            ("grpc-message", &b"done"[..])
+        );
+
         let raw = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello";
-        let mut headers = [const { std::mem::MaybeUninit::uninit() }; 16];
-        let _result = FullResponse::decode_uninit(raw, &mut headers);
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        let mut trailers = [httparse::EMPTY_HEADER; 4];
+        assert!(response.trailers(&mut trailers).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_into_http() {
+        let raw = b"HTTP/1.0 404 Not Found\r\nX-Tag: a\r\nX-Tag: b\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        let response = response.into_http().unwrap();
+
+        assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+        assert_eq!(response.version(), http::Version::HTTP_10);
+        let tags: Vec<_> = response.headers().get_all("x-tag").iter().collect();
+        assert_eq!(tags, ["a", "b"]);
+        assert_eq!(response.body(), "abc");
+        assert!(
+            response
+                .extensions()
+                .get::<hyper::ext::ReasonPhrase>()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_into_http_invalid_status() {
+        // httparse accepts any three digits, the conversion only 100..=599
+        for code in ["099", "600", "999"] {
+            let raw = format!("HTTP/1.1 {code} Odd\r\nContent-Length: 0\r\n\r\n");
+            let mut headers = [httparse::EMPTY_HEADER; 16];
+            let (response, _) = FullResponse::decode(raw.as_bytes(), &mut headers).unwrap();
+            assert!(
+                matches!(response.into_http(), Err(WireError::InvalidHttp(_))),
+                "{code}"
+            );
+        }
+
+        let raw = b"HTTP/1.1 599 Odd\r\nContent-Length: 0\r\n\r\n";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+        assert_eq!(response.into_http().unwrap().status(), 599);
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_into_http_round_trip() {
+        let raw = b"HTTP/1.1 200 Fine\r\nSet-Cookie: a=1\r\nDate: Sun, 06 Nov 1994 08:49:37 GMT\r\nSet-Cookie: b=2\r\nX-Request-ID: 7\r\nContent-Length: 5\r\n\r\nhello";
+        let mut headers = [httparse::EMPTY_HEADER; 16];
+        let (response, _) = FullResponse::decode(raw, &mut headers).unwrap();
+
+        let encoded = response
+            .into_http()
+            .unwrap()
+            .map(Full::new)
+            .encode()
+            .unwrap();
+        assert_eq!(encoded, &raw[..]);
     }
 }
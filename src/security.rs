@@ -0,0 +1,176 @@
+//! Typed security-related headers.
+//!
+//! This module provides [`StrictTransportSecurity`] and [`XFrameOptions`], used by the
+//! security header accessors on [`FullResponse`](crate::response::FullResponse).
+
+use crate::error::WireError;
+
+/// A parsed `Strict-Transport-Security` header value (RFC 6797 §6.1).
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::StrictTransportSecurity;
+///
+/// let hsts = StrictTransportSecurity::parse(b"max-age=31536000; includeSubDomains; preload").unwrap();
+/// assert_eq!(hsts.max_age, 31536000);
+/// assert!(hsts.include_subdomains);
+/// assert!(hsts.preload);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrictTransportSecurity {
+    /// `max-age`, in seconds. Zero tells the browser to forget the host.
+    pub max_age: u64,
+    /// Whether `includeSubDomains` is present.
+    pub include_subdomains: bool,
+    /// Whether `preload` is present (not part of RFC 6797, but widely used).
+    pub preload: bool,
+}
+
+impl StrictTransportSecurity {
+    /// Parses a `Strict-Transport-Security` field value.
+    ///
+    /// Directive names are case-insensitive and `max-age` may be quoted. Unknown
+    /// directives are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidStrictTransportSecurity`] if `max-age` is missing or
+    /// not a number, or if any directive appears more than once.
+    pub fn parse(value: &[u8]) -> Result<Self, WireError> {
+        let mut max_age = None;
+        let mut include_subdomains = false;
+        let mut preload = false;
+        let mut seen: Vec<&[u8]> = Vec::new();
+
+        for directive in value.split(|&b| b == b';').map(<[u8]>::trim_ascii) {
+            if directive.is_empty() {
+                continue;
+            }
+            let (name, arg) = match directive.iter().position(|&b| b == b'=') {
+                Some(eq) => (
+                    directive[..eq].trim_ascii(),
+                    Some(directive[eq + 1..].trim_ascii()),
+                ),
+                None => (directive, None),
+            };
+            if seen.iter().any(|s| s.eq_ignore_ascii_case(name)) {
+                return Err(WireError::InvalidStrictTransportSecurity);
+            }
+            seen.push(name);
+
+            if name.eq_ignore_ascii_case(b"max-age") {
+                let arg = arg.ok_or(WireError::InvalidStrictTransportSecurity)?;
+                let arg = arg
+                    .strip_prefix(b"\"")
+                    .and_then(|a| a.strip_suffix(b"\""))
+                    .unwrap_or(arg);
+                max_age = Some(parse_seconds(arg)?);
+            } else if name.eq_ignore_ascii_case(b"includeSubDomains") {
+                include_subdomains = true;
+            } else if name.eq_ignore_ascii_case(b"preload") {
+                preload = true;
+            }
+        }
+
+        Ok(Self {
+            max_age: max_age.ok_or(WireError::InvalidStrictTransportSecurity)?,
+            include_subdomains,
+            preload,
+        })
+    }
+}
+
+/// A parsed `X-Frame-Options` header value (RFC 7034).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum XFrameOptions {
+    /// `DENY`: the page may not be framed.
+    Deny,
+    /// `SAMEORIGIN`: the page may only be framed by the same origin.
+    SameOrigin,
+    /// Any other value, such as the obsolete `ALLOW-FROM`, as sent.
+    Other(String),
+}
+
+impl XFrameOptions {
+    /// Parses an `X-Frame-Options` field value, ignoring case and surrounding spaces.
+    pub fn parse(value: &[u8]) -> Self {
+        let value = value.trim_ascii();
+        if value.eq_ignore_ascii_case(b"DENY") {
+            XFrameOptions::Deny
+        } else if value.eq_ignore_ascii_case(b"SAMEORIGIN") {
+            XFrameOptions::SameOrigin
+        } else {
+            XFrameOptions::Other(String::from_utf8_lossy(value).into_owned())
+        }
+    }
+}
+
+/// Parses a non-empty run of ASCII digits into a `u64`.
+fn parse_seconds(digits: &[u8]) -> Result<u64, WireError> {
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(WireError::InvalidStrictTransportSecurity);
+    }
+    digits
+        .iter()
+        .try_fold(0u64, |acc, &d| {
+            acc.checked_mul(10)?.checked_add(u64::from(d - b'0'))
+        })
+        .ok_or(WireError::InvalidStrictTransportSecurity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hsts_parse() {
+        assert_eq!(
+            StrictTransportSecurity::parse(b"MAX-AGE=\"600\"").unwrap(),
+            StrictTransportSecurity {
+                max_age: 600,
+                include_subdomains: false,
+                preload: false,
+            }
+        );
+        let hsts =
+            StrictTransportSecurity::parse(b" includesubdomains ; max-age = 0 ; foo=bar").unwrap();
+        assert_eq!(hsts.max_age, 0);
+        assert!(hsts.include_subdomains);
+    }
+
+    #[test]
+    fn test_hsts_rejects_invalid() {
+        for value in [
+            &b""[..],
+            b"includeSubDomains",
+            b"max-age",
+            b"max-age=abc",
+            b"max-age=-1",
+            b"max-age=1; max-age=2",
+            b"max-age=1; preload; PRELOAD",
+        ] {
+            assert!(
+                matches!(
+                    StrictTransportSecurity::parse(value),
+                    Err(WireError::InvalidStrictTransportSecurity)
+                ),
+                "{}",
+                String::from_utf8_lossy(value)
+            );
+        }
+    }
+
+    #[test]
+    fn test_x_frame_options_parse() {
+        assert_eq!(XFrameOptions::parse(b"deny"), XFrameOptions::Deny);
+        assert_eq!(
+            XFrameOptions::parse(b" SameOrigin "),
+            XFrameOptions::SameOrigin
+        );
+        assert_eq!(
+            XFrameOptions::parse(b"ALLOW-FROM https://example.com"),
+            XFrameOptions::Other("ALLOW-FROM https://example.com".to_string())
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Reusable buffers for processing many messages.
+//!
+//! This module provides [`WireSession`], which keeps the header storage used for
+//! decoding and the output buffer used for encoding across calls, so a busy proxy
+//! does not allocate them for every message.
+
+use std::mem::MaybeUninit;
+
+#[cfg(feature = "encode")]
+use bytes::{Bytes, BytesMut};
+use httparse::Header;
+
+#[cfg(feature = "encode")]
+use crate::WireEncode;
+use crate::error::WireError;
+use crate::message::{Message, decode_message};
+use crate::owned::DEFAULT_MAX_HEADERS;
+
+/// Decoding and encoding state shared across the messages of a session.
+///
+/// Decoding uses a header array owned by the session instead of one provided by the
+/// caller. Encoding, with the `encode` feature, appends to an internal [`BytesMut`] and splits the message off as
+/// [`Bytes`]; once the caller drops it, its allocation is reclaimed for the following
+/// messages.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::{Message, WireSession};
+///
+/// let mut session = WireSession::new();
+/// let mut input: &[u8] = b"GET /a HTTP/1.1\r\nHost: x\r\n\r\nGET /b HTTP/1.1\r\nHost: x\r\n\r\n";
+///
+/// while !input.is_empty() {
+///     let (message, len) = session.decode_next(input).unwrap();
+///     let Message::Request(request) = message else { unreachable!() };
+///     println!("{}", request.head.path.unwrap());
+///     input = &input[len..];
+/// }
+///
+/// # #[cfg(feature = "encode")] {
+/// use bytes::Bytes;
+/// use http_body_util::Full;
+///
+/// let response = http::Response::new(Full::new(Bytes::from("ok")));
+/// let bytes = session.encode_response(response).unwrap();
+/// assert!(bytes.ends_with(b"\r\n\r\nok"));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct WireSession {
+    /// Header storage, borrowed anew by each decode.
+    headers: HeaderStorage,
+    /// Output buffer; each encoded message is split off its front.
+    #[cfg(feature = "encode")]
+    out: BytesMut,
+}
+
+impl WireSession {
+    /// Creates a session that decodes messages with up to 64 headers.
+    pub fn new() -> Self {
+        Self::with_max_headers(DEFAULT_MAX_HEADERS)
+    }
+
+    /// Creates a session that decodes messages with up to `max_headers` headers.
+    ///
+    /// Decoding a message with more headers fails with
+    /// [`WireError::HttparseError`].
+    pub fn with_max_headers(max_headers: usize) -> Self {
+        Self {
+            headers: HeaderStorage::new(max_headers),
+            #[cfg(feature = "encode")]
+            out: BytesMut::new(),
+        }
+    }
+
+    /// Decodes the request or response at the start of `input`.
+    ///
+    /// Works like [`decode_message`], with the session's header storage. Returns the
+    /// message and its total length, so the caller can advance past it. The message
+    /// borrows the session until it is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`decode_message`].
+    pub fn decode_next<'s, 'buf>(
+        &'s mut self,
+        input: &'buf [u8],
+    ) -> Result<(Message<'s, 'buf>, usize), WireError> {
+        decode_message(input, self.headers.slots())
+    }
+
+    /// Encodes a request like [`WireEncode::encode`](crate::WireEncode::encode),
+    /// reusing the session's output buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`WireEncode::encode`](crate::WireEncode::encode).
+    #[cfg(feature = "encode")]
+    pub fn encode_request<B>(&mut self, request: http::Request<B>) -> Result<Bytes, WireError>
+    where
+        B: http_body_util::BodyExt + Send + Sync + 'static,
+        B::Data: Send + Sync + 'static,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        request.encode_into(&mut self.out)?;
+        Ok(self.out.split().freeze())
+    }
+
+    /// Encodes a response like [`WireEncode::encode`](crate::WireEncode::encode),
+    /// reusing the session's output buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`WireEncode::encode`](crate::WireEncode::encode).
+    #[cfg(feature = "encode")]
+    pub fn encode_response<B>(&mut self, response: http::Response<B>) -> Result<Bytes, WireError>
+    where
+        B: hyper::body::Body + Send + Sync + 'static,
+        B::Data: Send + Sync + 'static,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        response.encode_into(&mut self.out)?;
+        Ok(self.out.split().freeze())
+    }
+}
+
+impl Default for WireSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Header slots owned by a decoder and lent out anew for each buffer it decodes.
+#[derive(Debug)]
+pub(crate) struct HeaderStorage(Vec<MaybeUninit<Header<'static>>>);
+
+impl HeaderStorage {
+    /// Creates storage for up to `max_headers` headers.
+    pub(crate) fn new(max_headers: usize) -> Self {
+        let mut headers = Vec::with_capacity(max_headers);
+        headers.resize_with(max_headers, MaybeUninit::uninit);
+        Self(headers)
+    }
+
+    /// Initializes the header storage and lends it out for a buffer of lifetime `'buf`.
+    pub(crate) fn slots<'buf>(&mut self) -> &mut [Header<'buf>] {
+        for slot in &mut self.0 {
+            slot.write(httparse::EMPTY_HEADER);
+        }
+        let slots: *mut [MaybeUninit<Header<'static>>] = self.0.as_mut_slice();
+        // SAFETY: every slot was just initialized with `EMPTY_HEADER`, which is a valid
+        // `Header<'buf>` for any `'buf`. The slots keep borrowing the decoded buffer
+        // after the returned slice is dropped, but being `MaybeUninit` they are never
+        // read again before being overwritten above.
+        unsafe { &mut *(slots as *mut [Header<'buf>]) }
+    }
+}
+
+impl Default for HeaderStorage {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_HEADERS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "encode")]
+    use crate::WireEncode;
+    #[cfg(feature = "encode")]
+    use http_body_util::Full;
+
+    #[test]
+    fn test_decode_next_reuses_headers_across_inputs() {
+        let mut session = WireSession::new();
+
+        for path in ["/first", "/second"] {
+            let input = format!("GET {path} HTTP/1.1\r\nHost: example.com\r\nX-Id: 1\r\n\r\n");
+            let (message, len) = session.decode_next(input.as_bytes()).unwrap();
+            let Message::Request(request) = message else {
+                panic!("expected a request");
+            };
+            assert_eq!(request.head.path, Some(path));
+            assert_eq!(request.head.headers.len(), 2);
+            assert_eq!(len, input.len());
+        }
+
+        let (message, _) = session
+            .decode_next(b"HTTP/1.1 204 No Content\r\n\r\n")
+            .unwrap();
+        assert!(matches!(message, Message::Response(_)));
+    }
+
+    #[test]
+    fn test_decode_next_max_headers() {
+        let mut session = WireSession::with_max_headers(1);
+        assert!(matches!(
+            session.decode_next(b"GET / HTTP/1.1\r\nA: 1\r\nB: 2\r\n\r\n"),
+            Err(WireError::HttparseError(httparse::Error::TooManyHeaders))
+        ));
+    }
+
+    #[cfg(feature = "encode")]
+    #[test]
+    fn test_encode_matches_and_reuses_buffer() {
+        let request = || {
+            http::Request::builder()
+                .method("POST")
+                .uri("/items")
+                .header("Host", "example.com")
+                .body(Full::new(Bytes::from("payload")))
+                .unwrap()
+        };
+
+        let mut session = WireSession::new();
+        let first = session.encode_request(request()).unwrap();
+        assert_eq!(first, request().encode().unwrap());
+
+        // Once the previous message is dropped, its allocation is reused
+        let ptr = first.as_ptr();
+        drop(first);
+        let second = session.encode_request(request()).unwrap();
+        assert_eq!(second.as_ptr(), ptr);
+    }
+}
@@ -1,142 +1,414 @@
-/// Fast usize parser (decimal).
-#[inline(always)]
-pub(crate) fn parse_usize(buf: &[u8]) -> Option<usize> {
-    let mut res: usize = 0;
-    let mut found = false;
-
-    for &b in buf {
-        if b.is_ascii_digit() {
-            // Check for overflow could be added here if needed,
-            // but wrapping is standard for "fast" parsing logic.
-            res = res.wrapping_mul(10).wrapping_add((b - b'0') as usize);
-            found = true;
-        } else if found {
-            // We were parsing numbers, now we hit a non-digit: stop.
-            break;
-        } else if b == b' ' || b == b'\t' {
-            // Skip leading whitespace
-            continue;
-        } else {
-            // Found a non-digit before any digit (e.g., letters)
-            return None;
-        }
-    }
+use alloc::vec;
+use alloc::vec::Vec;
+use bytes::BufMut;
 
-    if found { Some(res) } else { None }
-}
+use crate::chunked::{ChunkRules, walk_chunked};
+use crate::error::WireError;
 
-/// Returns the total length of the chunked body (including the final 0\r\n\r\n).
+/// Returns `true` for a token character (RFC 9110 §5.6.2), as used in method names.
 #[inline]
-pub(crate) fn parse_chunked_body(buf: &[u8]) -> Option<usize> {
-    let mut pos = 0;
-    let len = buf.len();
+pub(crate) fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
 
-    loop {
-        if pos >= len {
+/// Strict usize parser (decimal).
+///
+/// Every byte of `buf` must be a digit: no sign, whitespace or trailing characters
+/// are allowed. Returns `None` if `buf` is empty, holds anything
+/// else, or if the number overflows `usize`.
+#[inline]
+pub(crate) fn parse_usize_strict(buf: &[u8]) -> Option<usize> {
+    if buf.is_empty() {
+        return None;
+    }
+    buf.iter().try_fold(0usize, |res, &b| {
+        if !b.is_ascii_digit() {
             return None;
         }
+        res.checked_mul(10)?.checked_add((b - b'0') as usize)
+    })
+}
 
-        // Find CRLF fast.
-        // We only scan a limited window because chunk sizes are usually small strings.
-        let mut i = pos;
-        let mut found_crlf = false;
-
-        // Scan for LF. The max chunk size line usually isn't massive.
-        while i < len {
-            if buf[i] == b'\n' {
-                found_crlf = true;
-                break;
-            }
-            i += 1;
-        }
+/// Parses a `Content-Length` value with [`parse_usize_strict`].
+///
+/// The value must be a plain decimal number (RFC 9110 §8.6): anything else, such as
+/// `12abc` or `+12`, is an error rather than a length read from part of it, since a
+/// receiver that reads it differently from the sender would frame the body
+/// differently. So is a number too large for `usize`, rather than a wrapped length.
+pub(crate) fn parse_content_length(value: &[u8]) -> Result<usize, WireError> {
+    parse_usize_strict(value).ok_or(WireError::InvalidContentLength)
+}
 
-        if !found_crlf {
-            return None;
-        } // Incomplete chunk size line
+/// Converts an httparse error on the head at the start of `buf` into a [`WireError`].
+///
+/// Syntax errors become [`WireError::Malformed`]. Parsing stops at the offending byte,
+/// so the shortest prefix of `buf` for which `fails` returns `true` ends with it. Only
+/// the head, up to the first blank line, is searched, and its prefixes are parsed with
+/// one header slot per line, so they never run out of slots. Running out of header
+/// slots is not a syntax error and stays [`WireError::HttparseError`].
+pub(crate) fn head_error(
+    err: httparse::Error,
+    buf: &[u8],
+    fails: impl for<'a> Fn(&mut [httparse::Header<'a>], &'a [u8]) -> bool,
+) -> WireError {
+    let reason = match err {
+        httparse::Error::TooManyHeaders => return err.into(),
+        httparse::Error::HeaderName => "invalid header name",
+        httparse::Error::HeaderValue => "invalid header value",
+        httparse::Error::NewLine => "invalid new line",
+        httparse::Error::Status => "invalid response status",
+        httparse::Error::Token => "invalid token",
+        httparse::Error::Version => "invalid HTTP version",
+    };
 
-        // Parse hex from pos to i-1 (ignoring \r)
-        // i points to \n, i-1 should be \r.
-        if i == 0 || buf[i - 1] != b'\r' {
-            return None;
-        } // Invalid format
-
-        let hex_end = i - 1;
-        // Parse hex digits until semicolon (extension) or end of line
-        let mut chunk_size = 0usize;
-        for &b in &buf[pos..hex_end] {
-            if b == b';' {
-                break;
-            } // Ignore chunk extensions
-
-            let val = match b {
-                b'0'..=b'9' => b - b'0',
-                b'a'..=b'f' => b - b'a' + 10,
-                b'A'..=b'F' => b - b'A' + 10,
-                _ => continue, // Skip whitespace or invalid chars silently for speed
-            };
-
-            // Check overflow if necessary, but for HTTP chunks usize is usually enough
-            chunk_size = (chunk_size << 4) | (val as usize);
+    // The error is in the head, so neither the lines nor the search go past its end
+    let (lines, head_len) = head_lines(buf);
+    let buf = &buf[..head_len];
+    let mut slots = vec![httparse::EMPTY_HEADER; lines + 1];
+    // `buf[..valid]` parses as partial and `buf[..invalid]` fails
+    let (mut valid, mut invalid) = (0, buf.len());
+    while invalid - valid > 1 {
+        let mid = valid + (invalid - valid) / 2;
+        if fails(&mut slots, &buf[..mid]) {
+            invalid = mid;
+        } else {
+            valid = mid;
         }
+    }
+    WireError::Malformed {
+        offset: invalid - 1,
+        reason,
+    }
+}
 
-        // Move pos after the \n
-        pos = i + 1;
-
-        if chunk_size == 0 {
-            // Last chunk. Need to skip trailers and find final empty line (\r\n).
-            // Current pos is after "0\r\n".
-            // The simplest end is immediately "\r\n".
-            if pos + 2 <= len && &buf[pos..pos + 2] == b"\r\n" {
-                return Some(pos + 2);
-            }
-            // If there are trailers, we need to scan for \r\n\r\n
-            // Scanning for double CRLF
-            let mut k = pos;
-            while k + 3 < len {
-                if buf[k] == b'\r'
-                    && buf[k + 1] == b'\n'
-                    && buf[k + 2] == b'\r'
-                    && buf[k + 3] == b'\n'
-                {
-                    return Some(k + 4);
-                }
-                k += 1;
-            }
-            return None; // Incomplete trailers
+/// Counts the lines of the head at the start of `buf` in one forward scan.
+///
+/// Returns the number of `\n` up to the blank line that ends the head, which is at
+/// least the number of header lines, and the length of the head. If the head is
+/// incomplete, the whole of `buf` is counted.
+pub(crate) fn head_lines(buf: &[u8]) -> (usize, usize) {
+    let count_lf = |bytes: &[u8]| bytes.iter().filter(|&&b| b == b'\n').count();
+    match find_blank_line(buf, 0) {
+        // The `\n` of the blank line itself does not end a header line
+        Some(head_len) => (count_lf(&buf[..head_len]) - 1, head_len),
+        None => (count_lf(buf), buf.len()),
+    }
+}
+
+/// Returns the position after the first blank line (`\n\r\n` or `\n\n`) whose
+/// leading `\n` is at or after `from`.
+pub(crate) fn find_blank_line(buf: &[u8], from: usize) -> Option<usize> {
+    let mut pos = from;
+    while let Some(lf) = find_lf(buf, pos) {
+        match &buf[lf + 1..] {
+            [b'\n', ..] => return Some(lf + 2),
+            [b'\r', b'\n', ..] => return Some(lf + 3),
+            _ => pos = lf + 1,
         }
+    }
+    None
+}
+
+/// Returns the position of the first `\n` at or after `from`.
+pub(crate) fn find_lf(buf: &[u8], from: usize) -> Option<usize> {
+    buf.get(from..)?
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|lf| from + lf)
+}
 
-        // Check if full chunk is available: data (chunk_size) + CRLF (2)
-        let next_start = pos + chunk_size + 2;
-        if next_start > len {
-            return None; // Incomplete chunk data
+/// Reports a chunked body cut short at the end of a `len`-byte message as
+/// [`WireError::IncompleteChunkedBody`](crate::WireError::IncompleteChunkedBody).
+///
+/// The framing used by `decode` reports a missing end as an invalid body at offset
+/// `len`, see [`ChunkedError::at`](crate::chunked::ChunkedError::at).
+pub(crate) fn flag_incomplete_chunked(err: crate::WireError, len: usize) -> crate::WireError {
+    match err {
+        crate::WireError::InvalidChunkedBody { offset } if offset == len => {
+            crate::WireError::IncompleteChunkedBody
         }
-        pos = next_start;
+        err => err,
     }
 }
 
-/// Check for "chunked" case-insensitive.
+/// Returns `true` if the last coding of a `Transfer-Encoding` value is `chunked`.
+///
+/// Per RFC 9112 §6.1 a body is chunked only when `chunked` is the final coding, so
+/// `gzip, chunked` matches but `chunked, gzip` does not. Codings are compared ASCII
+/// case-insensitively and trimmed of whitespace; empty list elements are skipped.
 #[inline(always)]
 pub(crate) fn is_chunked_slice(buf: &[u8]) -> bool {
-    let mut start = 0;
-    while start < buf.len() && matches!(buf[start], b' ' | b'\t') {
-        start += 1;
+    buf.rsplit(|&b| b == b',')
+        .map(<[u8]>::trim_ascii)
+        .find(|coding| !coding.is_empty())
+        .is_some_and(|coding| coding.eq_ignore_ascii_case(b"chunked"))
+}
+
+/// Splits a complete chunked body into the data slices of its chunks, in order.
+///
+/// The terminating zero-size chunk is not included. Chunk extensions and trailers are
+/// discarded. The body is walked with the same rules as the framing used by `decode`,
+/// with lines allowed to end with a bare LF if `bare_lf` is set. If it is malformed
+/// or incomplete, returns the offset in `buf` where parsing stopped, which is
+/// `buf.len()` if it is incomplete.
+pub(crate) fn split_chunks(buf: &[u8], bare_lf: bool) -> Result<Vec<&[u8]>, usize> {
+    let rules = ChunkRules {
+        bare_lf,
+        ..ChunkRules::STRICT
+    };
+    let mut chunks = Vec::new();
+    walk_chunked(buf, rules, |_, _| {}, |chunk| chunks.push(chunk))
+        .map_err(|err| err.offset(buf.len()))?;
+    Ok(chunks)
+}
+
+/// Decodes a complete chunked body, returning the concatenated chunk data.
+///
+/// Chunk extensions and trailers are discarded. On failure, returns the offset where
+/// parsing stopped, as [`split_chunks`] does.
+pub(crate) fn dechunk_body(buf: &[u8], bare_lf: bool) -> Result<Vec<u8>, usize> {
+    split_chunks(buf, bare_lf).map(|chunks| chunks.concat())
+}
+
+/// Returns the value of the first header matching `name` (ASCII case-insensitive).
+#[inline]
+pub(crate) fn find_header<'buf>(
+    headers: &[httparse::Header<'buf>],
+    name: &str,
+) -> Option<&'buf [u8]> {
+    headers
+        .iter()
+        .find(|h| h.name.eq_ignore_ascii_case(name))
+        .map(|h| h.value)
+}
+
+/// Returns `true` if the last `Transfer-Encoding` header marks the body as chunked.
+///
+/// A repeated header continues the list of codings, so only the last one can end with
+/// the final coding.
+#[inline]
+pub(crate) fn has_chunked_encoding(headers: &[httparse::Header<'_>]) -> bool {
+    headers
+        .iter()
+        .rfind(|h| h.name.eq_ignore_ascii_case("Transfer-Encoding"))
+        .is_some_and(|h| is_chunked_slice(h.value))
+}
+
+/// Hop-by-hop headers defined by RFC 9110 §7.6.1, which a proxy must not forward.
+pub(crate) const HOP_BY_HOP_HEADERS: [&str; 6] = [
+    "Connection",
+    "Keep-Alive",
+    "TE",
+    "Trailer",
+    "Transfer-Encoding",
+    "Upgrade",
+];
+
+/// Iterates over the comma-separated tokens of all `Connection` headers, trimmed.
+pub(crate) fn connection_tokens<'buf>(
+    headers: &[httparse::Header<'buf>],
+) -> impl Iterator<Item = &'buf [u8]> {
+    headers
+        .iter()
+        .filter(|h| h.name.eq_ignore_ascii_case("Connection"))
+        .flat_map(|h| h.value.split(|&b| b == b','))
+        .map(|token| token.trim_ascii())
+        .filter(|token| !token.is_empty())
+}
+
+/// Returns `true` if `name` is a hop-by-hop header for a message with the given headers.
+///
+/// This covers the fixed hop-by-hop set, any `Proxy-*` header, and every header
+/// nominated by a `Connection` header.
+pub(crate) fn is_hop_by_hop(headers: &[httparse::Header<'_>], name: &str) -> bool {
+    let bytes = name.as_bytes();
+    HOP_BY_HOP_HEADERS
+        .iter()
+        .any(|h| name.eq_ignore_ascii_case(h))
+        || (bytes.len() >= 6 && bytes[..6].eq_ignore_ascii_case(b"proxy-"))
+        || connection_tokens(headers).any(|token| token.eq_ignore_ascii_case(bytes))
+}
+
+/// Iterates over the comma-separated codings of all `Transfer-Encoding` headers, in order.
+///
+/// Tokens are trimmed of surrounding whitespace; empty list elements are skipped.
+pub(crate) fn transfer_codings<'buf>(
+    headers: &[httparse::Header<'buf>],
+) -> impl Iterator<Item = &'buf [u8]> {
+    headers
+        .iter()
+        .filter(|h| h.name.eq_ignore_ascii_case("Transfer-Encoding"))
+        .flat_map(|h| h.value.split(|&b| b == b','))
+        .map(|coding| coding.trim_ascii())
+        .filter(|coding| !coding.is_empty())
+}
+
+/// Iterates over the comma-separated codings of all `Content-Encoding` headers, in order.
+///
+/// Tokens are trimmed of surrounding whitespace; empty list elements are skipped.
+#[cfg(feature = "std")]
+pub(crate) fn content_codings<'buf>(
+    headers: &[httparse::Header<'buf>],
+) -> impl Iterator<Item = &'buf [u8]> {
+    headers
+        .iter()
+        .filter(|h| h.name.eq_ignore_ascii_case("Content-Encoding"))
+        .flat_map(|h| h.value.split(|&b| b == b','))
+        .map(|coding| coding.trim_ascii())
+        .filter(|coding| !coding.is_empty())
+}
+
+/// Collects parsed headers into an [`http::HeaderMap`], in order.
+#[cfg(feature = "std")]
+pub(crate) fn header_map(
+    headers: &[httparse::Header<'_>],
+) -> Result<http::HeaderMap, crate::WireError> {
+    let mut map = http::HeaderMap::with_capacity(headers.len());
+    for header in headers {
+        let name =
+            http::HeaderName::from_bytes(header.name.as_bytes()).map_err(http::Error::from)?;
+        let value = http::HeaderValue::from_bytes(header.value).map_err(http::Error::from)?;
+        map.append(name, value);
     }
+    Ok(map)
+}
+
+/// Returns the length of the head including its terminating `\r\n\r\n`.
+#[cfg(feature = "encode")]
+#[inline]
+pub(crate) fn find_head_end(message: &[u8]) -> Option<usize> {
+    message
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
 
-    let mut end = buf.len();
-    while end > start && matches!(buf[end - 1], b' ' | b'\t' | b'\r' | b'\n') {
-        end -= 1;
+/// Returns the body with chunked framing removed, borrowing when it is not chunked.
+///
+/// With `bare_lf`, chunk lines may end with a bare LF, as accepted by
+/// [`DecodeOptions::allow_bare_lf`](crate::DecodeOptions::allow_bare_lf).
+pub(crate) fn unframed_body<'buf>(
+    headers: &[httparse::Header<'_>],
+    body: &'buf [u8],
+    bare_lf: bool,
+) -> Result<alloc::borrow::Cow<'buf, [u8]>, crate::WireError> {
+    if has_chunked_encoding(headers) {
+        dechunk_body(body, bare_lf)
+            .map(alloc::borrow::Cow::Owned)
+            .map_err(|offset| crate::WireError::InvalidChunkedBody { offset })
+    } else {
+        Ok(alloc::borrow::Cow::Borrowed(body))
     }
+}
+
+/// Returns the value of a `;`-separated parameter of a header value such as
+/// `Content-Type` (ASCII case-insensitive name), with surrounding quotes removed.
+#[cfg(feature = "encoding")]
+pub(crate) fn header_param<'a>(value: &'a [u8], param: &str) -> Option<&'a [u8]> {
+    value.split(|&b| b == b';').skip(1).find_map(|item| {
+        let eq = item.iter().position(|&b| b == b'=')?;
+        let (name, val) = (item[..eq].trim_ascii(), item[eq + 1..].trim_ascii());
+        if !name.eq_ignore_ascii_case(param.as_bytes()) {
+            return None;
+        }
+        match val {
+            [b'"', inner @ .., b'"'] => Some(inner),
+            _ => Some(val),
+        }
+    })
+}
+
+/// Combines all values of a named header into one, following RFC 9110 §5.3.
+///
+/// Values are joined with `, `, or with `; ` for `Cookie`. `Set-Cookie` cannot be
+/// combined, so only its first value is returned. A single value is borrowed.
+pub(crate) fn combine_header_values<'buf>(
+    headers: &[httparse::Header<'buf>],
+    name: &str,
+) -> Option<alloc::borrow::Cow<'buf, [u8]>> {
+    let mut values = headers
+        .iter()
+        .filter(|h| h.name.eq_ignore_ascii_case(name))
+        .map(|h| h.value.trim_ascii());
 
-    let sliced = &buf[start..end];
-    if sliced.len() != 7 {
-        return false;
+    let first = values.next()?;
+    if name.eq_ignore_ascii_case("Set-Cookie") {
+        return Some(alloc::borrow::Cow::Borrowed(first));
     }
 
-    (sliced[0] | 0x20) == b'c'
-        && (sliced[1] | 0x20) == b'h'
-        && (sliced[2] | 0x20) == b'u'
-        && (sliced[3] | 0x20) == b'n'
-        && (sliced[4] | 0x20) == b'k'
-        && (sliced[5] | 0x20) == b'e'
-        && (sliced[6] | 0x20) == b'd'
+    let Some(second) = values.next() else {
+        return Some(alloc::borrow::Cow::Borrowed(first));
+    };
+
+    let separator: &[u8] = if name.eq_ignore_ascii_case("Cookie") {
+        b"; "
+    } else {
+        b", "
+    };
+    let mut combined = first.to_vec();
+    for value in core::iter::once(second).chain(values) {
+        combined.extend_from_slice(separator);
+        combined.extend_from_slice(value);
+    }
+    Some(alloc::borrow::Cow::Owned(combined))
+}
+
+/// Returns the HTTP version token for an `httparse` minor version number.
+#[inline]
+pub(crate) fn version_token(minor: u8) -> &'static [u8] {
+    if minor == 0 { b"HTTP/1.0" } else { b"HTTP/1.1" }
+}
+
+/// Writes a request line (`METHOD target HTTP/1.x\r\n`).
+#[inline]
+pub(crate) fn write_request_line<B: BufMut>(buf: &mut B, method: &str, target: &str, minor: u8) {
+    buf.put_slice(method.as_bytes());
+    buf.put_u8(b' ');
+    buf.put_slice(target.as_bytes());
+    buf.put_u8(b' ');
+    buf.put_slice(version_token(minor));
+    buf.put_slice(b"\r\n");
+}
+
+/// Writes a status line (`HTTP/1.x code reason\r\n`), with the code as three digits.
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn write_status_line<B: BufMut>(buf: &mut B, minor: u8, code: u16, reason: &[u8]) {
+    buf.put_slice(version_token(minor));
+    buf.put_u8(b' ');
+    buf.put_u8(b'0' + (code / 100 % 10) as u8);
+    buf.put_u8(b'0' + (code / 10 % 10) as u8);
+    buf.put_u8(b'0' + (code % 10) as u8);
+    buf.put_u8(b' ');
+    buf.put_slice(reason);
+    buf.put_slice(b"\r\n");
+}
+
+/// Returns the number of bytes [`write_parsed_headers`] writes for `headers`.
+#[inline]
+pub(crate) fn parsed_headers_len(headers: &[httparse::Header<'_>]) -> usize {
+    headers
+        .iter()
+        .map(|h| h.name.len() + h.value.len() + 4)
+        .sum::<usize>()
+        + 2
+}
+
+/// Writes decoded headers in their original order and casing, then the blank line
+/// that ends the head.
+#[inline]
+pub(crate) fn write_parsed_headers<B: BufMut>(buf: &mut B, headers: &[httparse::Header<'_>]) {
+    for header in headers {
+        write_header(buf, header.name.as_bytes(), header.value);
+    }
+    buf.put_slice(b"\r\n");
+}
+
+/// Writes a single header line (`name: value\r\n`) without any validation.
+#[inline]
+pub(crate) fn write_header<B: BufMut>(buf: &mut B, name: &[u8], value: &[u8]) {
+    buf.put_slice(name);
+    buf.put_slice(b": ");
+    buf.put_slice(value);
+    buf.put_slice(b"\r\n");
 }
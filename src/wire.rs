@@ -1,57 +1,1539 @@
+//! Direct HTTP/1.x serialization.
+//!
+//! [`serialize_request`] and [`serialize_response`] write a message with an in-memory
+//! body straight into a [`BytesMut`]. They are the synchronous core of the encoding
+//! traits, which read the message body first and then serialize it the same way.
+//!
+//! The output buffer grows as needed, so bodies of any size are written in full and
+//! there is no intermediate channel whose capacity would need tuning.
+//!
+//! [`RequestBuilder`] writes a request from plain strings and bytes, without building
+//! an [`http::Request`] first, which is handy for test fixtures.
+
 use std::{
-    io,
-    pin::Pin,
-    sync::Arc,
-    task::{Context, Poll},
+    collections::HashMap,
+    future::Future,
+    sync::OnceLock,
+    task::{Context, Poll, Waker},
+    time::SystemTime,
+};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::header::{CONTENT_LENGTH, DATE, HeaderName, TRAILER, TRANSFER_ENCODING};
+use http::{HeaderMap, HeaderValue};
+use std::fmt::Write as _;
+use tokio::runtime::Runtime;
+
+use crate::error::WireError;
+use crate::util::{
+    find_head_end, is_chunked_slice, version_token, write_header, write_request_line,
 };
 
-use parking_lot::Mutex;
-use tokio::io::{AsyncRead, AsyncWrite};
+/// Runtime shared by all synchronous encode calls.
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// Runs a future to completion on the shared single-threaded runtime.
+///
+/// The runtime is created on first use and reused afterwards, so synchronous
+/// encoding does not pay the runtime setup cost on every call. Like any
+/// `block_on`, this panics if called from within an async runtime.
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output, WireError> {
+    if let Some(rt) = RUNTIME.get() {
+        return Ok(rt.block_on(future));
+    }
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| WireError::Connection(Box::new(e)))?;
+
+    // If another thread won the race, our runtime is simply dropped
+    Ok(RUNTIME.get_or_init(|| rt).block_on(future))
+}
+
+/// Runs an encode future to completion, without the runtime when the body is sized.
+///
+/// A body whose size is known upfront, such as `Full` or `Empty`, is normally in
+/// memory already, so reading it never waits. For such bodies the future is first
+/// polled once on the calling thread with no runtime involved, and usually completes
+/// there. Otherwise, or if that poll is still pending, it is finished with
+/// [`block_on`].
+pub(crate) fn block_on_encode<F: Future>(sized: bool, future: F) -> Result<F::Output, WireError> {
+    if !sized {
+        return block_on(future);
+    }
+
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    match future.as_mut().poll(&mut cx) {
+        Poll::Ready(output) => Ok(output),
+        Poll::Pending => block_on(future),
+    }
+}
+
+/// Returns `true` if the size of a body is known before reading it.
+pub(crate) fn is_sized<B: hyper::body::Body>(body: &B) -> bool {
+    body.is_end_stream() || body.size_hint().exact().is_some()
+}
+
+/// Collects a body whose size is known upfront into a single buffer.
+///
+/// For a single-frame body such as `Full<Bytes>` the original `Bytes` is returned
+/// without copying. Bodies of unknown size are handed back untouched in `Err`, since
+/// collecting them would change how they are framed on the wire.
+pub(crate) async fn collect_sized<B>(body: B) -> Result<Result<Bytes, B>, WireError>
+where
+    B: http_body_util::BodyExt,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    if body.size_hint().exact().is_none() {
+        return Ok(Err(body));
+    }
+    let collected = body
+        .collect()
+        .await
+        .map_err(|e| WireError::Connection(e.into()))?;
+    Ok(Ok(collected.to_bytes()))
+}
+
+/// Splits an encoded message into its head and body buffers.
+///
+/// If the body on the wire has the length of `body`, it was written verbatim with
+/// `Content-Length` framing (chunked framing always adds bytes), and `body` itself is
+/// returned in place of the slice of `message`. An empty body is omitted.
+pub(crate) fn split_vectored(
+    mut message: Bytes,
+    body: Option<Bytes>,
+) -> Result<Vec<Bytes>, WireError> {
+    let head_end = find_head_end(&message).ok_or(WireError::PartialHead)?;
+    let wire_body = message.split_off(head_end);
+    let body = match body {
+        Some(body) if body.len() == wire_body.len() => body,
+        _ => wire_body,
+    };
+
+    if body.is_empty() {
+        Ok(vec![message])
+    } else {
+        Ok(vec![message, body])
+    }
+}
 
-/// Socket wrapper that captures written bytes while simulating a real connection
-pub(crate) struct WireCapture {
-    pub(crate) inner: tokio::io::DuplexStream,
-    pub(crate) captured: Arc<Mutex<Vec<u8>>>,
+/// Serializes a request whose body is already in memory, appending it to `buf`.
+///
+/// This is the synchronous core behind [`WireEncode`](crate::WireEncode) and
+/// [`WireEncodeAsync`](crate::WireEncodeAsync): it writes the request line, the
+/// headers and the body directly, without any I/O or task spawning.
+///
+/// The request target is the URI as given. Unlike hyper's client, which always sends
+/// the origin form, a URI with a scheme and authority is written in absolute form
+/// (`GET http://example.com/ HTTP/1.1`), as forward proxies expect, and the URI `*`
+/// is kept for a server-wide `OPTIONS * HTTP/1.1`. A `CONNECT` request is written in
+/// authority form (`CONNECT example.com:443 HTTP/1.1`), without a body or any
+/// `Content-Length` and `Transfer-Encoding` headers.
+///
+/// No `Host` header is derived from the URI: requests without one, or with one that
+/// does not match the target, are written as supplied. Header names are written in
+/// lowercase, unless a [`HeaderCase`] extension records their spelling, and the body
+/// is framed the way hyper's client frames it:
+///
+/// - An empty body is written without a `Content-Length`.
+/// - A `Transfer-Encoding` header makes the body chunked (`chunked` is appended to
+///   the last coding if missing) and any `Content-Length` is dropped.
+/// - A valid `Content-Length` header is kept, and at most that many bytes of the body
+///   are written.
+/// - Otherwise a `content-length` header with the body length is appended.
+///
+/// # Errors
+///
+/// Returns [`WireError::UnsupportedVersion`] for versions other than HTTP/1.0 and
+/// HTTP/1.1.
+///
+/// # Example
+///
+/// ```rust
+/// use bytes::{Bytes, BytesMut};
+/// use http_wire::wire::serialize_request;
+///
+/// let request = http::Request::builder()
+///     .method("POST")
+///     .uri("/items")
+///     .header("Host", "example.com")
+///     .body(Bytes::from("hello"))
+///     .unwrap();
+///
+/// let mut buf = BytesMut::new();
+/// serialize_request(request, &mut buf).unwrap();
+/// assert_eq!(
+///     &buf[..],
+///     b"POST /items HTTP/1.1\r\nhost: example.com\r\ncontent-length: 5\r\n\r\nhello"
+/// );
+/// ```
+pub fn serialize_request(
+    request: http::Request<Bytes>,
+    buf: &mut BytesMut,
+) -> Result<(), WireError> {
+    let (parts, body) = request.into_parts();
+    write_request(buf, parts, BufferedBody::from_bytes(body))
 }
 
-impl WireCapture {
-    pub(crate) fn new(inner: tokio::io::DuplexStream) -> Self {
+/// Serializes a response whose body is already in memory, appending it to `buf`.
+///
+/// The response counterpart of [`serialize_request`]. The status line uses the
+/// [`hyper::ext::ReasonPhrase`] extension if set, else the canonical reason. The body
+/// is framed the way hyper's server frames it:
+///
+/// - Responses with a 1xx, 204 or 304 status are written without a body, and any
+///   `Transfer-Encoding` header is dropped.
+/// - A `Transfer-Encoding` header makes the body chunked, as for requests. In an
+///   HTTP/1.0 response it is dropped instead.
+/// - A valid `Content-Length` header is kept, and at most that many bytes of the body
+///   are written.
+/// - Otherwise a `content-length` header with the body length is appended, even for
+///   an empty body.
+///
+/// A `date` header with the current time is appended unless one is set.
+///
+/// # Errors
+///
+/// Returns [`WireError::UnsupportedVersion`] for versions other than HTTP/1.0 and
+/// HTTP/1.1.
+///
+/// # Example
+///
+/// ```rust
+/// use bytes::{Bytes, BytesMut};
+/// use http_wire::wire::serialize_response;
+///
+/// let response = http::Response::builder()
+///     .status(404)
+///     .header("Date", "Sun, 06 Nov 1994 08:49:37 GMT")
+///     .body(Bytes::from("missing"))
+///     .unwrap();
+///
+/// let mut buf = BytesMut::new();
+/// serialize_response(response, &mut buf).unwrap();
+/// assert_eq!(
+///     &buf[..],
+///     b"HTTP/1.1 404 Not Found\r\ndate: Sun, 06 Nov 1994 08:49:37 GMT\r\ncontent-length: 7\r\n\r\nmissing"
+/// );
+/// ```
+pub fn serialize_response(
+    response: http::Response<Bytes>,
+    buf: &mut BytesMut,
+) -> Result<(), WireError> {
+    let (parts, body) = response.into_parts();
+    write_response(buf, parts, BufferedBody::from_bytes(body))
+}
+
+/// Builds the wire bytes of a request without going through [`http::Request`].
+///
+/// Everything is written as given: the method, target and header names are not
+/// validated or normalized, and the headers keep their order and spelling. The only
+/// header added is `content-length`, for a non-empty body when neither
+/// `Content-Length` nor `Transfer-Encoding` is set. The body is written as is, so a
+/// chunked body must already be framed. This makes it easy to produce malformed or
+/// unusual messages to feed the decoder.
+///
+/// # Example
+///
+/// ```rust
+/// use http_wire::wire::RequestBuilder;
+///
+/// let raw = RequestBuilder::new()
+///     .method("POST")
+///     .target("/items")
+///     .header("Host", "example.com")
+///     .body("hello")
+///     .build();
+/// assert_eq!(
+///     &raw[..],
+///     b"POST /items HTTP/1.1\r\nHost: example.com\r\ncontent-length: 5\r\n\r\nhello"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct RequestBuilder {
+    method: String,
+    target: String,
+    minor: u8,
+    headers: Vec<(String, Bytes)>,
+    body: Bytes,
+}
+
+impl RequestBuilder {
+    /// Creates a builder for `GET / HTTP/1.1` with no headers and an empty body.
+    pub fn new() -> Self {
         Self {
-            inner,
-            captured: Arc::new(Mutex::new(Vec::new())),
+            method: "GET".to_owned(),
+            target: "/".to_owned(),
+            minor: 1,
+            headers: Vec::new(),
+            body: Bytes::new(),
         }
     }
+
+    /// Sets the method.
+    pub fn method(mut self, method: &str) -> Self {
+        self.method = method.to_owned();
+        self
+    }
+
+    /// Sets the request target, such as `/path?query` or `http://host/path`.
+    pub fn target(mut self, target: &str) -> Self {
+        self.target = target.to_owned();
+        self
+    }
+
+    /// Sets the minor HTTP version, as in `httparse`: `0` for HTTP/1.0, any other
+    /// value for HTTP/1.1.
+    pub fn version(mut self, minor: u8) -> Self {
+        self.minor = minor;
+        self
+    }
+
+    /// Appends a header. Repeated names are written as separate lines.
+    pub fn header(mut self, name: &str, value: impl AsRef<[u8]>) -> Self {
+        self.headers
+            .push((name.to_owned(), Bytes::copy_from_slice(value.as_ref())));
+        self
+    }
+
+    /// Sets the body.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Returns the request in wire format.
+    pub fn build(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(self.body.len() + 128);
+        write_request_line(&mut buf, &self.method, &self.target, self.minor);
+        for (name, value) in &self.headers {
+            write_header(&mut buf, name.as_bytes(), value);
+        }
+        let framed = self.headers.iter().any(|(name, _)| {
+            name.eq_ignore_ascii_case("content-length")
+                || name.eq_ignore_ascii_case("transfer-encoding")
+        });
+        if !self.body.is_empty() && !framed {
+            write_header(
+                &mut buf,
+                b"content-length",
+                self.body.len().to_string().as_bytes(),
+            );
+        }
+        buf.put_slice(b"\r\n");
+        buf.put_slice(&self.body);
+        buf.freeze()
+    }
 }
 
-impl AsyncRead for WireCapture {
-    fn poll_read(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &mut tokio::io::ReadBuf<'_>,
-    ) -> Poll<io::Result<()>> {
-        Pin::new(&mut self.inner).poll_read(cx, buf)
+impl Default for RequestBuilder {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl AsyncWrite for WireCapture {
-    fn poll_write(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-        buf: &[u8],
-    ) -> Poll<Result<usize, io::Error>> {
-        // Capture the bytes being written
-        self.captured.lock().extend_from_slice(buf);
-        Pin::new(&mut self.inner).poll_write(cx, buf)
+/// Original spellings of header names, honored when serializing.
+///
+/// [`http::HeaderMap`] stores header names in lowercase, so they are written in
+/// lowercase by default, as hyper does. Attaching a `HeaderCase` to the extensions of
+/// a request or response writes them as recorded instead: the n-th value of a header
+/// uses the n-th spelling recorded for its name, or the last one if fewer were
+/// recorded. Names without a recorded spelling stay lowercase. This also applies to
+/// the `content-length`, `transfer-encoding` and `date` headers added while framing.
+///
+/// Only this crate's serializer reads the extension, through [`serialize_request`],
+/// [`serialize_response`] and the encoding traits. It has no effect on messages
+/// written by hyper itself, for example over a [`CapturingIo`](crate::CapturingIo)
+/// connection, since hyper always lowercases names.
+///
+/// [`FullRequest::into_http`](crate::request::FullRequest::into_http) and
+/// [`FullResponse::into_http`](crate::response::FullResponse::into_http) attach one
+/// with the names as they were decoded.
+///
+/// # Example
+///
+/// ```rust
+/// use bytes::{Bytes, BytesMut};
+/// use http_wire::wire::{HeaderCase, serialize_request};
+///
+/// let mut case = HeaderCase::new();
+/// case.append("Host").unwrap();
+/// case.append("X-API-Key").unwrap();
+///
+/// let request = http::Request::builder()
+///     .uri("/")
+///     .header("host", "example.com")
+///     .header("x-api-key", "secret")
+///     .extension(case)
+///     .body(Bytes::new())
+///     .unwrap();
+///
+/// let mut buf = BytesMut::new();
+/// serialize_request(request, &mut buf).unwrap();
+/// assert_eq!(
+///     &buf[..],
+///     b"GET / HTTP/1.1\r\nHost: example.com\r\nX-API-Key: secret\r\n\r\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HeaderCase(HeaderMap<Bytes>);
+
+impl HeaderCase {
+    /// Creates an empty record, which leaves every name in lowercase.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
-        Pin::new(&mut self.inner).poll_flush(cx)
+    /// Records `name` as the spelling of the next value of its header.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WireError::InvalidHttp`] if `name` is not a valid header name.
+    pub fn append(&mut self, name: &str) -> Result<(), WireError> {
+        let key = HeaderName::from_bytes(name.as_bytes()).map_err(http::Error::from)?;
+        self.0.append(key, Bytes::copy_from_slice(name.as_bytes()));
+        Ok(())
+    }
+}
+
+/// Records the spelling and order of decoded headers, for `into_http`.
+pub(crate) fn recorded_headers(
+    headers: &[httparse::Header<'_>],
+) -> Result<(HeaderCase, HeaderOrder), WireError> {
+    let mut case = HeaderCase::new();
+    let mut order = HeaderOrder::new();
+    for header in headers {
+        case.append(header.name)?;
+        order.push(HeaderName::from_bytes(header.name.as_bytes()).map_err(http::Error::from)?);
+    }
+    Ok((case, order))
+}
+
+/// Returns how to write the name of the `index`-th value of `name`.
+fn spelling<'a>(case: Option<&'a HeaderCase>, name: &'a HeaderName, index: usize) -> &'a [u8] {
+    let recorded = case.and_then(|case| {
+        let spellings = case.0.get_all(name);
+        spellings
+            .iter()
+            .nth(index)
+            .or_else(|| spellings.iter().next_back())
+    });
+    recorded.map_or(name.as_str().as_bytes(), |spelling| spelling)
+}
+
+/// The order in which header values are written, honored when serializing.
+///
+/// [`http::HeaderMap`] keeps the values of a name together and orders names by first
+/// insertion, so headers such as `Set-Cookie` cannot be interleaved with others.
+/// Attaching a `HeaderOrder` to the extensions of a request or response writes the
+/// values in the recorded sequence of names instead: the n-th occurrence of a name
+/// writes the n-th value of that name. Values that are not covered, because the name
+/// is missing or occurs fewer times than it has values, are written afterwards in the
+/// map's order, and occurrences without a matching value are skipped.
+///
+/// The header map stays the source of truth for the framing headers, which are
+/// adjusted as usual. Like [`HeaderCase`], the extension is only read by this crate's
+/// serializer.
+///
+/// [`FullRequest::into_http`](crate::request::FullRequest::into_http) and
+/// [`FullResponse::into_http`](crate::response::FullResponse::into_http) attach one
+/// with the headers in the order they were decoded.
+///
+/// # Example
+///
+/// ```rust
+/// use bytes::{Bytes, BytesMut};
+/// use http::header::{HeaderName, HeaderValue, DATE, SET_COOKIE};
+/// use http_wire::wire::{HeaderOrder, serialize_response};
+///
+/// let mut response = http::Response::new(Bytes::new());
+/// let mut order = HeaderOrder::new();
+/// order.append_all(
+///     response.headers_mut(),
+///     [
+///         (SET_COOKIE, HeaderValue::from_static("a=1")),
+///         (HeaderName::from_static("x-id"), HeaderValue::from_static("7")),
+///         (SET_COOKIE, HeaderValue::from_static("b=2")),
+///         (DATE, HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT")),
+///     ],
+/// );
+/// response.extensions_mut().insert(order);
+///
+/// let mut buf = BytesMut::new();
+/// serialize_response(response, &mut buf).unwrap();
+/// assert_eq!(
+///     &buf[..],
+///     b"HTTP/1.1 200 OK\r\nset-cookie: a=1\r\nx-id: 7\r\nset-cookie: b=2\r\ndate: Sun, 06 Nov 1994 08:49:37 GMT\r\ncontent-length: 0\r\n\r\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct HeaderOrder(Vec<HeaderName>);
+
+impl HeaderOrder {
+    /// Creates an empty order, which leaves the map's order unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the next occurrence of `name`.
+    pub fn push(&mut self, name: HeaderName) {
+        self.0.push(name);
+    }
+
+    /// Appends `headers` to `map`, recording their order.
+    pub fn append_all<I>(&mut self, map: &mut HeaderMap, headers: I)
+    where
+        I: IntoIterator<Item = (HeaderName, HeaderValue)>,
+    {
+        for (name, value) in headers {
+            self.0.push(name.clone());
+            map.append(name, value);
+        }
+    }
+}
+
+/// How a body described its length before being read, which decides its framing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyLength {
+    /// The body was at its end upfront, like `Empty` or an empty `Full`.
+    None,
+    /// The body reported an exact size.
+    Known(u64),
+    /// The size is only known once the stream ends.
+    Unknown,
+}
+
+impl BodyLength {
+    /// Describes `body` before any of it is read.
+    fn of<B: hyper::body::Body>(body: &B) -> Self {
+        if body.is_end_stream() {
+            BodyLength::None
+        } else {
+            body.size_hint()
+                .exact()
+                .map_or(BodyLength::Unknown, BodyLength::Known)
+        }
+    }
+}
+
+/// A message body read into memory, frame by frame.
+#[derive(Debug)]
+pub(crate) struct BufferedBody {
+    length: BodyLength,
+    /// The non-empty data frames, in order.
+    chunks: Vec<Bytes>,
+    /// The trailers, if the body ended with any.
+    trailers: Option<HeaderMap>,
+}
+
+impl BufferedBody {
+    /// Wraps a body that is already in memory, described like `Full` would be.
+    fn from_bytes(body: Bytes) -> Self {
+        let (length, chunks) = if body.is_empty() {
+            (BodyLength::None, Vec::new())
+        } else {
+            (BodyLength::Known(body.len() as u64), vec![body])
+        };
+        Self {
+            length,
+            chunks,
+            trailers: None,
+        }
+    }
+
+    /// Reads every frame of `body`.
+    ///
+    /// This only polls the body, so it works on any executor. Data frames are kept
+    /// as they are, so a `Full<Bytes>` body is not copied.
+    pub(crate) async fn read<B>(body: B) -> Result<Self, WireError>
+    where
+        B: http_body_util::BodyExt,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let length = BodyLength::of(&body);
+
+        let mut body = std::pin::pin!(body);
+        let mut chunks = Vec::new();
+        let mut trailers: Option<HeaderMap> = None;
+        while let Some(frame) = http_body_util::BodyExt::frame(&mut body).await {
+            let frame = frame.map_err(|e| WireError::Connection(e.into()))?;
+            match frame.into_data() {
+                Ok(mut data) if data.has_remaining() => {
+                    chunks.push(data.copy_to_bytes(data.remaining()));
+                }
+                Ok(_) => {}
+                Err(frame) => {
+                    if let Ok(frame_trailers) = frame.into_trailers() {
+                        trailers.get_or_insert_default().extend(frame_trailers);
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            length,
+            chunks,
+            trailers,
+        })
+    }
+
+    /// Appends the body to `buf` with the given delimiting.
+    fn write(self, buf: &mut BytesMut, delimiter: Delimiter, headers: &HeaderMap) {
+        let mut encoder = BodyEncoder::new(delimiter);
+        for chunk in &self.chunks {
+            encoder.data(buf, chunk);
+        }
+        encoder.finish(buf, self.trailers.as_ref(), headers);
+    }
+}
+
+/// Frames the data of a body as it is written, one frame at a time.
+struct BodyEncoder {
+    delimiter: Delimiter,
+    /// Body bytes still allowed by a `Content-Length`.
+    remaining: usize,
+}
+
+impl BodyEncoder {
+    fn new(delimiter: Delimiter) -> Self {
+        let remaining = match delimiter {
+            Delimiter::Length(len) => usize::try_from(len).unwrap_or(usize::MAX),
+            Delimiter::Chunked | Delimiter::Close => usize::MAX,
+        };
+        Self {
+            delimiter,
+            remaining,
+        }
+    }
+
+    /// Returns `true` once a `Content-Length` body is complete, so further data would
+    /// be dropped.
+    fn is_full(&self) -> bool {
+        matches!(self.delimiter, Delimiter::Length(_)) && self.remaining == 0
+    }
+
+    /// Appends a data frame to `buf`, framed as a chunk or cut to the declared length.
+    fn data(&mut self, buf: &mut BytesMut, data: &[u8]) {
+        match self.delimiter {
+            Delimiter::Length(_) => {
+                let take = data.len().min(self.remaining);
+                buf.put_slice(&data[..take]);
+                self.remaining -= take;
+            }
+            Delimiter::Close => buf.put_slice(data),
+            Delimiter::Chunked if data.is_empty() => {}
+            Delimiter::Chunked => {
+                let _ = write!(buf, "{:X}\r\n", data.len());
+                buf.put_slice(data);
+                buf.put_slice(b"\r\n");
+            }
+        }
+    }
+
+    /// Appends the end of the body to `buf`: for chunked framing, the last chunk and
+    /// the trailers.
+    ///
+    /// Only trailers declared in the message's `Trailer` headers are written, and
+    /// never the fields that RFC 9110 §6.5.1 forbids in trailers.
+    fn finish(self, buf: &mut BytesMut, trailers: Option<&HeaderMap>, headers: &HeaderMap) {
+        if self.delimiter != Delimiter::Chunked {
+            return;
+        }
+        buf.put_slice(b"0\r\n");
+        if let Some(trailers) = trailers {
+            let declared = declared_trailers(headers);
+            for (name, value) in trailers {
+                if declared.contains(name) && is_allowed_trailer(name) {
+                    write_header(buf, name.as_str().as_bytes(), value.as_bytes());
+                }
+            }
+        }
+        buf.put_slice(b"\r\n");
+    }
+}
+
+/// How a message body is delimited on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delimiter {
+    /// `Content-Length` framing; at most this many body bytes are written.
+    Length(u64),
+    /// The chunked transfer coding.
+    Chunked,
+    /// The body runs until the connection closes (HTTP/1.0 responses only).
+    Close,
+}
+
+/// The framing of an outgoing message: how its body is delimited and which framing
+/// headers are written.
+#[derive(Debug)]
+struct Framing {
+    delimiter: Delimiter,
+    /// Whether the message's own `Content-Length` headers are written.
+    keep_length: bool,
+    /// Whether the message's own `Transfer-Encoding` headers are written.
+    keep_coding: bool,
+    /// A `content-length` to append after the message's headers.
+    add_length: Option<u64>,
+    /// Whether to append `transfer-encoding: chunked` after the message's headers.
+    add_chunked: bool,
+}
+
+impl Framing {
+    /// A message without a body whose `Content-Length`, if any, is kept.
+    const BODILESS: Framing = Framing {
+        delimiter: Delimiter::Length(0),
+        keep_length: true,
+        keep_coding: false,
+        add_length: None,
+        add_chunked: false,
+    };
+
+    /// Framing by the message's own, valid `Content-Length`.
+    fn declared(len: u64) -> Self {
+        Framing {
+            delimiter: Delimiter::Length(len),
+            ..Self::BODILESS
+        }
+    }
+
+    /// Framing by a `content-length` computed from the body.
+    fn computed(len: u64) -> Self {
+        Framing {
+            delimiter: Delimiter::Length(len),
+            keep_length: false,
+            add_length: Some(len),
+            ..Self::BODILESS
+        }
+    }
+
+    /// Chunked framing, as requested by the message's `Transfer-Encoding` or added.
+    fn chunked(added: bool) -> Self {
+        Framing {
+            delimiter: Delimiter::Chunked,
+            keep_length: false,
+            keep_coding: !added,
+            add_length: None,
+            add_chunked: added,
+        }
+    }
+}
+
+/// Decides the framing of a request, following hyper's client.
+fn request_framing(parts: &http::request::Parts, minor: u8, length: BodyLength) -> Framing {
+    // A CONNECT request has no content (RFC 9110 §9.3.6), so none is sent or declared
+    if parts.method == http::Method::CONNECT {
+        return Framing {
+            keep_length: false,
+            ..Framing::BODILESS
+        };
+    }
+    let declared = content_length(&parts.headers);
+    match length {
+        BodyLength::None => Framing::BODILESS,
+        // HTTP/1.0 has no chunked coding, and no way to frame a body of unknown size
+        _ if minor == 0 => match (declared, length) {
+            (Some(len), _) => Framing::declared(len),
+            (None, BodyLength::Known(len)) => Framing::computed(len),
+            _ => Framing::BODILESS,
+        },
+        _ if parts.headers.contains_key(TRANSFER_ENCODING) => Framing::chunked(false),
+        _ => match (declared, length) {
+            (Some(len), _) => Framing::declared(len),
+            (None, BodyLength::Known(len)) => Framing::computed(len),
+            // These methods almost never have a body, so none is sent
+            _ if matches!(parts.method, http::Method::GET | http::Method::HEAD) => {
+                Framing::BODILESS
+            }
+            _ => Framing::chunked(true),
+        },
+    }
+}
+
+/// Decides the framing of a response, following hyper's server.
+fn response_framing(parts: &http::response::Parts, minor: u8, length: BodyLength) -> Framing {
+    let status = parts.status;
+    if status.is_informational()
+        || status == http::StatusCode::NO_CONTENT
+        || status == http::StatusCode::NOT_MODIFIED
+    {
+        return Framing::BODILESS;
+    }
+    if minor == 1 && parts.headers.contains_key(TRANSFER_ENCODING) {
+        return Framing::chunked(false);
+    }
+    match (content_length(&parts.headers), length) {
+        (_, BodyLength::None) => Framing::computed(0),
+        (Some(len), _) => Framing::declared(len),
+        (None, BodyLength::Known(len)) => Framing::computed(len),
+        (None, BodyLength::Unknown) if minor == 0 => Framing {
+            delimiter: Delimiter::Close,
+            keep_length: false,
+            ..Framing::BODILESS
+        },
+        (None, BodyLength::Unknown) => Framing::chunked(true),
     }
+}
+
+/// Returns the request target for the request line.
+///
+/// CONNECT uses the authority form (`host:port`); any other method writes the URI as
+/// given, in origin, absolute or asterisk (`*`) form.
+fn request_target(parts: &http::request::Parts) -> String {
+    match parts.uri.authority() {
+        Some(authority) if parts.method == http::Method::CONNECT => authority.to_string(),
+        _ => parts.uri.to_string(),
+    }
+}
+
+/// Appends a request to `buf`, with the framing described by [`serialize_request`].
+pub(crate) fn write_request(
+    buf: &mut BytesMut,
+    parts: http::request::Parts,
+    body: BufferedBody,
+) -> Result<(), WireError> {
+    let minor = minor_version(parts.version)?;
+    let framing = request_framing(&parts, minor, body.length);
+
+    buf.reserve(256 + body.chunks.iter().map(Bytes::len).sum::<usize>());
+    write_request_line(buf, parts.method.as_str(), &request_target(&parts), minor);
+    write_headers(buf, &parts.headers, &framing, &parts.extensions);
+    buf.put_slice(b"\r\n");
+    body.write(buf, framing.delimiter, &parts.headers);
+    Ok(())
+}
+
+/// Appends a response to `buf`, with the framing described by [`serialize_response`].
+pub(crate) fn write_response(
+    buf: &mut BytesMut,
+    parts: http::response::Parts,
+    body: BufferedBody,
+) -> Result<(), WireError> {
+    let minor = minor_version(parts.version)?;
+    let framing = response_framing(&parts, minor, body.length);
+
+    buf.reserve(256 + body.chunks.iter().map(Bytes::len).sum::<usize>());
+    write_response_head(buf, &parts, minor, &framing);
+    body.write(buf, framing.delimiter, &parts.headers);
+    Ok(())
+}
+
+/// Writes a response to `writer` as its body is read, with the framing described by
+/// [`serialize_response`], and returns the number of bytes written.
+///
+/// Only one data frame is held in memory at a time. Once a body framed by a
+/// `Content-Length` is complete, the rest of it is not read.
+pub(crate) async fn stream_response<W, B>(
+    writer: &mut W,
+    response: http::Response<B>,
+) -> Result<usize, WireError>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    B: hyper::body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let (parts, body) = response.into_parts();
+    let minor = minor_version(parts.version)?;
+    let framing = response_framing(&parts, minor, BodyLength::of(&body));
+
+    let mut head = BytesMut::with_capacity(256);
+    write_response_head(&mut head, &parts, minor, &framing);
+    stream_message(writer, head, framing.delimiter, body, &parts.headers).await
+}
+
+/// Writes a request to `writer` as its body is read, like [`stream_response`], with
+/// the framing described by [`serialize_request`].
+pub(crate) async fn stream_request<W, B>(
+    writer: &mut W,
+    request: http::Request<B>,
+) -> Result<usize, WireError>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    B: hyper::body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let (parts, body) = request.into_parts();
+    let minor = minor_version(parts.version)?;
+    let framing = request_framing(&parts, minor, BodyLength::of(&body));
+
+    let mut head = BytesMut::with_capacity(256);
+    write_request_line(
+        &mut head,
+        parts.method.as_str(),
+        &request_target(&parts),
+        minor,
+    );
+    write_headers(&mut head, &parts.headers, &framing, &parts.extensions);
+    head.put_slice(b"\r\n");
+    stream_message(writer, head, framing.delimiter, body, &parts.headers).await
+}
+
+/// Writes a message head, then its body one data frame at a time, and returns the
+/// number of bytes written.
+async fn stream_message<W, B>(
+    writer: &mut W,
+    mut buf: BytesMut,
+    delimiter: Delimiter,
+    body: B,
+    headers: &HeaderMap,
+) -> Result<usize, WireError>
+where
+    W: tokio::io::AsyncWrite + Unpin,
+    B: hyper::body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let io_err = |e: std::io::Error| WireError::Connection(Box::new(e));
+    let mut written = buf.len();
+    writer.write_all(&buf).await.map_err(io_err)?;
+
+    let mut encoder = BodyEncoder::new(delimiter);
+    let mut trailers: Option<HeaderMap> = None;
+    let mut body = std::pin::pin!(body);
+    while !encoder.is_full() {
+        let Some(frame) = http_body_util::BodyExt::frame(&mut body).await else {
+            break;
+        };
+        let frame = frame.map_err(|e| WireError::Connection(e.into()))?;
+        match frame.into_data() {
+            Ok(mut data) => {
+                buf.clear();
+                encoder.data(&mut buf, &data.copy_to_bytes(data.remaining()));
+                written += buf.len();
+                writer.write_all(&buf).await.map_err(io_err)?;
+            }
+            Err(frame) => {
+                if let Ok(frame_trailers) = frame.into_trailers() {
+                    trailers.get_or_insert_default().extend(frame_trailers);
+                }
+            }
+        }
+    }
+
+    buf.clear();
+    encoder.finish(&mut buf, trailers.as_ref(), headers);
+    written += buf.len();
+    writer.write_all(&buf).await.map_err(io_err)?;
+    writer.flush().await.map_err(io_err)?;
+    Ok(written)
+}
+
+/// Appends the status line and headers of a response, with the blank line.
+fn write_response_head(
+    buf: &mut BytesMut,
+    parts: &http::response::Parts,
+    minor: u8,
+    framing: &Framing,
+) {
+    buf.put_slice(version_token(minor));
+    buf.put_u8(b' ');
+    buf.put_slice(parts.status.as_str().as_bytes());
+    buf.put_u8(b' ');
+    match parts.extensions.get::<hyper::ext::ReasonPhrase>() {
+        Some(reason) => buf.put_slice(reason.as_bytes()),
+        None => buf.put_slice(
+            parts
+                .status
+                .canonical_reason()
+                .unwrap_or("<none>")
+                .as_bytes(),
+        ),
+    }
+    buf.put_slice(b"\r\n");
+
+    write_headers(buf, &parts.headers, framing, &parts.extensions);
+    if !parts.headers.contains_key(DATE) {
+        let date = httpdate::fmt_http_date(SystemTime::now());
+        let case = parts.extensions.get::<HeaderCase>();
+        write_header(buf, spelling(case, &DATE, 0), date.as_bytes());
+    }
+    buf.put_slice(b"\r\n");
+}
+
+/// Writes the header lines of a message, adjusting its framing headers.
+fn write_headers(
+    buf: &mut BytesMut,
+    headers: &HeaderMap,
+    framing: &Framing,
+    extensions: &http::Extensions,
+) {
+    let case = extensions.get::<HeaderCase>();
+    let last_coding = headers.get_all(TRANSFER_ENCODING).iter().next_back();
+
+    for (name, index, value) in ordered_values(headers, extensions.get::<HeaderOrder>()) {
+        if *name == CONTENT_LENGTH && !framing.keep_length {
+            continue;
+        }
+        if *name == TRANSFER_ENCODING {
+            if !framing.keep_coding {
+                continue;
+            }
+            // `chunked` must be the final coding, so add it to the last value if missing
+            let is_last = last_coding.is_some_and(|last| std::ptr::eq(last, value));
+            if is_last && !is_chunked_slice(value.as_bytes()) {
+                buf.put_slice(spelling(case, name, index));
+                buf.put_slice(b": ");
+                buf.put_slice(value.as_bytes());
+                buf.put_slice(b", chunked\r\n");
+                continue;
+            }
+        }
+        write_header(buf, spelling(case, name, index), value.as_bytes());
+    }
+
+    if let Some(len) = framing.add_length {
+        write_header(
+            buf,
+            spelling(case, &CONTENT_LENGTH, 0),
+            len.to_string().as_bytes(),
+        );
+    }
+    if framing.add_chunked {
+        write_header(buf, spelling(case, &TRANSFER_ENCODING, 0), b"chunked");
+    }
+}
+
+/// Lists the header values in the order they are written, each with its index among
+/// the values of its name.
+///
+/// Without a [`HeaderOrder`] this is the map's own order. With one, the listed values
+/// come first, in the recorded order, followed by the values it does not cover.
+fn ordered_values<'a>(
+    headers: &'a HeaderMap,
+    order: Option<&'a HeaderOrder>,
+) -> Vec<(&'a HeaderName, usize, &'a HeaderValue)> {
+    let mut values = Vec::with_capacity(headers.len());
+    // Number of values of each name already listed
+    let mut listed: HashMap<&HeaderName, usize> = HashMap::new();
+
+    for name in order.map_or(&[][..], |order| &order.0) {
+        let index = listed.entry(name).or_default();
+        if let Some(value) = headers.get_all(name).iter().nth(*index) {
+            values.push((name, *index, value));
+            *index += 1;
+        }
+    }
+
+    // Values of the same name are yielded together, so counting the repeats of the
+    // previous name gives the index of each value
+    let mut previous: Option<(&HeaderName, usize)> = None;
+    for (name, value) in headers {
+        let index = match previous {
+            Some((prev, index)) if prev == name => index + 1,
+            _ => 0,
+        };
+        previous = Some((name, index));
+        if index >= listed.get(name).copied().unwrap_or(0) {
+            values.push((name, index, value));
+        }
+    }
+    values
+}
+
+/// Parses the `Content-Length` of a message.
+///
+/// Returns `None` if there is none, or if any value is not a plain number or
+/// disagrees with the others.
+fn content_length(headers: &HeaderMap) -> Option<u64> {
+    let mut length = None;
+    for value in headers.get_all(CONTENT_LENGTH) {
+        for item in value.to_str().ok()?.split(',') {
+            let item = item.trim();
+            if item.is_empty() || !item.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let n: u64 = item.parse().ok()?;
+            if length.is_some_and(|len| len != n) {
+                return None;
+            }
+            length = Some(n);
+        }
+    }
+    length
+}
+
+/// Returns the field names listed in the `Trailer` headers of a message.
+fn declared_trailers(headers: &HeaderMap) -> Vec<HeaderName> {
+    headers
+        .get_all(TRAILER)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| HeaderName::from_bytes(name.trim().as_bytes()).ok())
+        .collect()
+}
+
+/// Returns `false` for fields that must not be sent as trailers.
+fn is_allowed_trailer(name: &HeaderName) -> bool {
+    use http::header::{
+        AUTHORIZATION, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_RANGE, CONTENT_TYPE, HOST,
+        MAX_FORWARDS, SET_COOKIE, TE,
+    };
+
+    !matches!(
+        *name,
+        AUTHORIZATION
+            | CACHE_CONTROL
+            | CONTENT_ENCODING
+            | CONTENT_LENGTH
+            | CONTENT_RANGE
+            | CONTENT_TYPE
+            | HOST
+            | MAX_FORWARDS
+            | SET_COOKIE
+            | TRAILER
+            | TRANSFER_ENCODING
+            | TE
+    )
+}
+
+/// Returns the `httparse` minor version number of an HTTP/1.x version.
+///
+/// Only HTTP/1.0 and HTTP/1.1 are supported.
+#[inline]
+fn minor_version(version: http::Version) -> Result<u8, WireError> {
+    match version {
+        http::Version::HTTP_10 => Ok(0),
+        http::Version::HTTP_11 => Ok(1),
+        _ => Err(WireError::UnsupportedVersion),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WireDecode;
+    use crate::request::FullRequest;
+    use http_body_util::StreamBody;
+    use hyper::body::Frame;
+    use std::convert::Infallible;
+
+    const DATE_VALUE: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+    const HTTP_10: http::Version = http::Version::HTTP_10;
+    const HTTP_11: http::Version = http::Version::HTTP_11;
+
+    fn streaming(frames: Vec<Frame<Bytes>>) -> BufferedBody {
+        let body = StreamBody::new(futures::stream::iter(
+            frames.into_iter().map(Ok::<_, Infallible>),
+        ));
+        futures::executor::block_on(BufferedBody::read(body)).unwrap()
+    }
+
+    fn trailers(pairs: &[(&'static str, &'static str)]) -> Frame<Bytes> {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.append(*name, value.parse().unwrap());
+        }
+        Frame::trailers(map)
+    }
+
+    fn request(
+        method: &str,
+        version: http::Version,
+        headers: &[(&str, &str)],
+    ) -> http::request::Parts {
+        let mut builder = http::Request::builder()
+            .method(method)
+            .uri("/p")
+            .version(version);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(()).unwrap().into_parts().0
+    }
+
+    fn response(
+        status: u16,
+        version: http::Version,
+        headers: &[(&str, &str)],
+    ) -> http::response::Parts {
+        let mut builder = http::Response::builder()
+            .status(status)
+            .version(version)
+            .header(DATE, DATE_VALUE);
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        builder.body(()).unwrap().into_parts().0
+    }
+
+    #[test]
+    fn test_serialize_request_framing() {
+        let cases: [(http::request::Parts, &str, &str); 6] = [
+            (
+                request("POST", HTTP_11, &[("Host", "x")]),
+                "abc",
+                "POST /p HTTP/1.1\r\nhost: x\r\ncontent-length: 3\r\n\r\nabc",
+            ),
+            // A declared length is kept, and bounds the body
+            (
+                request("POST", HTTP_11, &[("Content-Length", "3"), ("X", "y")]),
+                "abcdef",
+                "POST /p HTTP/1.1\r\ncontent-length: 3\r\nx: y\r\n\r\nabc",
+            ),
+            // Transfer-Encoding wins over Content-Length, and ends in chunked
+            (
+                request(
+                    "POST",
+                    HTTP_11,
+                    &[("Transfer-Encoding", "gzip"), ("Content-Length", "3")],
+                ),
+                "ab",
+                "POST /p HTTP/1.1\r\ntransfer-encoding: gzip, chunked\r\n\r\n2\r\nab\r\n0\r\n\r\n",
+            ),
+            // An empty body drops Transfer-Encoding but keeps Content-Length
+            (
+                request(
+                    "POST",
+                    HTTP_11,
+                    &[("Content-Length", "5"), ("Transfer-Encoding", "chunked")],
+                ),
+                "",
+                "POST /p HTTP/1.1\r\ncontent-length: 5\r\n\r\n",
+            ),
+            // HTTP/1.0 has no chunked coding
+            (
+                request("POST", HTTP_10, &[("Transfer-Encoding", "chunked")]),
+                "hi",
+                "POST /p HTTP/1.0\r\ncontent-length: 2\r\n\r\nhi",
+            ),
+            (request("GET", HTTP_11, &[]), "", "GET /p HTTP/1.1\r\n\r\n"),
+        ];
+
+        for (parts, body, expected) in cases {
+            let mut buf = BytesMut::new();
+            serialize_request(
+                http::Request::from_parts(parts, Bytes::from(body)),
+                &mut buf,
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8_lossy(&buf), expected);
+        }
+    }
+
+    #[test]
+    fn test_write_request_streaming_body() {
+        let frames = || {
+            vec![
+                Frame::data(Bytes::from("ab")),
+                Frame::data(Bytes::new()),
+                Frame::data(Bytes::from("cdefghijklmnopq")),
+                trailers(&[("x-checksum", "1"), ("x-other", "2"), ("host", "h")]),
+            ]
+        };
+        let cases = [
+            (
+                request("POST", HTTP_11, &[]),
+                "POST /p HTTP/1.1\r\ntransfer-encoding: chunked\r\n\r\n2\r\nab\r\nF\r\ncdefghijklmnopq\r\n0\r\n\r\n",
+            ),
+            // Only declared trailers that may be sent as trailers are written
+            (
+                request("PUT", HTTP_11, &[("Trailer", "X-Checksum, Host")]),
+                "PUT /p HTTP/1.1\r\ntrailer: X-Checksum, Host\r\ntransfer-encoding: chunked\r\n\r\n2\r\nab\r\nF\r\ncdefghijklmnopq\r\n0\r\nx-checksum: 1\r\n\r\n",
+            ),
+            // A GET or an HTTP/1.0 request cannot carry a body of unknown size
+            (request("GET", HTTP_11, &[]), "GET /p HTTP/1.1\r\n\r\n"),
+            (request("POST", HTTP_10, &[]), "POST /p HTTP/1.0\r\n\r\n"),
+        ];
+
+        for (parts, expected) in cases {
+            let mut buf = BytesMut::new();
+            write_request(&mut buf, parts, streaming(frames())).unwrap();
+            assert_eq!(String::from_utf8_lossy(&buf), expected);
+        }
+    }
+
+    #[test]
+    fn test_serialize_response_framing() {
+        let cases: [(http::response::Parts, &str, &str); 6] = [
+            (
+                response(200, HTTP_11, &[("Content-Length", "10"), ("X", "y")]),
+                "",
+                "HTTP/1.1 200 OK\r\ndate: D\r\nx: y\r\ncontent-length: 0\r\n\r\n",
+            ),
+            (
+                response(200, HTTP_11, &[("Content-Length", "bad")]),
+                "abc",
+                "HTTP/1.1 200 OK\r\ndate: D\r\ncontent-length: 3\r\n\r\nabc",
+            ),
+            (
+                response(
+                    200,
+                    HTTP_11,
+                    &[("Transfer-Encoding", "chunked"), ("Content-Length", "3")],
+                ),
+                "abc",
+                "HTTP/1.1 200 OK\r\ndate: D\r\ntransfer-encoding: chunked\r\n\r\n3\r\nabc\r\n0\r\n\r\n",
+            ),
+            // Bodiless statuses keep a declared Content-Length but never send a body
+            (
+                response(204, HTTP_11, &[("Content-Length", "3")]),
+                "abc",
+                "HTTP/1.1 204 No Content\r\ndate: D\r\ncontent-length: 3\r\n\r\n",
+            ),
+            (
+                response(304, HTTP_11, &[("Transfer-Encoding", "chunked")]),
+                "abc",
+                "HTTP/1.1 304 Not Modified\r\ndate: D\r\n\r\n",
+            ),
+            (
+                response(103, HTTP_11, &[("Link", "</style.css>; rel=preload")]),
+                "",
+                "HTTP/1.1 103 Early Hints\r\ndate: D\r\nlink: </style.css>; rel=preload\r\n\r\n",
+            ),
+        ];
+
+        for (parts, body, expected) in cases {
+            let mut buf = BytesMut::new();
+            serialize_response(
+                http::Response::from_parts(parts, Bytes::from(body)),
+                &mut buf,
+            )
+            .unwrap();
+            assert_eq!(
+                String::from_utf8_lossy(&buf).replace(DATE_VALUE, "D"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_response_streaming_body() {
+        let frames = || {
+            vec![
+                Frame::data(Bytes::from("ab")),
+                Frame::data(Bytes::from("cd")),
+                trailers(&[("grpc-status", "0")]),
+            ]
+        };
+        let cases = [
+            (
+                response(200, HTTP_11, &[("Trailer", "grpc-status")]),
+                "HTTP/1.1 200 OK\r\ndate: D\r\ntrailer: grpc-status\r\ntransfer-encoding: chunked\r\n\r\n2\r\nab\r\n2\r\ncd\r\n0\r\ngrpc-status: 0\r\n\r\n",
+            ),
+            (
+                response(200, HTTP_11, &[("Content-Length", "3")]),
+                "HTTP/1.1 200 OK\r\ndate: D\r\ncontent-length: 3\r\n\r\nabc",
+            ),
+            // An HTTP/1.0 response without a length is delimited by closing the connection
+            (
+                response(200, HTTP_10, &[("Transfer-Encoding", "chunked")]),
+                "HTTP/1.0 200 OK\r\ndate: D\r\n\r\nabcd",
+            ),
+        ];
+
+        for (parts, expected) in cases {
+            let mut buf = BytesMut::new();
+            write_response(&mut buf, parts, streaming(frames())).unwrap();
+            assert_eq!(
+                String::from_utf8_lossy(&buf).replace(DATE_VALUE, "D"),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialize_request_target_forms() {
+        for (uri, line) in [
+            ("/a?b=1", "GET /a?b=1 HTTP/1.1\r\n"),
+            (
+                "http://example.com:8080/a?b=1",
+                "GET http://example.com:8080/a?b=1 HTTP/1.1\r\n",
+            ),
+            ("http://example.com", "GET http://example.com/ HTTP/1.1\r\n"),
+        ] {
+            let request = http::Request::builder()
+                .uri(uri)
+                .body(Bytes::new())
+                .unwrap();
+            let mut buf = BytesMut::new();
+            serialize_request(request, &mut buf).unwrap();
+            assert!(buf.starts_with(line.as_bytes()), "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_serialize_connect() {
+        for uri in ["example.com:443", "https://example.com:443/ignored"] {
+            let request = http::Request::builder()
+                .method(http::Method::CONNECT)
+                .uri(uri)
+                .header("Host", "example.com:443")
+                .header("Content-Length", "4")
+                .body(Bytes::from("data"))
+                .unwrap();
+            let mut buf = BytesMut::new();
+            serialize_request(request, &mut buf).unwrap();
+            assert_eq!(
+                &buf[..],
+                b"CONNECT example.com:443 HTTP/1.1\r\nhost: example.com:443\r\n\r\n"
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialize_options_asterisk() {
+        let request = http::Request::builder()
+            .method(http::Method::OPTIONS)
+            .uri("*")
+            .header("Host", "example.com")
+            .body(Bytes::new())
+            .unwrap();
+        let mut buf = BytesMut::new();
+        serialize_request(request, &mut buf).unwrap();
+        assert_eq!(&buf[..], b"OPTIONS * HTTP/1.1\r\nhost: example.com\r\n\r\n");
+    }
+
+    #[test]
+    fn test_serialize_no_host_added() {
+        // Neither a missing Host nor one that disagrees with the URI is fixed up
+        for (host, expected) in [
+            (None, "GET http://example.com/ HTTP/1.1\r\n\r\n"),
+            (
+                Some("other.example"),
+                "GET http://example.com/ HTTP/1.1\r\nhost: other.example\r\n\r\n",
+            ),
+        ] {
+            let mut builder = http::Request::builder().uri("http://example.com/");
+            if let Some(host) = host {
+                builder = builder.header("Host", host);
+            }
+            let mut buf = BytesMut::new();
+            serialize_request(builder.body(Bytes::new()).unwrap(), &mut buf).unwrap();
+            assert_eq!(String::from_utf8_lossy(&buf), expected);
+        }
+    }
+
+    #[test]
+    fn test_request_builder_feeds_decoder() {
+        let raw = RequestBuilder::new()
+            .method("PUT")
+            .target("/items/1")
+            .version(0)
+            .header("Host", "example.com")
+            .header("X-Tag", "a")
+            .header("x-tag", "b")
+            .body("data")
+            .build();
+        assert_eq!(
+            &raw[..],
+            b"PUT /items/1 HTTP/1.0\r\nHost: example.com\r\nX-Tag: a\r\nx-tag: b\r\ncontent-length: 4\r\n\r\ndata"
+        );
+
+        let mut headers = [httparse::EMPTY_HEADER; 8];
+        let (request, len) = FullRequest::decode(&raw, &mut headers).unwrap();
+        assert_eq!(len, raw.len());
+        assert_eq!(request.head.method, Some("PUT"));
+        assert_eq!(request.body, b"data");
+
+        // A framing header set by the caller is left alone
+        let raw = RequestBuilder::default()
+            .method("POST")
+            .header("Transfer-Encoding", "chunked")
+            .body("4\r\ndata\r\n0\r\n\r\n")
+            .build();
+        assert_eq!(
+            &raw[..],
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n4\r\ndata\r\n0\r\n\r\n"
+        );
+        let (request, _) = FullRequest::decode(&raw, &mut headers).unwrap();
+        assert_eq!(request.body_chunks().unwrap(), [&b"data"[..]]);
+
+        assert_eq!(
+            &RequestBuilder::new().build()[..],
+            b"GET / HTTP/1.1\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn test_serialize_unsupported_version() {
+        let request = http::Request::builder()
+            .version(http::Version::HTTP_2)
+            .body(Bytes::new())
+            .unwrap();
+        assert!(matches!(
+            serialize_request(request, &mut BytesMut::new()),
+            Err(WireError::UnsupportedVersion)
+        ));
+    }
+
+    #[test]
+    fn test_serialize_header_case() {
+        let mut case = HeaderCase::new();
+        for name in ["Set-Cookie", "SET-COOKIE", "Content-Length", "DATE"] {
+            case.append(name).unwrap();
+        }
+        assert!(matches!(
+            case.append("Bad Name"),
+            Err(WireError::InvalidHttp(_))
+        ));
+
+        let response = http::Response::builder()
+            .header("set-cookie", "a=1")
+            .header("set-cookie", "b=2")
+            .header("set-cookie", "c=3")
+            .header("x-other", "1")
+            .extension(case)
+            .body(Bytes::from("hi"))
+            .unwrap();
+        let mut buf = BytesMut::new();
+        serialize_response(response, &mut buf).unwrap();
+
+        let head = String::from_utf8(buf.to_vec()).unwrap();
+        assert!(head.starts_with(
+            "HTTP/1.1 200 OK\r\nSet-Cookie: a=1\r\nSET-COOKIE: b=2\r\nSET-COOKIE: c=3\r\nx-other: 1\r\nContent-Length: 2\r\nDATE: "
+        ));
+    }
+
+    #[test]
+    fn test_serialize_header_order() {
+        use http::header::{HOST, SET_COOKIE, USER_AGENT};
+
+        let mut request = http::Request::new(Bytes::from("hi"));
+        *request.method_mut() = http::Method::POST;
+        let headers = request.headers_mut();
+        headers.append(SET_COOKIE, HeaderValue::from_static("a"));
+        headers.append(SET_COOKIE, HeaderValue::from_static("b"));
+        headers.append(SET_COOKIE, HeaderValue::from_static("c"));
+        headers.append(HOST, HeaderValue::from_static("x"));
+        headers.append(USER_AGENT, HeaderValue::from_static("curl"));
+
+        // Names without values are skipped, uncovered values follow in map order
+        let mut order = HeaderOrder::new();
+        for name in [
+            USER_AGENT,
+            SET_COOKIE,
+            HeaderName::from_static("x-none"),
+            HOST,
+        ] {
+            order.push(name);
+        }
+        request.extensions_mut().insert(order);
+
+        let mut buf = BytesMut::new();
+        serialize_request(request, &mut buf).unwrap();
+        assert_eq!(
+            &buf[..],
+            b"POST / HTTP/1.1\r\nuser-agent: curl\r\nset-cookie: a\r\nhost: x\r\nset-cookie: b\r\nset-cookie: c\r\ncontent-length: 2\r\n\r\nhi"
+        );
+    }
+
+    #[test]
+    fn test_block_on_encode_falls_back_when_pending() {
+        assert_eq!(block_on_encode(true, async { 1 }).unwrap(), 1);
 
-    fn poll_shutdown(
-        mut self: Pin<&mut Self>,
-        cx: &mut Context<'_>,
-    ) -> Poll<Result<(), io::Error>> {
-        Pin::new(&mut self.inner).poll_shutdown(cx)
+        let mut polled = false;
+        let pending_once = std::future::poll_fn(|cx| {
+            if polled {
+                Poll::Ready(2)
+            } else {
+                polled = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        });
+        assert_eq!(block_on_encode(true, pending_once).unwrap(), 2);
     }
 }